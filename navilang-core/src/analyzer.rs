@@ -1,11 +0,0 @@
-// Analyzer module - Semantic Analysis Stage
-// This module will implement context resolution, type checking, flow validation, and dependency analysis
-
-// Placeholder for now
-pub struct SemanticAnalyzer;
-
-impl SemanticAnalyzer {
-    pub fn new() -> Self {
-        Self
-    }
-}
@@ -0,0 +1,209 @@
+// Attribute validation - the `[key=value]` pairs each statement kind accepts
+use crate::error::{ErrorCollector, NaviLangError, Result};
+use crate::parser::ast::{Program, Statement, StatementKind, Value, Visitor, walk_program};
+
+/// Values an attribute accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeType {
+    Number,
+    Boolean,
+    String,
+    Duration,
+    /// One of the listed words, written bare or quoted
+    OneOf(&'static [&'static str]),
+}
+
+impl AttributeType {
+    pub fn accepts(self, value: &Value) -> bool {
+        match self {
+            AttributeType::Number => matches!(value, Value::Number(_) | Value::Float(_)),
+            AttributeType::Boolean => matches!(value, Value::Boolean(_)),
+            AttributeType::String => matches!(value, Value::String(_)),
+            AttributeType::Duration => matches!(value, Value::Duration(_)),
+            AttributeType::OneOf(words) => match value {
+                Value::Identifier(word) | Value::String(word) => words.contains(&word.as_str()),
+                _ => false,
+            },
+        }
+    }
+
+    fn describe(self) -> String {
+        match self {
+            AttributeType::Number => "a number".to_string(),
+            AttributeType::Boolean => "a boolean".to_string(),
+            AttributeType::String => "a string".to_string(),
+            AttributeType::Duration => "a duration".to_string(),
+            AttributeType::OneOf(words) => format!("one of {}", words.join(", ")),
+        }
+    }
+}
+
+/// An attribute key and the values it accepts
+#[derive(Debug)]
+pub struct AttributeRule {
+    pub key: &'static str,
+    pub value: AttributeType,
+}
+
+const PRIORITY: AttributeRule = AttributeRule {
+    key: "priority",
+    value: AttributeType::OneOf(&["high", "medium", "low"]),
+};
+
+const FLOW: &[AttributeRule] = &[PRIORITY, AttributeRule { key: "weight", value: AttributeType::Number }];
+
+const INTERACTION: &[AttributeRule] = &[
+    PRIORITY,
+    AttributeRule { key: "retry", value: AttributeType::Number },
+    AttributeRule { key: "timeout", value: AttributeType::Duration },
+];
+
+const ACTION: &[AttributeRule] = &[PRIORITY, AttributeRule { key: "owner", value: AttributeType::String }];
+
+const ENDPOINT: &[AttributeRule] = &[
+    AttributeRule { key: "auth", value: AttributeType::Boolean },
+    AttributeRule { key: "deprecated", value: AttributeType::Boolean },
+];
+
+/// Attributes accepted by each kind of statement, checked by [`check_attributes`]
+///
+/// | statement   | key        | value               |
+/// |-------------|------------|---------------------|
+/// | flow        | priority   | high, medium or low |
+/// | flow        | weight     | number              |
+/// | interaction | priority   | high, medium or low |
+/// | interaction | retry      | number              |
+/// | interaction | timeout    | duration            |
+/// | action      | priority   | high, medium or low |
+/// | action      | owner      | string              |
+/// | endpoint    | auth       | boolean             |
+/// | endpoint    | deprecated | boolean             |
+///
+/// Other statements take no attributes. Add a row here to accept a new key.
+pub fn schema(kind: &StatementKind) -> &'static [AttributeRule] {
+    match kind {
+        StatementKind::Flow { .. } => FLOW,
+        StatementKind::Interaction { .. } => INTERACTION,
+        StatementKind::Action { .. } => ACTION,
+        StatementKind::Endpoint { .. } => ENDPOINT,
+        _ => &[],
+    }
+}
+
+/// Report attributes that the statement's kind doesn't accept, values of the
+/// wrong type and keys given twice, each pointing at the attribute
+pub fn check_attributes(program: &Program, src: &str) -> Result<()> {
+    let mut check = AttributeCheck { src, errors: ErrorCollector::new() };
+    walk_program(&mut check, program);
+    check.errors.into_result(())
+}
+
+struct AttributeCheck<'a> {
+    src: &'a str,
+    errors: ErrorCollector,
+}
+
+impl<'ast> Visitor<'ast> for AttributeCheck<'_> {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        let rules = schema(&stmt.kind);
+
+        for (i, attribute) in stmt.attributes.iter().enumerate() {
+            let error = if stmt.attributes[..i].iter().any(|earlier| earlier.key == attribute.key) {
+                let message = format!("Duplicate attribute '{}'", attribute.key);
+                NaviLangError::semantic_error(message, self.src.to_string(), attribute.span.clone())
+            } else {
+                match rules.iter().find(|rule| rule.key == attribute.key) {
+                    Some(rule) if rule.value.accepts(&attribute.value) => continue,
+                    Some(rule) => NaviLangError::type_error(
+                        format!("{} for attribute '{}'", rule.value.describe(), rule.key),
+                        attribute.value.to_string(),
+                        self.src.to_string(),
+                        attribute.span.clone(),
+                    ),
+                    None => {
+                        let expected = match rules {
+                            [] => "this statement takes no attributes".to_string(),
+                            rules => {
+                                let keys: Vec<&str> = rules.iter().map(|rule| rule.key).collect();
+                                format!("expected one of {}", keys.join(", "))
+                            }
+                        };
+                        let message = format!("Unknown attribute '{}'; {}", attribute.key, expected);
+                        NaviLangError::semantic_error(message, self.src.to_string(), attribute.span.clone())
+                    }
+                }
+            };
+            self.errors.add_error(error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    fn check(input: &str) -> Result<()> {
+        check_attributes(&parse_program(input), input)
+    }
+
+    #[test]
+    fn test_valid_attributes_pass() {
+        let input = r#"CONTEXT Shop {
+    User -> Checkout [priority=high, weight=2]
+    Api CALLS Payments [retry=3, timeout=5s, priority="low"]
+    ENDPOINT GET "/cart" [auth=true]
+}"#;
+        assert!(check(input).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_attribute_is_reported() {
+        let input = "CONTEXT Shop { User -> Checkout [colour=red] }";
+
+        match check(input) {
+            Err(NaviLangError::SemanticError { message, span, .. }) => {
+                assert_eq!(message, "Unknown attribute 'colour'; expected one of priority, weight");
+                assert_eq!(span.offset(), input.find("colour").unwrap());
+                assert_eq!(span.len(), "colour=red".len());
+            }
+            other => panic!("Expected SemanticError, got {:?}", other),
+        }
+
+        let input = "CONTEXT Shop { LOOP { BREAK } [priority=high] }";
+        let Err(NaviLangError::SemanticError { message, .. }) = check(input) else {
+            panic!("Expected SemanticError");
+        };
+        assert_eq!(message, "Unknown attribute 'priority'; this statement takes no attributes");
+    }
+
+    #[test]
+    fn test_wrong_typed_attribute_is_reported() {
+        let input = r#"CONTEXT Shop { Api CALLS Payments [retry="three"] }"#;
+
+        match check(input) {
+            Err(NaviLangError::TypeError { expected, found, span, .. }) => {
+                assert_eq!(expected, "a number for attribute 'retry'");
+                assert_eq!(found, "\"three\"");
+                assert_eq!(span.offset(), input.find("retry").unwrap());
+            }
+            other => panic!("Expected TypeError, got {:?}", other),
+        }
+
+        let input = "CONTEXT Shop { User DOES Checkout [priority=urgent] }";
+        let Err(NaviLangError::TypeError { expected, .. }) = check(input) else {
+            panic!("Expected TypeError");
+        };
+        assert_eq!(expected, "one of high, medium, low for attribute 'priority'");
+    }
+
+    #[test]
+    fn test_duplicate_attribute_is_reported() {
+        let input = "CONTEXT Shop { User -> Checkout [weight=1, weight=2] }";
+        let Err(NaviLangError::SemanticError { message, span, .. }) = check(input) else {
+            panic!("Expected SemanticError");
+        };
+        assert_eq!(message, "Duplicate attribute 'weight'");
+        assert_eq!(span.offset(), input.rfind("weight").unwrap());
+    }
+}
@@ -0,0 +1,617 @@
+// Flow graph analysis - cycles and reachability over GOES TO / CALLS edges,
+// and cycles over USES dependencies
+use super::resolve::{declarations, starts};
+use crate::error::{ErrorCollector, NaviLangError, Result};
+use crate::parser::ast::{Context, InteractionVerb, Program, Statement, StatementKind, Visitor, walk_program, walk_statement};
+use petgraph::algo::{has_path_connecting, tarjan_scc};
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::Bfs;
+use std::collections::{HashMap, HashSet};
+
+/// Directed graph of the identifiers a program moves between
+///
+/// Edges come from `GOES TO` flows and `CALLS` interactions. `RECEIVES` and
+/// `RETURNS` describe data rather than control, so they add no edges.
+pub struct FlowGraph {
+    pub graph: DiGraph<String, ()>,
+    nodes: HashMap<String, NodeIndex>,
+}
+
+impl FlowGraph {
+    /// Build the graph for a whole program
+    ///
+    /// With `include_loops` false, edges inside `LOOP`/`WHILE` bodies are
+    /// left out, since cycles there are intentional.
+    pub fn build(program: &Program, include_loops: bool) -> Self {
+        let mut builder = Builder::new(include_loops);
+        walk_program(&mut builder, program);
+        builder.graph
+    }
+
+    /// Build the graph for a block of statements, as [`build`](Self::build) does
+    fn of_statements(statements: &[Statement], include_loops: bool) -> Self {
+        let mut builder = Builder::new(include_loops);
+        for stmt in statements {
+            walk_statement(&mut builder, stmt);
+        }
+        builder.graph
+    }
+
+    pub fn node(&self, name: &str) -> Option<NodeIndex> {
+        self.nodes.get(name).copied()
+    }
+
+    fn add_node(&mut self, name: &str) -> NodeIndex {
+        if let Some(&index) = self.nodes.get(name) {
+            return index;
+        }
+        let index = self.graph.add_node(name.to_string());
+        self.nodes.insert(name.to_string(), index);
+        index
+    }
+
+    fn add_edge(&mut self, from: &str, to: &str) {
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+        self.graph.update_edge(from, to, ());
+    }
+}
+
+/// Adds the edges of each statement to a [`FlowGraph`]
+struct Builder {
+    graph: FlowGraph,
+    include_loops: bool,
+    /// `LOOP`/`WHILE` bodies enclosing the statement being visited
+    loop_depth: usize,
+}
+
+impl Builder {
+    fn new(include_loops: bool) -> Self {
+        Self {
+            graph: FlowGraph { graph: DiGraph::new(), nodes: HashMap::new() },
+            include_loops,
+            loop_depth: 0,
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for Builder {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        if self.loop_depth == 0 || self.include_loops {
+            match &stmt.kind {
+                StatementKind::Flow { from, to, .. } => self.graph.add_edge(from, to),
+                StatementKind::Interaction { subject, verb: InteractionVerb::Calls, object, .. } => {
+                    if let Some(object) = object.as_identifier() {
+                        self.graph.add_edge(subject, object);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if matches!(stmt.kind, StatementKind::Loop { .. }) {
+            self.loop_depth += 1;
+        }
+    }
+
+    fn leave_statement(&mut self, stmt: &'ast Statement) {
+        if matches!(stmt.kind, StatementKind::Loop { .. }) {
+            self.loop_depth -= 1;
+        }
+    }
+}
+
+/// Find the cycles formed by flow edges outside of loop bodies
+///
+/// Each cycle is a strongly connected component, listed with its nodes in the
+/// order they first appear in the source; cycles are ordered the same way.
+pub fn find_cycles(program: &Program) -> Vec<Vec<String>> {
+    cycles(&FlowGraph::build(program, false).graph)
+}
+
+/// Strongly connected components that form a cycle, with nodes and
+/// components in the order their nodes were added to the graph
+fn cycles(graph: &DiGraph<String, ()>) -> Vec<Vec<String>> {
+    let mut components: Vec<Vec<NodeIndex>> = tarjan_scc(graph)
+        .into_iter()
+        .filter(|component| {
+            component.len() > 1 || graph.contains_edge(component[0], component[0])
+        })
+        .collect();
+
+    for component in &mut components {
+        component.sort();
+    }
+    components.sort();
+
+    components
+        .into_iter()
+        .map(|component| component.into_iter().map(|index| graph[index].clone()).collect())
+        .collect()
+}
+
+/// `(subject, dependency)` pairs of every `USES` statement, in source order,
+/// each listed once
+pub fn dependency_edges(program: &Program) -> Vec<(String, String)> {
+    let mut edges: Vec<(String, String)> = Vec::new();
+    for (subject, dependency, _) in uses_statements(program) {
+        if !edges.iter().any(|(s, d)| s == subject && d == dependency) {
+            edges.push((subject.to_string(), dependency.to_string()));
+        }
+    }
+    edges
+}
+
+/// Groups of names that depend on each other through `USES`, directly or
+/// indirectly, ordered like `find_cycles`
+pub fn find_dependency_cycles(program: &Program) -> Vec<Vec<String>> {
+    let mut graph = DiGraph::new();
+    let mut nodes = HashMap::new();
+    let mut node = |graph: &mut DiGraph<String, ()>, name: &str| {
+        *nodes.entry(name.to_string()).or_insert_with(|| graph.add_node(name.to_string()))
+    };
+
+    for (subject, dependency) in dependency_edges(program) {
+        let from = node(&mut graph, &subject);
+        let to = node(&mut graph, &dependency);
+        graph.update_edge(from, to, ());
+    }
+    cycles(&graph)
+}
+
+/// Semantic errors for circular `USES` dependencies, meant to be reported as
+/// warnings
+///
+/// Each cycle is reported once, at the last `USES` statement that belongs to it.
+pub fn check_dependency_cycles(program: &Program, src: &str) -> Vec<NaviLangError> {
+    let statements = uses_statements(program);
+
+    find_dependency_cycles(program)
+        .into_iter()
+        .filter_map(|cycle| {
+            let in_cycle = |name: &str| cycle.iter().any(|n| n == name);
+            let (_, _, stmt) = statements.iter()
+                .rfind(|(subject, dependency, _)| in_cycle(subject) && in_cycle(dependency))?;
+
+            let message = match cycle.split_last() {
+                Some((last, rest)) if !rest.is_empty() => {
+                    format!("Circular dependency between {} and {}", rest.join(", "), last)
+                }
+                _ => format!("{} depends on itself", cycle.join("")),
+            };
+            Some(NaviLangError::semantic_error(message, src.to_string(), stmt.span.clone()))
+        })
+        .collect()
+}
+
+fn uses_statements(program: &Program) -> Vec<(&str, &str, &Statement)> {
+    #[derive(Default)]
+    struct Collector<'ast> {
+        found: Vec<(&'ast str, &'ast str, &'ast Statement)>,
+    }
+
+    impl<'ast> Visitor<'ast> for Collector<'ast> {
+        fn visit_statement(&mut self, stmt: &'ast Statement) {
+            if let StatementKind::Uses { subject, dependency } = &stmt.kind {
+                self.found.push((subject, dependency, stmt));
+            }
+        }
+    }
+
+    let mut collector = Collector::default();
+    walk_program(&mut collector, program);
+    collector.found
+}
+
+/// List declared variables that no flow starting at `entry` can reach
+///
+/// Edges inside loop bodies count here. Variables are reported once each, in
+/// declaration order; the entry itself is never reported.
+pub fn find_unreachable(program: &Program, entry: &str) -> Vec<String> {
+    let flow = FlowGraph::build(program, true);
+
+    let mut visited = HashSet::new();
+    if let Some(start) = flow.node(entry) {
+        let mut bfs = Bfs::new(&flow.graph, start);
+        while let Some(index) = bfs.next(&flow.graph) {
+            visited.insert(flow.graph[index].as_str());
+        }
+    }
+
+    let mut unreachable = Vec::new();
+    for name in declared_names(program) {
+        if name != entry && !visited.contains(name) && !unreachable.iter().any(|n| n == name) {
+            unreachable.push(name.to_string());
+        }
+    }
+    unreachable
+}
+
+/// Flow errors for states that cannot be reached, meant to be reported as
+/// warnings
+///
+/// Each top-level context is checked on its own, starting from its `START`
+/// node, or else its first declared variable that takes part in a flow. Only
+/// variables that appear in the flow graph count as states; ones used purely
+/// as data (e.g. the object of `RETURNS`) are never reported.
+pub fn check_reachability(program: &Program, src: &str) -> Vec<NaviLangError> {
+    let mut warnings = Vec::new();
+
+    for context in &program.contexts {
+        let single = Program { contexts: vec![context.clone()], includes: Vec::new(), span: context.span.clone() };
+        let flow = FlowGraph::build(&single, true);
+
+        let names = declared_names(&single);
+        let entry = start_node(&context.statements)
+            .or_else(|| names.iter().find(|name| flow.node(name).is_some()).copied());
+        let Some(entry) = entry else {
+            continue;
+        };
+
+        for name in find_unreachable(&single, entry) {
+            if flow.node(&name).is_none() {
+                continue;
+            }
+            if let Some(decl) = find_declaration(context, &name) {
+                let message = format!("State '{}' is unreachable from '{}'", name, entry);
+                warnings.push(NaviLangError::flow_error(message, src.to_string(), decl.span.clone()));
+            }
+        }
+    }
+
+    warnings
+}
+
+/// States that no flow leaves, in the order they first appear in a flow
+///
+/// Only `GOES TO` flows count, including those inside blocks and loop
+/// bodies; a service that is merely called is not a state.
+pub fn terminal_states(program: &Program) -> Vec<String> {
+    let flows = flow_statements(program);
+    let mut states: Vec<&str> = Vec::new();
+    for (from, to, _) in &flows {
+        for name in [*from, *to] {
+            if !states.contains(&name) {
+                states.push(name);
+            }
+        }
+    }
+    states.into_iter()
+        .filter(|state| !flows.iter().any(|(from, _, _)| from == state))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Flow errors for flows that can never finish, meant to be reported as
+/// warnings
+///
+/// Each top-level context with flows is checked on its own, nested contexts
+/// included. A context without any terminal state is reported at its span;
+/// otherwise each state with no path to a terminal one is reported at the
+/// first flow leaving it.
+pub fn check_terminal_states(program: &Program, src: &str) -> Vec<NaviLangError> {
+    let mut warnings = Vec::new();
+
+    for context in &program.contexts {
+        let single = Program { contexts: vec![context.clone()], includes: Vec::new(), span: context.span.clone() };
+        let flows = flow_statements(&single);
+        if flows.is_empty() {
+            continue;
+        }
+
+        let terminals = terminal_states(&single);
+        if terminals.is_empty() {
+            let message = format!("Context '{}' has no terminal state, so its flows never end", context.name);
+            warnings.push(NaviLangError::flow_error(message, src.to_string(), context.span.clone()));
+            continue;
+        }
+
+        let mut graph = FlowGraph { graph: DiGraph::new(), nodes: HashMap::new() };
+        for (from, to, _) in &flows {
+            graph.add_edge(from, to);
+        }
+        let terminals: Vec<NodeIndex> = terminals.iter().filter_map(|name| graph.node(name)).collect();
+        let mut reported = HashSet::new();
+        for (from, _, stmt) in &flows {
+            let Some(node) = graph.node(from) else {
+                continue;
+            };
+            let ends = terminals.iter().any(|&terminal| has_path_connecting(&graph.graph, node, terminal, None));
+            if !ends && reported.insert(*from) {
+                let message = format!("State '{}' has no path to a terminal state", from);
+                warnings.push(NaviLangError::flow_error(message, src.to_string(), stmt.span.clone()));
+            }
+        }
+    }
+
+    warnings
+}
+
+/// `(from, to)` of every `GOES TO` flow with its statement, in source order
+fn flow_statements(program: &Program) -> Vec<(&str, &str, &Statement)> {
+    #[derive(Default)]
+    struct Collector<'ast> {
+        found: Vec<(&'ast str, &'ast str, &'ast Statement)>,
+    }
+
+    impl<'ast> Visitor<'ast> for Collector<'ast> {
+        fn visit_statement(&mut self, stmt: &'ast Statement) {
+            if let StatementKind::Flow { from, to, .. } = &stmt.kind {
+                self.found.push((from, to, stmt));
+            }
+        }
+    }
+
+    let mut collector = Collector::default();
+    walk_program(&mut collector, program);
+    collector.found
+}
+
+/// Report `PARALLEL` branches that have no path to their `JOIN AT` target
+///
+/// A branch joins if a flow from any state it moves between reaches the
+/// target, anywhere in the program and counting loop bodies. Branches with
+/// no flows at all, such as a lone `DOES`, never join.
+pub fn check_parallel_joins(program: &Program, src: &str) -> Result<()> {
+    #[derive(Default)]
+    struct Joins<'ast> {
+        found: Vec<(&'ast str, &'ast [Vec<Statement>])>,
+    }
+
+    impl<'ast> Visitor<'ast> for Joins<'ast> {
+        fn visit_statement(&mut self, stmt: &'ast Statement) {
+            if let StatementKind::Parallel { branches, join: Some(join) } = &stmt.kind {
+                self.found.push((join, branches));
+            }
+        }
+    }
+
+    let mut joins = Joins::default();
+    walk_program(&mut joins, program);
+
+    let flow = FlowGraph::build(program, true);
+    let mut errors = ErrorCollector::new();
+    for (join, branches) in joins.found {
+        let target = flow.node(join);
+        for (i, branch) in branches.iter().enumerate() {
+            let states = FlowGraph::of_statements(branch, true);
+
+            let joins = target.is_some_and(|target| {
+                states.nodes.keys().filter_map(|name| flow.node(name)).any(|start| {
+                    has_path_connecting(&flow.graph, start, target, None)
+                })
+            });
+            if !joins && let Some(first) = branch.first() {
+                let message = format!("Branch {} of PARALLEL has no path to its join point '{}'", i + 1, join);
+                errors.add_error(NaviLangError::flow_error(message, src.to_string(), first.span.clone()));
+            }
+        }
+    }
+    errors.into_result(())
+}
+
+fn find_declaration<'a>(context: &'a Context, name: &str) -> Option<&'a Statement> {
+    declarations(&context.statements)
+        .into_iter()
+        .find(|stmt| matches!(&stmt.kind, StatementKind::VarDecl { name: n, .. } if n == name))
+        .or_else(|| context.children.iter().find_map(|child| find_declaration(child, name)))
+}
+
+/// The entry used when none is given: the first top-level context's `START`
+/// node, or else the first declared variable
+pub fn default_entry(program: &Program) -> Option<&str> {
+    program.contexts.iter()
+        .find_map(|context| start_node(&context.statements))
+        .or_else(|| declared_names(program).into_iter().next())
+}
+
+/// Node named by the first `START` in `statements`
+fn start_node(statements: &[Statement]) -> Option<&str> {
+    starts(statements).into_iter().find_map(|stmt| match &stmt.kind {
+        StatementKind::Start { node } => Some(node.as_str()),
+        _ => None,
+    })
+}
+
+/// Names of all `VAR` declarations, contexts before their children
+fn declared_names(program: &Program) -> Vec<&str> {
+    fn collect<'a>(context: &'a Context, names: &mut Vec<&'a str>) {
+        for stmt in declarations(&context.statements) {
+            if let StatementKind::VarDecl { name, .. } = &stmt.kind {
+                names.push(name);
+            }
+        }
+        for child in &context.children {
+            collect(child, names);
+        }
+    }
+
+    let mut names = Vec::new();
+    for context in &program.contexts {
+        collect(context, &mut names);
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    #[test]
+    fn test_two_node_cycle() {
+        let program = parse_program("CONTEXT Flow { A GOES TO B  B GOES TO A  B GOES TO C }");
+        assert_eq!(find_cycles(&program), vec![vec!["A".to_string(), "B".to_string()]]);
+    }
+
+    #[test]
+    fn test_two_node_dependency_cycle() {
+        let input = "CONTEXT Backend {
+    AuthService USES Database
+    Database USES AuthService
+    AuthService USES Cache
+    AuthService USES Database
+}";
+        let program = parse_program(input);
+
+        assert_eq!(dependency_edges(&program), vec![
+            ("AuthService".to_string(), "Database".to_string()),
+            ("Database".to_string(), "AuthService".to_string()),
+            ("AuthService".to_string(), "Cache".to_string()),
+        ]);
+        assert_eq!(
+            find_dependency_cycles(&program),
+            vec![vec!["AuthService".to_string(), "Database".to_string()]]
+        );
+
+        let warnings = check_dependency_cycles(&program, input);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].to_string(), "Semantic error: Circular dependency between AuthService and Database");
+        // Dependencies are not flows
+        assert!(find_cycles(&program).is_empty());
+
+        assert!(check_dependency_cycles(&parse_program("CONTEXT A { Api USES Db  Db USES Disk }"), "").is_empty());
+    }
+
+    #[test]
+    fn test_self_loop_and_calls_edges() {
+        let program = parse_program("CONTEXT Flow { A -> A  X CALLS Y  Y -> X  Y RETURNS X }");
+        assert_eq!(
+            find_cycles(&program),
+            vec![vec!["A".to_string()], vec!["X".to_string(), "Y".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_loop_bodies_are_excluded() {
+        let program = parse_program("CONTEXT Flow { A -> B  LOOP { B -> A } WHILE A IS true { A -> A } }");
+        assert!(find_cycles(&program).is_empty());
+    }
+
+    #[test]
+    fn test_acyclic_program() {
+        let program = parse_program("CONTEXT Flow { A -> B  B -> C  IF C IS true THEN C -> D }");
+        assert!(find_cycles(&program).is_empty());
+    }
+
+    #[test]
+    fn test_isolated_node_is_unreachable() {
+        let program = parse_program("CONTEXT Flow {
+    VAR Begin
+    VAR Middle
+    VAR End
+    VAR Orphan
+    Begin -> Middle
+    LOOP { Middle -> End }
+    Orphan -> End
+}");
+
+        assert_eq!(default_entry(&program), Some("Begin"));
+        assert_eq!(find_unreachable(&program, "Begin"), vec!["Orphan".to_string()]);
+        assert_eq!(
+            find_unreachable(&program, "Middle"),
+            vec!["Begin".to_string(), "Orphan".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_unknown_entry_reaches_nothing() {
+        let program = parse_program("CONTEXT Flow { VAR A VAR B A -> B }");
+        assert_eq!(find_unreachable(&program, "Missing"), vec!["A".to_string(), "B".to_string()]);
+    }
+
+    #[test]
+    fn test_reachability_warnings_skip_data_variables() {
+        let input = "CONTEXT Flow {
+    VAR Begin
+    VAR Done
+    VAR Orphan
+    VAR Payload:Object
+    Begin -> Done
+    Orphan -> Done
+    Done RETURNS Payload
+}
+CONTEXT Other { VAR Alone VAR Next Alone -> Next }";
+        let warnings = check_reachability(&parse_program(input), input);
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].to_string(), "Flow validation error: State 'Orphan' is unreachable from 'Begin'");
+    }
+
+    #[test]
+    fn test_parallel_branch_must_reach_join() {
+        let input = "CONTEXT Fulfil {
+    PARALLEL {
+        Pack -> Ship
+        Charge -> Receipt
+        Notify DOES Email
+    } JOIN AT Done
+    Ship -> Done
+}";
+        let err = check_parallel_joins(&parse_program(input), input).unwrap_err();
+        let NaviLangError::MultipleErrors { errors } = err else {
+            panic!("Expected two errors, got {:?}", err);
+        };
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(messages, vec![
+            "Flow validation error: Branch 2 of PARALLEL has no path to its join point 'Done'",
+            "Flow validation error: Branch 3 of PARALLEL has no path to its join point 'Done'",
+        ]);
+
+        let joined = "CONTEXT Fulfil {
+    PARALLEL { Pack -> Ship  LOOP { Charge -> Again  Again -> Done } } JOIN AT Done
+    Ship -> Done
+    PARALLEL { A -> B }
+}";
+        assert!(check_parallel_joins(&parse_program(joined), joined).is_ok());
+    }
+
+    #[test]
+    fn test_start_is_the_reachability_entry() {
+        let input = "CONTEXT Flow {
+    VAR Landing
+    VAR Login
+    VAR Home
+    START Login
+    Landing -> Login
+    Login -> Home
+}";
+        let program = parse_program(input);
+        assert_eq!(default_entry(&program), Some("Login"));
+
+        let warnings = check_reachability(&program, input);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].to_string(), "Flow validation error: State 'Landing' is unreachable from 'Login'");
+    }
+
+    #[test]
+    fn test_cycle_without_exit_has_no_terminal_states() {
+        let input = "CONTEXT Spin {\n    A -> B\n    B -> C\n    C -> A\n}";
+        let program = parse_program(input);
+        assert!(terminal_states(&program).is_empty());
+
+        let warnings = check_terminal_states(&program, input);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].to_string(), "Flow validation error: Context 'Spin' has no terminal state, so its flows never end");
+    }
+
+    #[test]
+    fn test_states_that_cannot_reach_a_terminal() {
+        let input = "CONTEXT Checkout {
+    Cart -> Pay
+    Pay -> Done
+    IF Pay IS false THEN Pay -> Retry1
+    Retry1 -> Wait
+    Wait -> Retry1
+    User CALLS PaymentApi
+}
+CONTEXT Empty { VAR User }";
+        let program = parse_program(input);
+        assert_eq!(terminal_states(&program), vec!["Done".to_string()]);
+
+        let messages: Vec<String> = check_terminal_states(&program, input).iter().map(ToString::to_string).collect();
+        assert_eq!(messages, vec![
+            "Flow validation error: State 'Retry1' has no path to a terminal state",
+            "Flow validation error: State 'Wait' has no path to a terminal state",
+        ]);
+    }
+}
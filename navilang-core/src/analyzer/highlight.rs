@@ -0,0 +1,94 @@
+// Highlight - token classes for syntax highlighting
+use crate::error::Span;
+use crate::lexer::TokenWithSpan;
+use crate::lexer::tokens::Token;
+use std::collections::HashSet;
+
+/// What a token is, for choosing its highlight
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenClass {
+    Keyword,
+    Type,
+    String,
+    /// Integers, floats and durations
+    Number,
+    /// An identifier some `VAR` declares
+    Variable,
+    /// Any other identifier
+    Identifier,
+    /// Comparisons, `=` and `->`
+    Operator,
+    Comment,
+}
+
+/// Classify the tokens worth highlighting, in order
+///
+/// Whitespace and delimiters (braces, brackets, parentheses, commas and
+/// colons) are left out. `TRUE` and `FALSE` count as keywords. An identifier
+/// is a [`TokenClass::Variable`] when a `VAR` anywhere in the stream declares
+/// its name; scopes are not considered, so this works on input that doesn't
+/// parse.
+pub fn classify(tokens: &[TokenWithSpan]) -> Vec<(Span, TokenClass)> {
+    let significant = || tokens.iter().filter(|t| !t.token.is_whitespace());
+    let declared: HashSet<&str> = significant()
+        .zip(significant().skip(1))
+        .filter_map(|(keyword, name)| match (&keyword.token, &name.token) {
+            (Token::Var, Token::Identifier(name)) => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    tokens.iter()
+        .filter_map(|t| {
+            let class = match &t.token {
+                Token::Identifier(name) if declared.contains(name.as_str()) => TokenClass::Variable,
+                Token::Identifier(_) => TokenClass::Identifier,
+                Token::QuotedString(_) => TokenClass::String,
+                Token::Number(_) | Token::Float(_) | Token::Duration(_) => TokenClass::Number,
+                Token::Comment | Token::BlockComment => TokenClass::Comment,
+                Token::Equals | Token::NotEquals | Token::LessThan | Token::GreaterThan
+                | Token::LessEqual | Token::GreaterEqual | Token::Arrow => TokenClass::Operator,
+                token if token.is_type() => TokenClass::Type,
+                token if token.is_keyword() || token.is_literal() => TokenClass::Keyword,
+                _ => return None,
+            };
+            Some((t.span.clone(), class))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+
+    #[test]
+    fn test_token_classes() {
+        let input = "CONTEXT Auth { // hi\n    VAR User:Entity\n    IF User.Age >= 18 THEN User -> \"Home\"\n    Done = true }";
+        let tokens = Lexer::new(input).tokenize().unwrap();
+        let classes = classify(&tokens);
+
+        let text: Vec<(&str, TokenClass)> = classes.iter()
+            .map(|(span, class)| (&input[span.start.offset..span.end.offset], *class))
+            .collect();
+        assert_eq!(text, vec![
+            ("CONTEXT", TokenClass::Keyword),
+            ("Auth", TokenClass::Identifier),
+            ("// hi", TokenClass::Comment),
+            ("VAR", TokenClass::Keyword),
+            ("User", TokenClass::Variable),
+            ("Entity", TokenClass::Type),
+            ("IF", TokenClass::Keyword),
+            ("User.Age", TokenClass::Identifier),
+            (">=", TokenClass::Operator),
+            ("18", TokenClass::Number),
+            ("THEN", TokenClass::Keyword),
+            ("User", TokenClass::Variable),
+            ("->", TokenClass::Operator),
+            ("\"Home\"", TokenClass::String),
+            ("Done", TokenClass::Identifier),
+            ("=", TokenClass::Operator),
+            ("true", TokenClass::Keyword),
+        ]);
+    }
+}
@@ -0,0 +1,169 @@
+// Symbol index - declarations and references for editor navigation
+use crate::analyzer::resolve::declarations;
+use crate::error::{Position, Span};
+use crate::parser::ast::{Context, Program, Statement, StatementKind, Visitor, walk_program};
+use std::collections::HashMap;
+
+/// A declared variable together with every place it is used
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedSymbol {
+    pub name: String,
+    /// Span of the name in its `VAR` declaration
+    pub declaration: Span,
+    /// Spans of the identifiers that resolve to this declaration, in source order
+    pub references: Vec<Span>,
+}
+
+/// Every declared variable of a program and the identifiers resolving to it
+///
+/// Names are resolved with the same scoping as the analyzer: a context sees
+/// its own variables and those of its parents, and the first `VAR` of a name
+/// in a scope wins. Identifiers without a declaration are not indexed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SymbolIndex {
+    symbols: Vec<IndexedSymbol>,
+}
+
+impl SymbolIndex {
+    /// Declared symbols, in declaration order
+    pub fn symbols(&self) -> &[IndexedSymbol] {
+        &self.symbols
+    }
+
+    /// Declaration of the symbol whose declaration or reference contains `pos`
+    pub fn definition_at(&self, pos: Position) -> Option<Span> {
+        self.symbol_at(pos).map(|symbol| symbol.declaration.clone())
+    }
+
+    /// References to the symbol whose declaration or reference contains `pos`
+    ///
+    /// The declaration itself is not included.
+    pub fn references_at(&self, pos: Position) -> Vec<Span> {
+        self.symbol_at(pos)
+            .map(|symbol| symbol.references.clone())
+            .unwrap_or_default()
+    }
+
+    fn symbol_at(&self, pos: Position) -> Option<&IndexedSymbol> {
+        self.symbols.iter().find(|symbol| {
+            symbol.declaration.contains(pos) || symbol.references.iter().any(|span| span.contains(pos))
+        })
+    }
+}
+
+/// Build the symbol index of `program`
+pub fn build_index(program: &Program) -> SymbolIndex {
+    let mut builder = IndexBuilder::default();
+    walk_program(&mut builder, program);
+    builder.index
+}
+
+#[derive(Default)]
+struct IndexBuilder {
+    index: SymbolIndex,
+    /// Symbol positions in `index` by name, one map per enclosing context
+    scopes: Vec<HashMap<String, usize>>,
+}
+
+impl IndexBuilder {
+    fn lookup(&self, name: &str) -> Option<usize> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name).copied())
+    }
+}
+
+impl<'ast> Visitor<'ast> for IndexBuilder {
+    fn visit_context(&mut self, context: &'ast Context) {
+        let mut scope = HashMap::new();
+        for stmt in declarations(&context.statements) {
+            if let Some(declared) = stmt.names.first() {
+                scope.entry(declared.name.clone()).or_insert_with(|| {
+                    self.index.symbols.push(IndexedSymbol {
+                        name: declared.name.clone(),
+                        declaration: declared.span.clone(),
+                        references: Vec::new(),
+                    });
+                    self.index.symbols.len() - 1
+                });
+            }
+        }
+        self.scopes.push(scope);
+    }
+
+    fn leave_context(&mut self, _context: &'ast Context) {
+        self.scopes.pop();
+    }
+
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        // The name a `VAR` declares is not a reference to it
+        let used = match stmt.kind {
+            StatementKind::VarDecl { .. } => &[],
+            _ => stmt.names.as_slice(),
+        };
+
+        for name in used {
+            if let Some(symbol) = self.lookup(&name.name) {
+                self.index.symbols[symbol].references.push(name.span.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    fn index(input: &str) -> SymbolIndex {
+        build_index(&parse_program(input))
+    }
+
+    /// Position of the `n`th byte of the first occurrence of `needle`
+    fn position(input: &str, needle: &str, n: usize) -> Position {
+        let offset = input.find(needle).unwrap() + n;
+        let line = input[..offset].matches('\n').count() + 1;
+        let column = offset - input[..offset].rfind('\n').map_or(0, |i| i + 1) + 1;
+        Position::new(line, column, offset)
+    }
+
+    #[test]
+    fn test_reference_resolves_to_declaration() {
+        let input = "CONTEXT Auth {\n    VAR User\n    VAR X\n    User GOES TO X\n}";
+        let index = index(input);
+
+        let declaration = index.definition_at(position(input, "User GOES", 2)).unwrap();
+        assert_eq!(declaration.start.offset, input.find("User\n").unwrap());
+        assert_eq!(declaration.end.offset, declaration.start.offset + "User".len());
+        assert_eq!((declaration.start.line, declaration.start.column), (2, 9));
+
+        let references = index.references_at(declaration.start);
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].start.offset, input.find("User GOES").unwrap());
+
+        assert!(index.definition_at(position(input, "GOES", 1)).is_none());
+        assert!(index.references_at(position(input, "GOES", 1)).is_empty());
+    }
+
+    #[test]
+    fn test_references_in_nested_statements_and_contexts() {
+        let input = "CONTEXT App {
+    VAR User
+    VAR Api
+    IF User IS Api THEN LOOP { User CALLS Api }
+    CONTEXT Child { VAR Api  User CALLS Api }
+}
+CONTEXT Other { User GOES TO Api }";
+        let index = index(input);
+
+        let names: Vec<&str> = index.symbols().iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["User", "Api", "Api"]);
+
+        // Condition, loop body and the child context all see the outer User
+        assert_eq!(index.symbols()[0].references.len(), 3);
+        // The child's Api shadows the outer one
+        assert_eq!(index.symbols()[1].references.len(), 2);
+        assert_eq!(index.symbols()[2].references.len(), 1);
+
+        // Sibling contexts don't see each other's variables
+        assert!(index.definition_at(position(input, "User GOES", 0)).is_none());
+    }
+}
@@ -0,0 +1,61 @@
+// Lint - style warnings that don't affect meaning
+use crate::error::{Diagnostic, NaviLangError};
+use crate::lexer::tokens;
+use crate::parser::ast::{Program, Statement, StatementKind, Visitor, walk_program};
+
+/// Warnings for variables named like a type keyword, ignoring case
+///
+/// Such a name can only be written with backticks (`` VAR `service` ``), and
+/// reads as the type wherever it is used. Each warning is reported at the
+/// declaration and suggests renaming the variable.
+pub fn naming(program: &Program, src: &str) -> Vec<Diagnostic> {
+    struct Naming<'a> {
+        src: &'a str,
+        warnings: Vec<Diagnostic>,
+    }
+
+    impl<'ast> Visitor<'ast> for Naming<'_> {
+        fn visit_statement(&mut self, stmt: &'ast Statement) {
+            let StatementKind::VarDecl { name, .. } = &stmt.kind else {
+                return;
+            };
+            if let Some(keyword) = tokens::types().iter().find(|keyword| keyword.eq_ignore_ascii_case(name)) {
+                let message = format!("Variable '{}' is named like the type '{}'; consider renaming it", name, keyword);
+                let warning = NaviLangError::semantic_error(message, self.src.to_string(), stmt.span.clone());
+                self.warnings.push(Diagnostic::warning(warning));
+            }
+        }
+    }
+
+    let mut naming = Naming { src, warnings: Vec::new() };
+    walk_program(&mut naming, program);
+    naming.warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    #[test]
+    fn test_variable_named_like_a_type() {
+        let input = "CONTEXT Auth {\n    VAR `service`\n    VAR Services\n    VAR `OBJECT`:Object\n}";
+        let warnings = naming(&parse_program(input), input);
+
+        let messages: Vec<String> = warnings.iter().map(ToString::to_string).collect();
+        assert_eq!(messages, vec![
+            "Warning: Variable 'service' is named like the type 'Service'; consider renaming it",
+            "Warning: Variable 'OBJECT' is named like the type 'Object'; consider renaming it",
+        ]);
+        assert!(warnings.iter().all(|warning| !warning.is_error()));
+    }
+
+    #[test]
+    fn test_unescaped_name_from_a_built_ast() {
+        // The parser rejects `VAR service`, but an AST deserialized from JSON can hold it
+        let mut program = parse_program("CONTEXT Auth { VAR Svc }");
+        program.contexts[0].statements[0].kind = StatementKind::VarDecl { name: "service".to_string(), type_annotation: None };
+
+        assert_eq!(naming(&program, "").len(), 1);
+    }
+}
@@ -0,0 +1,139 @@
+// Metrics - size and complexity figures for a program
+use crate::parser::ast::{Context, Program, Statement, StatementKind, Visitor, walk_program};
+use serde::Serialize;
+
+/// Size and complexity of a program, e.g. for governance reports
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Metrics {
+    /// Contexts, including nested ones
+    pub contexts: usize,
+    /// Statements of every kind, including those inside blocks
+    pub statements: usize,
+    /// `VAR` declarations
+    pub variables: usize,
+    /// `GOES TO` flows
+    pub flows: usize,
+    /// `CALLS`, `RECEIVES` and `RETURNS` interactions
+    pub interactions: usize,
+    /// `IF` statements
+    pub conditionals: usize,
+    /// `LOOP` and `WHILE` statements
+    pub loops: usize,
+    /// Most compound statements (`IF`, `LOOP`, `PARALLEL`, ...) enclosing a
+    /// single statement; 0 when every statement sits directly in a context
+    pub max_nesting_depth: usize,
+    /// One plus the number of decision points: each conditional, each loop
+    /// and each `AND`/`OR` in their guards
+    pub complexity: usize,
+}
+
+/// Compute the metrics of `program`
+pub fn compute(program: &Program) -> Metrics {
+    let mut counter = Counter {
+        metrics: Metrics { complexity: 1, ..Metrics::default() },
+        depth: 0,
+    };
+    walk_program(&mut counter, program);
+    counter.metrics
+}
+
+struct Counter {
+    metrics: Metrics,
+    /// Compound statements enclosing the statement being visited
+    depth: usize,
+}
+
+impl<'ast> Visitor<'ast> for Counter {
+    fn visit_context(&mut self, _context: &'ast Context) {
+        self.metrics.contexts += 1;
+    }
+
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        let metrics = &mut self.metrics;
+        metrics.statements += 1;
+        metrics.max_nesting_depth = metrics.max_nesting_depth.max(self.depth);
+
+        match &stmt.kind {
+            StatementKind::VarDecl { .. } => metrics.variables += 1,
+            StatementKind::Flow { .. } => metrics.flows += 1,
+            StatementKind::Interaction { .. } => metrics.interactions += 1,
+            StatementKind::Conditional { condition, .. } => {
+                metrics.conditionals += 1;
+                metrics.complexity += condition.predicates().len();
+            }
+            StatementKind::Loop { condition, .. } => {
+                metrics.loops += 1;
+                metrics.complexity += condition.as_ref().map_or(1, |condition| condition.predicates().len());
+            }
+            _ => {}
+        }
+
+        if is_compound(stmt) {
+            self.depth += 1;
+        }
+    }
+
+    fn leave_statement(&mut self, stmt: &'ast Statement) {
+        if is_compound(stmt) {
+            self.depth -= 1;
+        }
+    }
+}
+
+/// Whether `stmt` holds other statements
+fn is_compound(stmt: &Statement) -> bool {
+    matches!(
+        stmt.kind,
+        StatementKind::Conditional { .. }
+            | StatementKind::Loop { .. }
+            | StatementKind::Parallel { .. }
+            | StatementKind::Batch { .. }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    fn metrics(input: &str) -> Metrics {
+        compute(&parse_program(input))
+    }
+
+    #[test]
+    fn test_nested_program_metrics() {
+        let metrics = metrics(r#"CONTEXT Checkout {
+    VAR User
+    VAR Cart
+    User GOES TO Cart
+    IF Cart IS "empty" THEN User GOES TO Shop ELSE User CALLS Payment
+    WHILE Attempts < 3 AND Locked = false {
+        IF Attempts > 1 THEN PARALLEL { User DOES Wait  IF User IS "gone" THEN BREAK }
+    }
+    CONTEXT Receipt { VAR Email  Email RETURNS Ok }
+}"#);
+
+        assert_eq!(metrics, Metrics {
+            contexts: 2,
+            statements: 14,
+            variables: 3,
+            flows: 2,
+            interactions: 2,
+            conditionals: 3,
+            loops: 1,
+            // BREAK sits inside IF, PARALLEL, IF and WHILE
+            max_nesting_depth: 4,
+            // 1 + three single-comparison IFs + a two-comparison WHILE
+            complexity: 6,
+        });
+    }
+
+    #[test]
+    fn test_flat_program_has_no_nesting() {
+        let flat = metrics("CONTEXT A { VAR X  X -> Y }  CONTEXT B {}");
+        assert_eq!(flat.contexts, 2);
+        assert_eq!(flat.max_nesting_depth, 0);
+        assert_eq!(flat.complexity, 1);
+        assert_eq!(metrics(""), Metrics { complexity: 1, ..Metrics::default() });
+    }
+}
@@ -0,0 +1,106 @@
+// Analyzer module - Semantic Analysis Stage
+// This module will implement context resolution, type checking, flow validation, and dependency analysis
+
+pub mod attributes;
+pub mod graph;
+pub mod highlight;
+pub mod index;
+pub mod lint;
+pub mod metrics;
+pub mod ordering;
+pub mod resolve;
+pub mod timing;
+pub mod types;
+
+use crate::error::{Diagnostic, ErrorCollector, Result};
+use crate::parser::ast::Program;
+use crate::utils::duration::Duration;
+
+/// Options for the semantic passes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnalyzerConfig {
+    /// Treat undeclared identifiers as errors rather than warnings, and
+    /// require every identifier to be declared by a `VAR` earlier in the
+    /// source than its use, instead of anywhere in scope
+    pub strict: bool,
+    /// Warn about contexts whose interactions may wait longer than this in
+    /// the worst case; see [`timing::check_timeout_budget`]
+    pub timeout_budget: Option<Duration>,
+    /// Stop after this many errors, skipping the passes not yet run and
+    /// ending the errors with a `TooManyErrors` note
+    pub max_errors: Option<usize>,
+}
+
+/// Runs every semantic pass over a parsed program
+pub struct SemanticAnalyzer {
+    config: AnalyzerConfig,
+}
+
+impl SemanticAnalyzer {
+    pub fn new() -> Self {
+        Self::with_config(AnalyzerConfig::default())
+    }
+    
+    pub fn with_config(config: AnalyzerConfig) -> Self {
+        Self { config }
+    }
+    
+    /// Check declarations, name resolution, interaction and comparison
+    /// types, ordering constraints, parallel joins and statement attributes,
+    /// reporting the errors of every pass together, up to `max_errors`
+    ///
+    /// On success, returns the warnings found (such as undeclared identifiers,
+    /// unless `strict` is set, unreachable states or circular dependencies).
+    pub fn analyze(&self, program: &Program, src: &str) -> Result<Vec<Diagnostic>> {
+        let mut errors = ErrorCollector::with_optional_limit(self.config.max_errors);
+        
+        errors.add_result(resolve::check_duplicates(program, src));
+        if !errors.is_full() {
+            let identifiers = resolve::check_identifiers_with_config(program, src, &self.config);
+            for warning in errors.add_result(identifiers).unwrap_or_default() {
+                errors.add_warning(warning);
+            }
+        }
+        let passes: [fn(&Program, &str) -> Result<()>; 4] = [
+            types::check_operand_types,
+            ordering::check_ordering,
+            graph::check_parallel_joins,
+            attributes::check_attributes,
+        ];
+        for pass in passes {
+            // Past the limit, another pass could only add errors that get dropped
+            if errors.is_full() {
+                break;
+            }
+            errors.add_result(pass(program, src));
+        }
+        for warning in graph::check_reachability(program, src) {
+            errors.add_warning(warning);
+        }
+        for warning in graph::check_dependency_cycles(program, src) {
+            errors.add_warning(warning);
+        }
+        for warning in graph::check_terminal_states(program, src) {
+            errors.add_warning(warning);
+        }
+        for warning in lint::naming(program, src) {
+            errors.add_warning(warning.error);
+        }
+        if let Some(budget) = self.config.timeout_budget {
+            for warning in timing::check_timeout_budget(program, src, budget) {
+                errors.add_warning(warning);
+            }
+        }
+        
+        if errors.has_errors() {
+            return errors.into_result(Vec::new());
+        }
+        Ok(errors.into_diagnostics())
+    }
+}
+
+impl Default for SemanticAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,139 @@
+// Ordering analysis - happens-before constraints from BEFORE / AFTER
+use crate::error::{ErrorCollector, NaviLangError, Result};
+use crate::parser::ast::{OrderRelation, Program, Statement, StatementKind, Visitor, walk_program};
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::{DiGraph, NodeIndex};
+use std::collections::HashMap;
+
+/// Happens-before graph: an edge `A -> B` means A must happen before B
+///
+/// `A AFTER B` is stored as `B -> A`. Each edge keeps the statement that
+/// introduced it, for error reporting.
+#[derive(Default)]
+pub struct OrderingGraph<'ast> {
+    pub graph: DiGraph<String, &'ast Statement>,
+    nodes: HashMap<String, NodeIndex>,
+}
+
+impl<'ast> OrderingGraph<'ast> {
+    pub fn build(program: &'ast Program) -> Self {
+        let mut graph = Self::default();
+        walk_program(&mut graph, program);
+        graph
+    }
+
+    /// Groups of steps whose constraints require each to happen before the
+    /// others, in the order their first step appears
+    pub fn contradictions(&self) -> Vec<Vec<NodeIndex>> {
+        let mut components: Vec<Vec<NodeIndex>> = tarjan_scc(&self.graph)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || self.graph.contains_edge(component[0], component[0])
+            })
+            .collect();
+
+        for component in &mut components {
+            component.sort();
+        }
+        components.sort();
+        components
+    }
+
+    fn node(&mut self, name: &str) -> NodeIndex {
+        if let Some(&index) = self.nodes.get(name) {
+            return index;
+        }
+        let index = self.graph.add_node(name.to_string());
+        self.nodes.insert(name.to_string(), index);
+        index
+    }
+}
+
+impl<'ast> Visitor<'ast> for OrderingGraph<'ast> {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        if let StatementKind::Ordering { subject, relation, other } = &stmt.kind {
+            let (first, then) = match relation {
+                OrderRelation::Before => (subject, other),
+                OrderRelation::After => (other, subject),
+            };
+            let first = self.node(first);
+            let then = self.node(then);
+            self.graph.add_edge(first, then, stmt);
+        }
+    }
+}
+
+/// Report `BEFORE`/`AFTER` constraints that contradict each other
+///
+/// A contradiction is any cycle in the happens-before graph, such as
+/// `A BEFORE B` together with `B BEFORE A` (or `A AFTER B`). One error is
+/// reported per cycle, pointing at the last constraint that closes it.
+pub fn check_ordering(program: &Program, src: &str) -> Result<()> {
+    let ordering = OrderingGraph::build(program);
+    let mut errors = ErrorCollector::new();
+
+    for component in ordering.contradictions() {
+        let names: Vec<&str> = component.iter().map(|&index| ordering.graph[index].as_str()).collect();
+
+        let last = ordering.graph.edge_indices()
+            .filter(|&edge| {
+                let (from, to) = ordering.graph.edge_endpoints(edge).unwrap();
+                component.contains(&from) && component.contains(&to)
+            })
+            .map(|edge| ordering.graph[edge])
+            .max_by_key(|stmt| stmt.span.start.offset);
+
+        if let Some(stmt) = last {
+            let message = match names.split_last() {
+                Some((last, rest)) if !rest.is_empty() => format!(
+                    "Contradictory ordering: {} and {} cannot each happen before the other",
+                    rest.join(", "),
+                    last
+                ),
+                _ => format!("Contradictory ordering: {} cannot happen before itself", names.join("")),
+            };
+            errors.add_error(NaviLangError::semantic_error(message, src.to_string(), stmt.span.clone()));
+        }
+    }
+
+    errors.into_result(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    fn check(input: &str) -> Result<()> {
+        check_ordering(&parse_program(input), input)
+    }
+
+    #[test]
+    fn test_before_and_before_contradict() {
+        let input = "CONTEXT Checkout {\n    Pay BEFORE Ship\n    Ship BEFORE Pay\n}";
+
+        match check(input) {
+            Err(NaviLangError::SemanticError { message, span, .. }) => {
+                assert_eq!(message, "Contradictory ordering: Pay and Ship cannot each happen before the other");
+                assert_eq!(span.offset(), input.find("Ship BEFORE").unwrap());
+            }
+            other => panic!("Expected SemanticError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_after_reverses_the_edge() {
+        assert!(check("CONTEXT A { Pay BEFORE Ship  Ship AFTER Pay }").is_ok());
+        assert!(check("CONTEXT A { Pay BEFORE Ship  Pay AFTER Ship }").is_err());
+        assert!(check("CONTEXT A { Pay BEFORE Pay }").is_err());
+    }
+
+    #[test]
+    fn test_longer_cycles_and_consistent_chains() {
+        assert!(check("CONTEXT A { Review BEFORE Pay  Pay BEFORE Ship  IF X IS 1 THEN Review AFTER Review2 }").is_ok());
+
+        let input = "CONTEXT A { Review BEFORE Pay  Pay BEFORE Ship  LOOP { Ship BEFORE Review } }";
+        let message = check(input).unwrap_err().to_string();
+        assert_eq!(message, "Semantic error: Contradictory ordering: Review, Pay and Ship cannot each happen before the other");
+    }
+}
@@ -0,0 +1,427 @@
+// Name resolution - symbol tables and identifier checks
+use crate::analyzer::AnalyzerConfig;
+use crate::error::{ErrorCollector, NaviLangError, Result, Span};
+use crate::parser::ast::{Context, Program, Statement, StatementKind, TypeAnnotation, Visitor, walk_program, walk_statement};
+use crate::utils::distance::closest_match;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+/// A variable introduced by a `VAR` declaration
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub type_annotation: Option<TypeAnnotation>,
+    /// Span of the declaring statement
+    pub span: Span,
+}
+
+/// Lexically scoped symbol table with one scope per context
+///
+/// Lookups search from the innermost scope outwards, so a nested context sees
+/// the variables of its parents but never those of its siblings.
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    scopes: Vec<HashMap<String, Symbol>>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Declare a symbol in the innermost scope
+    ///
+    /// Returns the earlier symbol of the same name in that scope, if any; the
+    /// first declaration is kept.
+    pub fn declare(&mut self, symbol: Symbol) -> Option<&Symbol> {
+        let scope = self.scopes.last_mut()?;
+        match scope.entry(symbol.name.clone()) {
+            Entry::Occupied(existing) => Some(existing.into_mut()),
+            Entry::Vacant(slot) => {
+                slot.insert(symbol);
+                None
+            }
+        }
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<&Symbol> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// Every name visible from the innermost scope, sorted
+    pub fn visible_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.scopes.iter()
+            .flat_map(|scope| scope.keys().map(String::as_str))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Open a scope for `context` and declare every variable it contains,
+    /// including those declared inside its blocks
+    ///
+    /// Sub-contexts are not entered; callers push them as they recurse.
+    pub fn enter_context(&mut self, context: &Context) {
+        self.push_scope();
+        for stmt in declarations(&context.statements) {
+            if let StatementKind::VarDecl { name, type_annotation } = &stmt.kind {
+                self.declare(Symbol {
+                    name: name.clone(),
+                    type_annotation: type_annotation.clone(),
+                    span: stmt.span.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Every `VAR` statement in `statements`, descending into blocks, in source order
+pub(crate) fn declarations(statements: &[Statement]) -> Vec<&Statement> {
+    find_statements(statements, &|kind| matches!(kind, StatementKind::VarDecl { .. }))
+}
+
+/// Every `START` statement in `statements`, descending into blocks, in source order
+pub(crate) fn starts(statements: &[Statement]) -> Vec<&Statement> {
+    find_statements(statements, &|kind| matches!(kind, StatementKind::Start { .. }))
+}
+
+fn find_statements<'a>(statements: &'a [Statement], wanted: &dyn Fn(&StatementKind) -> bool) -> Vec<&'a Statement> {
+    struct Finder<'a, 'f> {
+        wanted: &'f dyn Fn(&StatementKind) -> bool,
+        found: Vec<&'a Statement>,
+    }
+
+    impl<'a> Visitor<'a> for Finder<'a, '_> {
+        fn visit_statement(&mut self, stmt: &'a Statement) {
+            if (self.wanted)(&stmt.kind) {
+                self.found.push(stmt);
+            }
+        }
+    }
+
+    let mut finder = Finder { wanted, found: Vec::new() };
+    for stmt in statements {
+        walk_statement(&mut finder, stmt);
+    }
+    finder.found
+}
+
+/// Largest edit distance at which a declared name is offered as a suggestion
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Find every flow, action, ordering, dependency or interaction that refers
+/// to an undeclared identifier
+///
+/// A name is in scope if it is declared in the same context or in one of the
+/// contexts enclosing it. The `UnknownIdentifier`s are returned as warnings,
+/// in source order; each one suggests the closest name in scope within two
+/// edits, if there is one.
+pub fn check_identifiers(program: &Program, src: &str) -> Vec<NaviLangError> {
+    check_identifiers_with_config(program, src, &AnalyzerConfig::default()).unwrap_or_default()
+}
+
+/// [`check_identifiers`], with `config.strict` making undeclared names errors
+/// and additionally rejecting uses that come before their declaration
+///
+/// Returns the warnings on success; in strict mode there are none.
+pub fn check_identifiers_with_config(program: &Program, src: &str, config: &AnalyzerConfig) -> Result<Vec<NaviLangError>> {
+    let mut check = IdentifierCheck {
+        table: SymbolTable::new(),
+        src,
+        strict: config.strict,
+        errors: ErrorCollector::new(),
+        warnings: Vec::new(),
+    };
+    walk_program(&mut check, program);
+    check.errors.into_result(check.warnings)
+}
+
+struct IdentifierCheck<'a> {
+    table: SymbolTable,
+    src: &'a str,
+    strict: bool,
+    errors: ErrorCollector,
+    warnings: Vec<NaviLangError>,
+}
+
+impl<'ast> Visitor<'ast> for IdentifierCheck<'_> {
+    fn visit_context(&mut self, context: &'ast Context) {
+        self.table.enter_context(context);
+    }
+
+    fn leave_context(&mut self, _context: &'ast Context) {
+        self.table.pop_scope();
+    }
+
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        let names: Vec<&str> = match &stmt.kind {
+            StatementKind::Flow { from, to, .. } => vec![from, to],
+            StatementKind::Action { actor, .. } => vec![actor],
+            StatementKind::Ordering { subject, other, .. } => vec![subject, other],
+            StatementKind::Uses { subject, dependency } => vec![subject, dependency],
+            StatementKind::Creation { subject, creator } => vec![subject, creator],
+            StatementKind::Parallel { join: Some(join), .. } => vec![join],
+            StatementKind::Start { node } => vec![node],
+            StatementKind::Interaction { subject, object, .. } => {
+                std::iter::once(subject.as_str()).chain(object.as_identifier()).collect()
+            }
+            _ => return,
+        };
+
+        for (i, name) in names.iter().enumerate() {
+            // `A -> A` refers to A once
+            if names[..i].contains(name) {
+                continue;
+            }
+            let span = stmt.span_of(name).clone();
+            if let Some(symbol) = self.table.lookup(name) {
+                if self.strict && symbol.span.start.offset > stmt.span.start.offset {
+                    let message = format!(
+                        "'{}' is used before its declaration at line {}, column {}",
+                        name, symbol.span.start.line, symbol.span.start.column
+                    );
+                    self.errors.add_error(NaviLangError::semantic_error(message, self.src.to_string(), span));
+                }
+                continue;
+            }
+            let error = match closest_match(name, self.table.visible_names(), MAX_SUGGESTION_DISTANCE) {
+                Some(suggestion) => NaviLangError::unknown_identifier_with_suggestion(
+                    name.to_string(),
+                    suggestion,
+                    self.src.to_string(),
+                    span,
+                ),
+                None => NaviLangError::unknown_identifier(name.to_string(), self.src.to_string(), span),
+            };
+            if self.strict {
+                self.errors.add_error(error);
+            } else {
+                self.warnings.push(error);
+            }
+        }
+    }
+}
+
+/// Report variables declared more than once in the same context, and
+/// contexts with more than one `START`
+///
+/// The error points at the redefinition and names the line of the first
+/// declaration. Redeclaring a parent's variable inside a nested context is
+/// shadowing and allowed, as is a nested context with its own `START`.
+pub fn check_duplicates(program: &Program, src: &str) -> Result<()> {
+    let mut errors = ErrorCollector::new();
+
+    for context in &program.contexts {
+        check_context_duplicates(context, src, &mut errors);
+    }
+
+    errors.into_result(())
+}
+
+fn check_context_duplicates(context: &Context, src: &str, errors: &mut ErrorCollector) {
+    let mut table = SymbolTable::new();
+    table.push_scope();
+
+    for stmt in declarations(&context.statements) {
+        if let StatementKind::VarDecl { name, type_annotation } = &stmt.kind {
+            let symbol = Symbol {
+                name: name.clone(),
+                type_annotation: type_annotation.clone(),
+                span: stmt.span.clone(),
+            };
+            if let Some(first) = table.declare(symbol) {
+                let message = format!(
+                    "Variable '{}' is already declared in context '{}' (first declared at line {}, column {})",
+                    name, context.name, first.span.start.line, first.span.start.column
+                );
+                errors.add_error(NaviLangError::semantic_error(message, src.to_string(), stmt.span.clone()));
+            }
+        }
+    }
+
+    if let [first, rest @ ..] = starts(&context.statements).as_slice() {
+        for stmt in rest {
+            let message = format!(
+                "Context '{}' already has a START (at line {}, column {})",
+                context.name, first.span.start.line, first.span.start.column
+            );
+            errors.add_error(NaviLangError::semantic_error(message, src.to_string(), stmt.span.clone()));
+        }
+    }
+
+    for child in &context.children {
+        check_context_duplicates(child, src, errors);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    fn check(input: &str) -> Vec<NaviLangError> {
+        check_identifiers(&parse_program(input), input)
+    }
+
+    fn check_strict(input: &str) -> Result<Vec<NaviLangError>> {
+        check_identifiers_with_config(&parse_program(input), input, &AnalyzerConfig { strict: true, ..AnalyzerConfig::default() })
+    }
+
+    #[test]
+    fn test_undeclared_flow_target() {
+        let input = "CONTEXT Auth {\n    VAR User:Entity\n    User GOES TO Dashboard\n}";
+
+        match check(input).as_slice() {
+            [NaviLangError::UnknownIdentifier { name, span, .. }] => {
+                assert_eq!(name, "Dashboard");
+                assert_eq!(span.offset(), input.find("Dashboard").unwrap());
+                assert_eq!(span.len(), "Dashboard".len());
+            }
+            other => panic!("Expected UnknownIdentifier, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_strict_mode_requires_prior_declaration() {
+        let input = "CONTEXT Auth {\n    User GOES TO Home\n    VAR User\n    VAR Home\n}";
+        assert!(check(input).is_empty());
+
+        match check_strict(input) {
+            Err(NaviLangError::MultipleErrors { errors }) => {
+                assert_eq!(errors.len(), 2);
+                assert_eq!(errors[0].to_string(), "Semantic error: 'User' is used before its declaration at line 3, column 5");
+                let NaviLangError::SemanticError { span, .. } = &errors[0] else {
+                    panic!("Expected SemanticError, got {:?}", errors[0]);
+                };
+                assert_eq!((span.offset(), span.len()), (input.find("User GOES").unwrap(), "User".len()));
+            }
+            other => panic!("Expected two SemanticErrors, got {:?}", other),
+        }
+
+        let ordered = "CONTEXT Auth { VAR User  VAR Home  User GOES TO Home  CONTEXT Child { User -> Home } }";
+        assert!(check_strict(ordered).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_undeclared_names_are_errors_only_in_strict_mode() {
+        let input = "CONTEXT Auth { VAR User  User GOES TO Dashboard }";
+
+        assert_eq!(check(input).len(), 1);
+        match check_strict(input) {
+            Err(NaviLangError::UnknownIdentifier { name, .. }) => assert_eq!(name, "Dashboard"),
+            other => panic!("Expected UnknownIdentifier, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_qualified_names_are_distinct_symbols() {
+        assert!(check("CONTEXT App { VAR A.B.C  VAR Editor  A.B.C GOES TO Editor }").is_empty());
+
+        let input = "CONTEXT App { VAR User  VAR Editor  User.Profile GOES TO Editor }";
+        match check(input).as_slice() {
+            [NaviLangError::UnknownIdentifier { name, .. }] => assert_eq!(name, "User.Profile"),
+            other => panic!("Expected UnknownIdentifier, got {:?}", other),
+        }
+        assert!(!check("CONTEXT App { VAR User.Profile  User GOES TO User.Profile }").is_empty());
+    }
+
+    #[test]
+    fn test_declared_names_resolve() {
+        let input = r#"CONTEXT Auth {
+    VAR User:Entity
+    VAR AuthService:Service
+    VAR Dashboard
+    User GOES TO Dashboard
+    User CALLS AuthService
+    AuthService RETURNS "token"
+    User DOES Login
+    LOOP { VAR Retry2 User -> Retry2 }
+}"#;
+        assert!(check(input).is_empty());
+    }
+
+    #[test]
+    fn test_sibling_contexts_do_not_share_scope() {
+        let input = "CONTEXT A { VAR User } CONTEXT B { VAR Home User -> Home }";
+
+        match check(input).as_slice() {
+            [NaviLangError::UnknownIdentifier { name, .. }] => assert_eq!(name, "User"),
+            other => panic!("Expected UnknownIdentifier, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_nested_contexts_see_parent_scope() {
+        let input = "CONTEXT Outer { VAR User CONTEXT Inner { VAR Home User -> Home } }";
+        assert!(check(input).is_empty());
+    }
+
+    #[test]
+    fn test_all_violations_are_collected() {
+        let input = "CONTEXT A { X -> Y  Z CALLS W }";
+
+        assert_eq!(check(input).len(), 4);
+    }
+
+    #[test]
+    fn test_unknown_identifier_suggests_close_name() {
+        let input = "CONTEXT Auth { VAR User VAR Dashboard User GOES TO Dashbord User -> Checkout }";
+
+        let errors = check(input);
+        let help: Vec<_> = errors.iter()
+            .map(|e| miette::Diagnostic::help(e).map(|h| h.to_string()))
+            .collect();
+        assert_eq!(help, vec![Some("did you mean `Dashboard`?".to_string()), None]);
+    }
+
+    fn check_dups(input: &str) -> Result<()> {
+        check_duplicates(&parse_program(input), input)
+    }
+
+    #[test]
+    fn test_duplicate_declaration_in_one_context() {
+        let input = "CONTEXT Auth {\n    VAR User\n    VAR Session\n    VAR User:Entity\n}";
+
+        match check_dups(input) {
+            Err(NaviLangError::SemanticError { message, span, .. }) => {
+                assert!(message.contains("'User'"));
+                assert!(message.contains("line 2, column 5"));
+                assert_eq!(span.offset(), input.find("VAR User:Entity").unwrap());
+            }
+            other => panic!("Expected a single SemanticError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_shadowing_in_nested_context_is_allowed() {
+        let input = "CONTEXT Outer { VAR User CONTEXT Inner { VAR User } } CONTEXT Other { VAR User }";
+        assert!(check_dups(input).is_ok());
+    }
+
+    #[test]
+    fn test_start_must_be_declared_and_unique() {
+        match check("CONTEXT Auth { VAR User START Ghost User -> User }").as_slice() {
+            [NaviLangError::UnknownIdentifier { name, .. }] => assert_eq!(name, "Ghost"),
+            other => panic!("Expected UnknownIdentifier, got {:?}", other),
+        }
+        assert!(check("CONTEXT Auth { VAR User START User }").is_empty());
+
+        let input = "CONTEXT Auth {\n    VAR User\n    START User\n    IF User IS true THEN START User\n    CONTEXT Inner { START User }\n}";
+        match check_dups(input) {
+            Err(NaviLangError::SemanticError { message, span, .. }) => {
+                assert_eq!(message, "Context 'Auth' already has a START (at line 3, column 5)");
+                assert_eq!(span.offset(), input.rfind("START User\n").unwrap());
+            }
+            other => panic!("Expected a single SemanticError, got {:?}", other),
+        }
+    }
+}
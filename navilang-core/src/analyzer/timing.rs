@@ -0,0 +1,109 @@
+// Timing - worst-case time spent waiting on interactions
+use crate::error::NaviLangError;
+use crate::parser::ast::{Context, Program, Statement, StatementKind};
+use crate::utils::duration::Duration;
+
+/// Worst-case time of every interaction in `program`, summed
+///
+/// An interaction with `RETRY n TIMEOUT t` may take `n * t`, and one with only
+/// a timeout `t`; interactions without a timeout are not counted. Statements
+/// are summed as if they ran one after another, so parallel branches and loops
+/// count once each.
+pub fn total_budget(program: &Program) -> Duration {
+    contexts_budget(&program.contexts)
+}
+
+fn contexts_budget(contexts: &[Context]) -> Duration {
+    contexts.iter()
+        .map(|context| context_budget(context) + contexts_budget(&context.children))
+        .sum()
+}
+
+/// Worst-case time of the interactions directly in `context`, including
+/// those inside its blocks but not those of nested contexts
+pub fn context_budget(context: &Context) -> Duration {
+    statements_budget(&context.statements)
+}
+
+fn statements_budget(statements: &[Statement]) -> Duration {
+    statements.iter()
+        .map(|stmt| match &stmt.kind {
+            StatementKind::Interaction { retry, timeout: Some(timeout), .. } => {
+                let attempts = retry.map_or(1, |retry| retry.max(1) as u64);
+                timeout.saturating_mul(attempts)
+            }
+            StatementKind::Conditional { then_branch, else_branch, .. } => {
+                statements_budget(then_branch) + statements_budget(else_branch.as_deref().unwrap_or_default())
+            }
+            StatementKind::Loop { body, .. } | StatementKind::Batch { body, .. } => statements_budget(body),
+            StatementKind::Parallel { branches, .. } => branches.iter().map(|branch| statements_budget(branch)).sum(),
+            _ => Duration::from_millis(0),
+        })
+        .sum()
+}
+
+/// Semantic errors for contexts whose interactions may take longer than
+/// `budget`, meant to be reported as warnings
+///
+/// Each context is measured on its own, without its nested contexts, and
+/// reported at its span.
+pub fn check_timeout_budget(program: &Program, src: &str, budget: Duration) -> Vec<NaviLangError> {
+    fn check(contexts: &[Context], src: &str, budget: Duration, warnings: &mut Vec<NaviLangError>) {
+        for context in contexts {
+            let total = context_budget(context);
+            if total.as_millis() > budget.as_millis() {
+                let message = format!(
+                    "Context '{}' may wait up to {} on interactions, over the {} timeout budget",
+                    context.name, total, budget
+                );
+                warnings.push(NaviLangError::semantic_error(message, src.to_string(), context.span.clone()));
+            }
+            check(&context.children, src, budget, warnings);
+        }
+    }
+
+    let mut warnings = Vec::new();
+    check(&program.contexts, src, budget, &mut warnings);
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    #[test]
+    fn test_retries_exceed_budget() {
+        let input = "CONTEXT Checkout {
+    User CALLS Payment RETRY 3 TIMEOUT 30s
+    User CALLS Shipping RETRY 3 TIMEOUT 30s
+    User CALLS Audit
+}
+CONTEXT Browse { User CALLS Catalog TIMEOUT 1m }";
+        let program = parse_program(input);
+        assert_eq!(total_budget(&program), "4m".parse().unwrap());
+
+        let warnings = check_timeout_budget(&program, input, "2m".parse().unwrap());
+        assert_eq!(warnings.len(), 1);
+        match &warnings[0] {
+            NaviLangError::SemanticError { message, span, .. } => {
+                assert_eq!(message, "Context 'Checkout' may wait up to 3m on interactions, over the 2m timeout budget");
+                assert_eq!(span.offset(), 0);
+            }
+            other => panic!("Expected SemanticError, got {:?}", other),
+        }
+
+        assert!(check_timeout_budget(&program, input, "3m".parse().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_nested_blocks_and_contexts() {
+        let program = parse_program("CONTEXT App {
+    IF Ready IS true THEN User CALLS Api TIMEOUT 10s ELSE User CALLS Backup TIMEOUT 5s
+    PARALLEL { User CALLS A TIMEOUT 1s  User CALLS B RETRY 2 TIMEOUT 1s }
+    CONTEXT Child { User CALLS Slow TIMEOUT 1h }
+}");
+        assert_eq!(context_budget(&program.contexts[0]), "18s".parse().unwrap());
+        assert_eq!(total_budget(&program), "3618s".parse().unwrap());
+    }
+}
@@ -0,0 +1,249 @@
+// Type checking - role expectations of interaction verbs and operands of comparisons
+use super::resolve::SymbolTable;
+use crate::error::{ErrorCollector, NaviLangError, Result};
+use crate::parser::ast::{
+    Context, InteractionVerb, Predicate, Program, Statement, StatementKind, TypeAnnotation, Value, Visitor,
+    walk_program,
+};
+
+/// Which side of an interaction a rule constrains
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Subject,
+    Object,
+}
+
+/// A constraint on the declared type of one side of an interaction
+#[derive(Debug)]
+pub struct InteractionRule {
+    pub verb: InteractionVerb,
+    pub role: Role,
+    pub allowed: &'static [TypeAnnotation],
+}
+
+const DATA: &[TypeAnnotation] = &[
+    TypeAnnotation::Object,
+    TypeAnnotation::String,
+    TypeAnnotation::Number,
+    TypeAnnotation::Boolean,
+];
+
+/// Role expectations checked by [`check_operand_types`]
+///
+/// | verb     | role    | allowed types                     |
+/// |----------|---------|-----------------------------------|
+/// | CALLS    | object  | Service, Endpoint                 |
+/// | RECEIVES | object  | Object, String, Number, Boolean   |
+/// | RETURNS  | subject | Service, Endpoint                 |
+/// | RETURNS  | object  | Object, String, Number, Boolean   |
+///
+/// Sides without a rule accept any type. Add a row here to extend the checks.
+pub const INTERACTION_RULES: &[InteractionRule] = &[
+    InteractionRule {
+        verb: InteractionVerb::Calls,
+        role: Role::Object,
+        allowed: &[TypeAnnotation::Service, TypeAnnotation::Endpoint],
+    },
+    InteractionRule { verb: InteractionVerb::Receives, role: Role::Object, allowed: DATA },
+    InteractionRule {
+        verb: InteractionVerb::Returns,
+        role: Role::Subject,
+        allowed: &[TypeAnnotation::Service, TypeAnnotation::Endpoint],
+    },
+    InteractionRule { verb: InteractionVerb::Returns, role: Role::Object, allowed: DATA },
+];
+
+/// Report interactions whose participants have the wrong declared type, and
+/// ordering comparisons (`<`, `>`, `<=`, `>=`) on operands that are not numbers
+///
+/// Only identifiers declared with a type annotation are checked; undeclared
+/// names are left to `resolve::check_identifiers`. In comparisons, string and
+/// boolean literals are rejected as well.
+pub fn check_operand_types(program: &Program, src: &str) -> Result<()> {
+    let mut check = TypeCheck { table: SymbolTable::new(), src, errors: ErrorCollector::new() };
+    walk_program(&mut check, program);
+    check.errors.into_result(())
+}
+
+struct TypeCheck<'a> {
+    table: SymbolTable,
+    src: &'a str,
+    errors: ErrorCollector,
+}
+
+/// Type of a value in a comparison, or `None` when it is unknown
+///
+/// A list takes its element type from its first item.
+fn value_type(value: &Value, declared: &dyn Fn(&str) -> Option<TypeAnnotation>) -> Option<TypeAnnotation> {
+    match value {
+        Value::Identifier(name) => declared(name),
+        Value::String(_) => Some(TypeAnnotation::String),
+        Value::Boolean(_) => Some(TypeAnnotation::Boolean),
+        Value::Number(_) | Value::Float(_) => Some(TypeAnnotation::Number),
+        Value::Duration(_) => None,
+        Value::Object(_) => Some(TypeAnnotation::Object),
+        Value::Type(annotation) => Some(annotation.clone()),
+        Value::List(items) => {
+            let element = items.first()
+                .and_then(|item| value_type(item, declared))
+                .unwrap_or(TypeAnnotation::Object);
+            Some(TypeAnnotation::List(Box::new(element)))
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for TypeCheck<'_> {
+    fn visit_context(&mut self, context: &'ast Context) {
+        self.table.enter_context(context);
+    }
+
+    fn leave_context(&mut self, _context: &'ast Context) {
+        self.table.pop_scope();
+    }
+
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        let StatementKind::Interaction { subject, verb, object, .. } = &stmt.kind else {
+            return;
+        };
+
+        for rule in INTERACTION_RULES.iter().filter(|rule| rule.verb == *verb) {
+            let name = match rule.role {
+                Role::Subject => Some(subject.as_str()),
+                Role::Object => object.as_identifier(),
+            };
+            let Some(name) = name else {
+                continue;
+            };
+            let found = self.table.lookup(name).and_then(|symbol| symbol.type_annotation.as_ref());
+
+            if let Some(found) = found
+                && !rule.allowed.contains(found)
+            {
+                self.errors.add_error(NaviLangError::type_error(
+                    expected(rule.allowed),
+                    found.to_string(),
+                    self.src.to_string(),
+                    stmt.span_of(name).clone(),
+                ));
+            }
+        }
+    }
+
+    fn visit_predicate(&mut self, predicate: &'ast Predicate) {
+        if !predicate.comparison.is_ordering() {
+            return;
+        }
+
+        let declared = |name: &str| {
+            self.table.lookup(name).and_then(|symbol| symbol.type_annotation.clone())
+        };
+        let operands = [
+            declared(&predicate.left),
+            value_type(&predicate.right, &declared),
+        ];
+
+        for found in operands.into_iter().flatten() {
+            if found != TypeAnnotation::Number {
+                self.errors.add_error(NaviLangError::type_error(
+                    TypeAnnotation::Number.to_string(),
+                    found.to_string(),
+                    self.src.to_string(),
+                    predicate.span.clone(),
+                ));
+            }
+        }
+    }
+}
+
+/// Human-readable list of allowed types, e.g. "Service or Endpoint"
+fn expected(allowed: &[TypeAnnotation]) -> String {
+    let names: Vec<String> = allowed.iter().map(|t| t.to_string()).collect();
+    match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+        _ => names.join(""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    fn check(input: &str) -> Result<()> {
+        check_operand_types(&parse_program(input), input)
+    }
+
+    #[test]
+    fn test_calling_an_entity_is_a_type_error() {
+        let input = "CONTEXT Chat { VAR User:Entity VAR OtherUser:Entity User CALLS OtherUser }";
+
+        match check(input) {
+            Err(NaviLangError::TypeError { expected, found, span, .. }) => {
+                assert_eq!(expected, "Service or Endpoint");
+                assert_eq!(found, "Entity");
+                assert_eq!(span.offset(), input.rfind("OtherUser").unwrap());
+                assert_eq!(span.len(), "OtherUser".len());
+            }
+            other => panic!("Expected TypeError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_well_typed_interactions() {
+        let input = r#"CONTEXT Auth {
+    VAR User:Entity
+    VAR AuthService:Service
+    VAR Session:Object
+    VAR Unknown
+    User CALLS AuthService
+    AuthService RECEIVES "credentials"
+    AuthService RETURNS Session
+    User CALLS Unknown
+    CONTEXT Nested { User CALLS AuthService }
+}"#;
+        assert!(check(input).is_ok());
+    }
+
+    #[test]
+    fn test_returns_checks_subject_and_object() {
+        let input = "CONTEXT A { VAR User:Entity VAR Api:Endpoint User RETURNS Api }";
+
+        match check(input) {
+            Err(NaviLangError::MultipleErrors { errors }) => {
+                let found: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+                assert_eq!(found, vec![
+                    "Type error: expected Service or Endpoint, found Entity",
+                    "Type error: expected Object, String, Number or Boolean, found Endpoint",
+                ]);
+            }
+            other => panic!("Expected MultipleErrors, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ordering_comparisons_need_numbers() {
+        let input = r#"CONTEXT Limits {
+    VAR Count:Number
+    VAR Name:String
+    VAR Untyped
+    IF Count >= 5 THEN Count -> Count
+    WHILE Count < Untyped { BREAK }
+    IF Name != "admin" THEN Name -> Name
+    IF Name > 3 THEN Name -> Name
+    IF Count <= "ten" THEN Count -> Count
+}"#;
+
+        match check(input) {
+            Err(NaviLangError::MultipleErrors { errors }) => {
+                let found: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+                assert_eq!(found, vec![
+                    "Type error: expected Number, found String",
+                    "Type error: expected Number, found String",
+                ]);
+                let NaviLangError::TypeError { span, .. } = &errors[0] else { unreachable!() };
+                assert_eq!(span.offset(), input.find("Name > 3").unwrap());
+            }
+            other => panic!("Expected MultipleErrors, got {:?}", other),
+        }
+    }
+}
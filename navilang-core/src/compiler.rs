@@ -0,0 +1,106 @@
+// Compiler - repeated compilation with a content-hash cache
+use crate::{CompilationResult, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Compiles files, reusing earlier results for sources that did not change
+///
+/// Entries are keyed by path and the SHA-256 of the source after `INCLUDE`
+/// resolution, so editing an included file also triggers a recompile. Files
+/// are still read on every call; only lexing and parsing are skipped.
+#[derive(Debug, Default)]
+pub struct Compiler {
+    cache: HashMap<PathBuf, ([u8; 32], CompilationResult)>,
+    compilations: usize,
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compile `path`, returning the cached result if its content is unchanged
+    ///
+    /// Failed compilations are not cached.
+    pub fn compile<P: AsRef<Path>>(&mut self, path: P) -> Result<&CompilationResult> {
+        let path = path.as_ref();
+        let source = crate::load_file(path)?;
+        let hash: [u8; 32] = Sha256::digest(source.content.as_bytes()).into();
+
+        let fresh = self.cache.get(path).is_some_and(|(cached, _)| *cached == hash);
+        if !fresh {
+            let result = crate::compile_source(source)?;
+            self.compilations += 1;
+            self.cache.insert(path.to_path_buf(), (hash, result));
+        }
+
+        Ok(&self.cache[path].1)
+    }
+
+    /// Drop the cached result for `path`, forcing the next compile to run
+    pub fn invalidate<P: AsRef<Path>>(&mut self, path: P) {
+        self.cache.remove(path.as_ref());
+    }
+
+    /// Number of times the pipeline actually ran, excluding cache hits
+    pub fn compilations(&self) -> usize {
+        self.compilations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unchanged_source_is_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("flow.navi");
+        std::fs::write(&path, "CONTEXT A { VAR User }").unwrap();
+
+        let mut compiler = Compiler::new();
+        compiler.compile(&path).unwrap();
+        let result = compiler.compile(&path).unwrap();
+        assert_eq!(result.contexts()[0].name, "A");
+        assert_eq!(compiler.compilations(), 1);
+
+        std::fs::write(&path, "CONTEXT B { VAR User }").unwrap();
+        assert_eq!(compiler.compile(&path).unwrap().contexts()[0].name, "B");
+        assert_eq!(compiler.compilations(), 2);
+
+        compiler.invalidate(&path);
+        compiler.compile(&path).unwrap();
+        assert_eq!(compiler.compilations(), 3);
+    }
+
+    #[test]
+    fn test_included_file_changes_invalidate() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("main.navi");
+        let shared = dir.path().join("shared.navi");
+        std::fs::write(&main, "INCLUDE \"shared.navi\"\nCONTEXT Main { VAR User }").unwrap();
+        std::fs::write(&shared, "CONTEXT Shared { VAR Api }").unwrap();
+
+        let mut compiler = Compiler::new();
+        compiler.compile(&main).unwrap();
+        compiler.compile(&main).unwrap();
+        assert_eq!(compiler.compilations(), 1);
+
+        std::fs::write(&shared, "CONTEXT Shared { VAR Api VAR Db }").unwrap();
+        compiler.compile(&main).unwrap();
+        assert_eq!(compiler.compilations(), 2);
+    }
+
+    #[test]
+    fn test_failed_compilation_is_not_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.navi");
+        std::fs::write(&path, "CONTEXT A {").unwrap();
+
+        let mut compiler = Compiler::new();
+        assert!(compiler.compile(&path).is_err());
+        assert!(compiler.compile(&path).is_err());
+        assert_eq!(compiler.compilations(), 0);
+    }
+}
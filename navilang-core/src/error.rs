@@ -1,8 +1,9 @@
-use miette::{Diagnostic, SourceSpan};
+use miette::{Diagnostic as _, SourceSpan};
+use std::fmt;
 use thiserror::Error;
 
 /// Main error type for NaviLang compiler
-#[derive(Error, Diagnostic, Debug)]
+#[derive(Error, miette::Diagnostic, Debug)]
 pub enum NaviLangError {
     #[error("Syntax error: {message}")]
     #[diagnostic(code(syntax::invalid))]
@@ -43,6 +44,8 @@ pub enum NaviLangError {
         src: String,
         #[label("Unknown identifier")]
         span: SourceSpan,
+        #[help]
+        suggestion: Option<String>,
     },
     
     #[error("Flow validation error: {message}")]
@@ -58,16 +61,41 @@ pub enum NaviLangError {
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     
+    /// A source file that could not be read, with the path that was tried
+    #[error("Failed to read file {path}")]
+    #[diagnostic(code(io::file))]
+    FileError {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    
     #[error("Generic error: {0}")]
-    GenericError(#[from] anyhow::Error),
+    GenericError(#[source] anyhow::Error),
+    
+    /// Note closing the errors of an `ErrorCollector` that reached its limit
+    #[error("Too many errors, stopped after {limit}")]
+    #[diagnostic(code(compilation::too_many_errors))]
+    TooManyErrors {
+        limit: usize,
+    },
     
     #[error("Multiple errors occurred")]
     #[diagnostic(code(compilation::multiple_errors))]
     MultipleErrors {
+        #[related]
         errors: Vec<NaviLangError>,
     },
 }
 
+/// Errors raised as a `NaviLangError` inside `anyhow` code, such as the
+/// reader's, keep their variant; anything else becomes a `GenericError`
+impl From<anyhow::Error> for NaviLangError {
+    fn from(error: anyhow::Error) -> Self {
+        error.downcast::<NaviLangError>().unwrap_or_else(NaviLangError::GenericError)
+    }
+}
+
 /// Convenience type alias for Results with NaviLangError
 pub type Result<T> = std::result::Result<T, NaviLangError>;
 
@@ -141,23 +169,217 @@ impl Span {
         
         Span::new(start, end)
     }
+    
+    /// Smallest span covering every span in `spans`, or `None` if it is empty
+    pub fn enclose(spans: &[Span]) -> Option<Span> {
+        let (first, rest) = spans.split_first()?;
+        Some(rest.iter().fold(first.clone(), |acc, span| acc.combine(span)))
+    }
+    
+    /// Whether `pos` lies within the span
+    ///
+    /// The end is exclusive, except that an empty span contains its own start.
+    pub fn contains(&self, pos: Position) -> bool {
+        if self.start.offset == self.end.offset {
+            return pos.offset == self.start.offset;
+        }
+        self.start.offset <= pos.offset && pos.offset < self.end.offset
+    }
+}
+
+/// How serious a reported problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Severity {
+    /// Fails compilation
+    Error,
+    /// Reported, but compilation still succeeds
+    Warning,
+}
+
+/// An error paired with its severity
+///
+/// Renders through miette like the wrapped error, but with the severity
+/// overridden, so warnings show as warnings.
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub error: NaviLangError,
+}
+
+impl Diagnostic {
+    pub fn error(error: NaviLangError) -> Self {
+        Self { severity: Severity::Error, error }
+    }
+    
+    pub fn warning(error: NaviLangError) -> Self {
+        Self { severity: Severity::Warning, error }
+    }
+    
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+    
+    /// Render as JSON in the same shape as `to_json`, with this severity
+    pub fn to_json(&self) -> serde_json::Value {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        
+        let mut errors = Vec::new();
+        flatten(&self.error, &mut errors);
+        
+        let mut json = to_json(&self.error);
+        let items = match &mut json {
+            serde_json::Value::Array(items) => items.iter_mut().collect(),
+            item => vec![item],
+        };
+        for (item, error) in items.into_iter().zip(errors) {
+            item["severity"] = severity.into();
+            item["message"] = message_with_severity(self.severity, error).into();
+        }
+        json
+    }
+}
+
+/// Warnings read "Warning: ..." rather than the wrapped variant's
+/// "Semantic error: ...", which would contradict their severity
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&message_with_severity(self.severity, &self.error))
+    }
+}
+
+/// Text of `error` when reported with `severity`, as in `Diagnostic`'s
+/// `Display`
+pub fn message_with_severity(severity: Severity, error: &NaviLangError) -> String {
+    match severity {
+        Severity::Error => error.to_string(),
+        Severity::Warning => format!("Warning: {}", error.detail()),
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+impl miette::Diagnostic for Diagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.error.code()
+    }
+    
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(match self.severity {
+            Severity::Error => miette::Severity::Error,
+            Severity::Warning => miette::Severity::Warning,
+        })
+    }
+    
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.error.help()
+    }
+    
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.error.source_code()
+    }
+    
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        self.error.labels()
+    }
+    
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn miette::Diagnostic> + 'a>> {
+        self.error.related()
+    }
 }
 
+/// Number of errors the CLI reports per file unless `--max-errors` says otherwise
+pub const DEFAULT_MAX_ERRORS: usize = 20;
+
 /// Error collector for batch processing and error recovery
+///
+/// Warnings are kept separately: they are reported alongside errors but never
+/// make `into_result` fail.
 #[derive(Debug, Default)]
 pub struct ErrorCollector {
     errors: Vec<NaviLangError>,
+    warnings: Vec<NaviLangError>,
+    limit: Option<usize>,
 }
 
 impl ErrorCollector {
     pub fn new() -> Self {
         Self {
             errors: Vec::new(),
+            warnings: Vec::new(),
+            limit: None,
         }
     }
     
+    /// A collector that keeps the first `limit` errors, then records a single
+    /// `TooManyErrors` note and ignores the rest
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Self::new()
+        }
+    }
+    
+    /// [`with_limit`](Self::with_limit) when `limit` is given, otherwise a
+    /// collector that keeps every error
+    pub fn with_optional_limit(limit: Option<usize>) -> Self {
+        Self {
+            limit,
+            ..Self::new()
+        }
+    }
+    
+    /// Whether an error past the limit has been recorded as `TooManyErrors`,
+    /// so further errors are dropped and the producer can stop
+    pub fn is_full(&self) -> bool {
+        self.limit.is_some_and(|limit| self.errors.len() > limit)
+    }
+    
+    pub fn add_warning(&mut self, warning: NaviLangError) {
+        self.warnings.push(warning);
+    }
+    
+    pub fn warnings(&self) -> &[NaviLangError] {
+        &self.warnings
+    }
+    
+    /// All collected problems, errors first, tagged with their severity
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.errors.into_iter()
+            .map(Diagnostic::error)
+            .chain(self.warnings.into_iter().map(Diagnostic::warning))
+            .collect()
+    }
+    
     pub fn add_error(&mut self, error: NaviLangError) {
-        self.errors.push(error);
+        match self.limit {
+            Some(limit) if self.errors.len() == limit => {
+                self.errors.push(NaviLangError::TooManyErrors { limit });
+            }
+            Some(limit) if self.errors.len() > limit => {}
+            _ => self.errors.push(error),
+        }
+    }
+    
+    /// Record the error of a failed result, if any, returning the success value
+    ///
+    /// `MultipleErrors` are flattened so the collector never nests them.
+    pub fn add_result<T>(&mut self, result: Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(NaviLangError::MultipleErrors { errors }) => {
+                for error in errors {
+                    self.add_error(error);
+                }
+                None
+            }
+            Err(error) => {
+                self.add_error(error);
+                None
+            }
+        }
     }
     
     pub fn has_errors(&self) -> bool {
@@ -181,10 +403,26 @@ impl ErrorCollector {
     pub fn errors(&self) -> &[NaviLangError] {
         &self.errors
     }
+    
+    pub fn into_errors(self) -> Vec<NaviLangError> {
+        self.errors
+    }
 }
 
 /// Helper functions for creating common errors
 impl NaviLangError {
+    /// The error's text without the "Semantic error: " style prefix naming
+    /// its kind
+    fn detail(&self) -> String {
+        match self {
+            Self::SyntaxError { message, .. }
+            | Self::SemanticError { message, .. }
+            | Self::FlowError { message, .. } => message.clone(),
+            Self::TypeError { expected, found, .. } => format!("expected {}, found {}", expected, found),
+            error => error.to_string(),
+        }
+    }
+    
     pub fn syntax_error(message: String, src: String, span: Span) -> Self {
         Self::SyntaxError {
             message,
@@ -215,6 +453,17 @@ impl NaviLangError {
             name,
             src,
             span: span.to_miette_span(),
+            suggestion: None,
+        }
+    }
+    
+    /// An unknown identifier error suggesting the name that was probably meant
+    pub fn unknown_identifier_with_suggestion(name: String, suggestion: &str, src: String, span: Span) -> Self {
+        Self::UnknownIdentifier {
+            name,
+            src,
+            span: span.to_miette_span(),
+            suggestion: Some(format!("did you mean `{}`?", suggestion)),
         }
     }
     
@@ -227,6 +476,145 @@ impl NaviLangError {
     }
 }
 
+/// Render an error as JSON for editors and other tools
+///
+/// A single error becomes `{code, severity, message, spans}`, where each span
+/// is `{offset, len, label}`. `MultipleErrors` is flattened into an array of
+/// such objects.
+pub fn to_json(err: &NaviLangError) -> serde_json::Value {
+    match err {
+        NaviLangError::MultipleErrors { errors } => {
+            let mut flattened = Vec::new();
+            for error in errors {
+                match to_json(error) {
+                    serde_json::Value::Array(items) => flattened.extend(items),
+                    item => flattened.push(item),
+                }
+            }
+            serde_json::Value::Array(flattened)
+        }
+        error => {
+            let spans: Vec<serde_json::Value> = error.labels()
+                .into_iter()
+                .flatten()
+                .map(|label| serde_json::json!({
+                    "offset": label.offset(),
+                    "len": label.len(),
+                    "label": label.label(),
+                }))
+                .collect();
+            
+            serde_json::json!({
+                "code": error.code().map(|code| code.to_string()),
+                "severity": "error",
+                "message": error.to_string(),
+                "spans": spans,
+            })
+        }
+    }
+}
+
+/// Render errors as a SARIF 2.1.0 log, for code scanning and quality gates
+///
+/// Each error (with `MultipleErrors` flattened) becomes one result whose
+/// `ruleId` is the error code and whose region is the primary span, in the
+/// 1-based lines and columns SARIF expects. `src_path` is the artifact URI.
+pub fn to_sarif(errors: &[NaviLangError], src_path: &str) -> serde_json::Value {
+    let results = errors.iter().map(|error| (Severity::Error, error, src_path));
+    sarif_log(results)
+}
+
+/// Like `to_sarif`, keeping the severity of each diagnostic as its level
+pub fn diagnostics_to_sarif(diagnostics: &[Diagnostic], src_path: &str) -> serde_json::Value {
+    let results = diagnostics.iter().map(|diagnostic| (diagnostic.severity, &diagnostic.error, src_path));
+    sarif_log(results)
+}
+
+/// Like `diagnostics_to_sarif` for several files at once, each paired with
+/// its artifact URI, so a whole run is reported in one log
+pub fn files_to_sarif(files: &[(String, Vec<Diagnostic>)]) -> serde_json::Value {
+    let results = files.iter().flat_map(|(src_path, diagnostics)| {
+        diagnostics.iter().map(move |diagnostic| (diagnostic.severity, &diagnostic.error, src_path.as_str()))
+    });
+    sarif_log(results)
+}
+
+/// The individual errors inside `error`, with `MultipleErrors` unwrapped
+fn flatten<'a>(error: &'a NaviLangError, found: &mut Vec<&'a NaviLangError>) {
+    match error {
+        NaviLangError::MultipleErrors { errors } => {
+            for error in errors {
+                flatten(error, found);
+            }
+        }
+        error => found.push(error),
+    }
+}
+
+fn sarif_log<'a>(diagnostics: impl Iterator<Item = (Severity, &'a NaviLangError, &'a str)>) -> serde_json::Value {
+    let mut rules: Vec<String> = Vec::new();
+    let mut results = Vec::new();
+    for (severity, error, src_path) in diagnostics {
+        let mut flattened = Vec::new();
+        flatten(error, &mut flattened);
+        
+        for error in flattened {
+            let rule_id = error.code().map(|code| code.to_string()).unwrap_or_default();
+            if !rules.contains(&rule_id) {
+                rules.push(rule_id.clone());
+            }
+            
+            let mut location = serde_json::json!({
+                "physicalLocation": { "artifactLocation": { "uri": src_path } },
+            });
+            if let Some(region) = sarif_region(error) {
+                location["physicalLocation"]["region"] = region;
+            }
+            
+            results.push(serde_json::json!({
+                "ruleId": rule_id,
+                "level": match severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                },
+                "message": { "text": message_with_severity(severity, error) },
+                "locations": [location],
+            }));
+        }
+    }
+    
+    let rules: Vec<serde_json::Value> = rules.into_iter().map(|id| serde_json::json!({ "id": id })).collect();
+    serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "navilang",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// Start and end of the primary label; `endColumn` is exclusive
+fn sarif_region(error: &NaviLangError) -> Option<serde_json::Value> {
+    let label = error.labels()?.next()?;
+    let source = error.source_code()?;
+    let start = source.read_span(label.inner(), 0, 0).ok()?;
+    let end = source.read_span(&SourceSpan::new((label.offset() + label.len()).into(), 0.into()), 0, 0).ok()?;
+    
+    Some(serde_json::json!({
+        "startLine": start.line() + 1,
+        "startColumn": start.column() + 1,
+        "endLine": end.line() + 1,
+        "endColumn": end.column() + 1,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -258,6 +646,32 @@ mod tests {
         assert_eq!(miette_span.len(), 4);
     }
     
+    #[test]
+    fn test_span_enclose() {
+        let a = Span::new(Position::new(1, 5, 4), Position::new(1, 8, 7));
+        let b = Span::new(Position::new(2, 1, 10), Position::new(2, 3, 12));
+        let c = Span::new(Position::new(1, 1, 0), Position::new(1, 2, 1));
+        
+        let enclosed = Span::enclose(&[a.clone(), b.clone(), c.clone()]).unwrap();
+        assert_eq!(enclosed, Span::new(c.start, b.end));
+        assert_eq!(Span::enclose(std::slice::from_ref(&a)), Some(a));
+        assert_eq!(Span::enclose(&[]), None);
+    }
+    
+    #[test]
+    fn test_span_contains_boundaries() {
+        let span = Span::new(Position::new(1, 3, 2), Position::new(1, 6, 5));
+        
+        assert!(!span.contains(Position::new(1, 2, 1)));
+        assert!(span.contains(Position::new(1, 3, 2)));
+        assert!(span.contains(Position::new(1, 5, 4)));
+        assert!(!span.contains(Position::new(1, 6, 5)));
+        
+        let empty = Span::new(Position::new(1, 3, 2), Position::new(1, 3, 2));
+        assert!(empty.contains(Position::new(1, 3, 2)));
+        assert!(!empty.contains(Position::new(1, 4, 3)));
+    }
+    
     #[test]
     fn test_error_collector() {
         let mut collector = ErrorCollector::new();
@@ -273,4 +687,171 @@ mod tests {
         let result: Result<()> = collector.into_result(());
         assert!(result.is_err());
     }
+    
+    #[test]
+    fn test_anyhow_keeps_navilang_errors() {
+        let file = NaviLangError::FileError {
+            path: "a.navi".to_string(),
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "gone"),
+        };
+        let wrapped = anyhow::Error::new(file);
+        assert!(matches!(NaviLangError::from(wrapped), NaviLangError::FileError { .. }));
+
+        let other = NaviLangError::from(anyhow::anyhow!("something else"));
+        assert!(matches!(other, NaviLangError::GenericError(_)));
+        assert_eq!(other.to_string(), "Generic error: something else");
+    }
+
+    #[test]
+    fn test_add_result_flattens_multiple_errors() {
+        let span = Span::new(Position::new(1, 1, 0), Position::new(1, 2, 1));
+        let error = |m: &str| NaviLangError::semantic_error(m.to_string(), String::new(), span.clone());
+        
+        let mut collector = ErrorCollector::new();
+        assert_eq!(collector.add_result(Ok(1)), Some(1));
+        collector.add_result::<()>(Err(error("a")));
+        collector.add_result::<()>(Err(NaviLangError::MultipleErrors {
+            errors: vec![error("b"), error("c")],
+        }));
+        
+        assert_eq!(collector.error_count(), 3);
+    }
+    
+    #[test]
+    fn test_collector_stops_at_limit() {
+        let span = Span::new(Position::new(1, 1, 0), Position::new(1, 2, 1));
+        let error = |m: &str| NaviLangError::semantic_error(m.to_string(), String::new(), span.clone());
+        
+        let mut collector = ErrorCollector::with_limit(2);
+        collector.add_error(error("a"));
+        collector.add_error(error("b"));
+        // At the limit, but nothing has been dropped yet
+        assert!(!collector.is_full());
+        collector.add_result::<()>(Err(NaviLangError::MultipleErrors {
+            errors: vec![error("c"), error("d")],
+        }));
+        collector.add_error(error("e"));
+        
+        assert!(collector.is_full());
+        let messages: Vec<String> = collector.errors().iter().map(ToString::to_string).collect();
+        assert_eq!(messages, vec![
+            "Semantic error: a",
+            "Semantic error: b",
+            "Too many errors, stopped after 2",
+        ]);
+        assert!(!ErrorCollector::with_optional_limit(None).is_full());
+    }
+    
+    #[test]
+    fn test_syntax_error_to_json() {
+        let span = Span::new(Position::new(1, 9, 8), Position::new(1, 13, 12));
+        let error = NaviLangError::syntax_error("Expected '{'".to_string(), "CONTEXT Test VAR".to_string(), span);
+        
+        let json = to_json(&error);
+        assert_eq!(json["code"], "syntax::invalid");
+        assert_eq!(json["severity"], "error");
+        assert_eq!(json["message"], "Syntax error: Expected '{'");
+        assert_eq!(json["spans"][0]["offset"], 8);
+        assert_eq!(json["spans"][0]["len"], 4);
+        assert_eq!(json["spans"][0]["label"], "Error occurred here");
+    }
+    
+    #[test]
+    fn test_multiple_errors_to_json_are_flattened() {
+        let span = Span::new(Position::new(1, 1, 0), Position::new(1, 2, 1));
+        let error = |name: &str| NaviLangError::unknown_identifier(name.to_string(), String::new(), span.clone());
+        let nested = NaviLangError::MultipleErrors {
+            errors: vec![error("A"), NaviLangError::MultipleErrors { errors: vec![error("B"), error("C")] }],
+        };
+        
+        let json = to_json(&nested);
+        let items = json.as_array().unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[2]["message"], "Unknown identifier: C");
+        assert_eq!(items[0]["code"], "semantic::unknown_identifier");
+    }
+    
+    #[test]
+    fn test_warnings_do_not_fail_into_result() {
+        let span = Span::new(Position::new(1, 1, 0), Position::new(1, 2, 1));
+        let mut collector = ErrorCollector::new();
+        collector.add_warning(NaviLangError::flow_error("unused".to_string(), String::new(), span));
+        
+        assert!(!collector.has_errors());
+        assert_eq!(collector.warnings().len(), 1);
+        
+        let diagnostics = collector.into_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(!diagnostics[0].is_error());
+        assert_eq!(diagnostics[0].to_json()["severity"], "warning");
+        assert_eq!(diagnostics[0].to_json()["message"], "Warning: unused");
+        assert_eq!(diagnostics[0].to_string(), "Warning: unused");
+        assert_eq!(
+            miette::Diagnostic::severity(&diagnostics[0]),
+            Some(miette::Severity::Warning)
+        );
+    }
+    
+    #[test]
+    fn test_sarif_log_structure() {
+        let src = "CONTEXT Auth {\n    User -> Dashbord\n}".to_string();
+        let span = Span::new(Position::new(2, 5, 19), Position::new(2, 21, 35));
+        let errors = vec![
+            NaviLangError::unknown_identifier("Dashbord".to_string(), src.clone(), span.clone()),
+            NaviLangError::MultipleErrors {
+                errors: vec![NaviLangError::semantic_error("dup".to_string(), src, span)],
+            },
+        ];
+        
+        let sarif = to_sarif(&errors, "auth.navi");
+        assert_eq!(sarif["$schema"], "https://json.schemastore.org/sarif-2.1.0.json");
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(sarif["runs"].as_array().unwrap().len(), 1);
+        assert_eq!(sarif["runs"][0]["tool"]["driver"]["name"], "navilang");
+        assert_eq!(sarif["runs"][0]["tool"]["driver"]["rules"].as_array().unwrap().len(), 2);
+        
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["ruleId"], "semantic::unknown_identifier");
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(results[0]["message"]["text"], "Unknown identifier: Dashbord");
+        
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "auth.navi");
+        assert_eq!(location["region"], serde_json::json!({
+            "startLine": 2, "startColumn": 5, "endLine": 2, "endColumn": 21,
+        }));
+        assert_eq!(results[1]["ruleId"], "semantic::invalid");
+    }
+    
+    #[test]
+    fn test_sarif_levels_follow_severity() {
+        let span = Span::new(Position::new(1, 1, 0), Position::new(1, 2, 1));
+        let warning = Diagnostic::warning(NaviLangError::flow_error("unused".to_string(), "A".to_string(), span));
+        
+        let sarif = diagnostics_to_sarif(&[warning], "<stdin>");
+        assert_eq!(sarif["runs"][0]["results"][0]["level"], "warning");
+        assert_eq!(sarif["runs"][0]["results"][0]["ruleId"], "flow::invalid");
+        assert_eq!(sarif["runs"][0]["results"][0]["message"]["text"], "Warning: unused");
+    }
+    
+    #[test]
+    fn test_sarif_log_of_several_files() {
+        let span = Span::new(Position::new(1, 1, 0), Position::new(1, 2, 1));
+        let files = vec![
+            ("a.navi".to_string(), vec![Diagnostic::warning(NaviLangError::flow_error("unused".to_string(), "A".to_string(), span.clone()))]),
+            ("b.navi".to_string(), Vec::new()),
+            ("c.navi".to_string(), vec![Diagnostic::error(NaviLangError::semantic_error("dup".to_string(), "C".to_string(), span))]),
+        ];
+        
+        let sarif = files_to_sarif(&files);
+        assert_eq!(sarif["runs"].as_array().unwrap().len(), 1);
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        let uris: Vec<_> = results.iter()
+            .map(|result| result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"].as_str().unwrap())
+            .collect();
+        assert_eq!(uris, ["a.navi", "c.navi"]);
+        assert_eq!(results[0]["level"], "warning");
+        assert_eq!(results[1]["level"], "error");
+    }
 }
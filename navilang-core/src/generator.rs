@@ -1,11 +0,0 @@
-// Generator module - Code Generation Stage
-// This module will implement diagram generation, API generation, documentation generation, and code generation
-
-// Placeholder for now
-pub struct CodeGenerator;
-
-impl CodeGenerator {
-    pub fn new() -> Self {
-        Self
-    }
-}
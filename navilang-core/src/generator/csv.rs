@@ -0,0 +1,63 @@
+// CSV generator - the flow graph as an edge list
+use crate::parser::ast::Program;
+use super::edges;
+
+/// Render every edge of a program's graph as CSV rows `source,target,type`,
+/// after a header row
+///
+/// Edges come in source order, one row per statement, so a flow written
+/// twice appears twice. `type` is `flow`, `uses`, `creates`, `calls`,
+/// `receives` or `returns`. Fields holding a comma, quote or line break are quoted.
+pub fn to_edge_list(program: &Program) -> String {
+    let mut out = String::from("source,target,type\n");
+    for edge in edges(program) {
+        out.push_str(&format!("{},{},{}\n", field(edge.source), field(edge.target), edge.kind));
+    }
+    out
+}
+
+/// A CSV field, quoted with inner quotes doubled when it needs to be
+fn field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    fn generate(input: &str) -> String {
+        to_edge_list(&parse_program(input))
+    }
+
+    #[test]
+    fn test_edge_rows() {
+        let output = generate(r#"CONTEXT Shop {
+    VAR User:Entity
+    User -> Cart
+    IF Cart IS "full" THEN Cart GOES TO Checkout
+    ASYNC Checkout CALLS Payments
+    Checkout RETURNS "receipt"
+    Payments USES Ledger
+    User DOES Browse
+}"#);
+
+        assert_eq!(output, "source,target,type
+User,Cart,flow
+Cart,Checkout,flow
+Checkout,Payments,calls
+Payments,Ledger,uses
+");
+    }
+
+    #[test]
+    fn test_fields_are_quoted_when_needed() {
+        assert_eq!(field("User"), "User");
+        assert_eq!(field("a,b"), "\"a,b\"");
+        assert_eq!(field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,380 @@
+// DOT generator - Graphviz digraphs from flow and interaction statements
+use crate::analyzer::graph::{dependency_edges, find_dependency_cycles};
+use crate::analyzer::resolve::declarations;
+use crate::parser::ast::{Context, InteractionVerb, Program, Statement, StatementKind, TypeAnnotation};
+use super::batch_label;
+use std::collections::{HashMap, HashSet};
+
+/// Render a program as a Graphviz `digraph`
+///
+/// Each context becomes a `cluster` subgraph. Declared variables are drawn with
+/// a shape matching their type annotation; flows, interactions and `USES`
+/// dependencies become edges, with `ASYNC` interactions drawn dashed and
+/// dependencies dotted. `BATCH` blocks become dashed
+/// clusters labelled "batch of N" around the nodes first seen inside them.
+/// A `START` node gets an arrow from a point marking the entry.
+pub fn to_dot(program: &Program) -> String {
+    let mut builder = DotBuilder::default();
+    builder.line(0, "digraph NaviLang {");
+    builder.line(1, "rankdir=TB;");
+
+    for context in &program.contexts {
+        builder.context(context, &HashMap::new(), 1);
+    }
+
+    builder.line(0, "}");
+    builder.out
+}
+
+/// Render only the `USES` dependencies of a program as a Graphviz `digraph`
+///
+/// Every name taking part in a dependency becomes one node, shaped by its type
+/// annotation wherever it is declared, with one edge per distinct `USES` pair.
+/// Edges inside a dependency cycle are drawn red.
+pub fn to_dependency_graph(program: &Program) -> String {
+    fn collect_types(contexts: &[Context], types: &mut HashMap<String, TypeAnnotation>) {
+        for context in contexts {
+            for stmt in declarations(&context.statements) {
+                if let StatementKind::VarDecl { name, type_annotation: Some(type_annotation) } = &stmt.kind {
+                    types.entry(name.clone()).or_insert_with(|| type_annotation.clone());
+                }
+            }
+            collect_types(&context.children, types);
+        }
+    }
+
+    let mut types = HashMap::new();
+    collect_types(&program.contexts, &mut types);
+    let cycles = find_dependency_cycles(program);
+    let same_cycle = |a: &str, b: &str| cycles.iter().any(|cycle| {
+        cycle.iter().any(|n| n == a) && cycle.iter().any(|n| n == b)
+    });
+
+    let mut builder = DotBuilder::default();
+    builder.line(0, "digraph Dependencies {");
+    builder.line(1, "rankdir=LR;");
+    for (subject, dependency) in dependency_edges(program) {
+        builder.node(&subject, &types, 1);
+        builder.node(&dependency, &types, 1);
+        let attrs = if same_cycle(&subject, &dependency) { " [color=red]" } else { "" };
+        builder.line(1, &format!("{} -> {}{};", quote(&subject), quote(&dependency), attrs));
+    }
+    builder.line(0, "}");
+    builder.out
+}
+
+#[derive(Default)]
+struct DotBuilder {
+    out: String,
+    declared: HashSet<String>,
+    clusters: usize,
+    starts: usize,
+}
+
+impl DotBuilder {
+    fn line(&mut self, depth: usize, text: &str) {
+        self.out.push_str(&"    ".repeat(depth));
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    /// Render a context; `inherited` holds the variable types of enclosing contexts
+    fn context(&mut self, context: &Context, inherited: &HashMap<String, TypeAnnotation>, depth: usize) {
+        let mut types = inherited.clone();
+        for stmt in &context.statements {
+            if let StatementKind::VarDecl { name, type_annotation: Some(type_annotation) } = &stmt.kind {
+                types.insert(name.clone(), type_annotation.clone());
+            }
+        }
+
+        self.clusters += 1;
+        self.line(depth, &format!("subgraph cluster_{} {{", self.clusters));
+        self.line(depth + 1, &format!("label={};", quote(&context.name)));
+
+        for stmt in &context.statements {
+            self.statement(stmt, &types, None, depth + 1);
+        }
+        for child in &context.children {
+            self.context(child, &types, depth + 1);
+        }
+
+        self.line(depth, "}");
+    }
+
+    /// Render a statement; `guard` labels edges inside conditional branches
+    fn statement(
+        &mut self,
+        stmt: &Statement,
+        types: &HashMap<String, TypeAnnotation>,
+        guard: Option<&str>,
+        depth: usize,
+    ) {
+        match &stmt.kind {
+            StatementKind::VarDecl { name, .. } => self.node(name, types, depth),
+            StatementKind::Action { actor, .. } => self.node(actor, types, depth),
+            StatementKind::Flow { from, to, label } => {
+                self.node(from, types, depth);
+                self.node(to, types, depth);
+                let label = match (label, guard) {
+                    (Some(label), Some(guard)) => Some(format!("{} ({})", label, guard)),
+                    (label, guard) => label.clone().or_else(|| guard.map(str::to_string)),
+                };
+                self.edge(from, to, label.as_deref(), depth);
+            }
+            StatementKind::Interaction { subject, verb, object, is_async, .. } => {
+                if let Some(object) = object.as_identifier() {
+                    self.node(subject, types, depth);
+                    self.node(object, types, depth);
+                    let label = match guard {
+                        Some(guard) => format!("{} ({})", verb_label(*verb), guard),
+                        None => verb_label(*verb).to_string(),
+                    };
+                    let mut attrs = format!(" [label={}", quote(&label));
+                    if *is_async {
+                        attrs.push_str(", style=dashed");
+                    }
+                    attrs.push(']');
+                    self.line(depth, &format!("{} -> {}{};", quote(subject), quote(object), attrs));
+                }
+            }
+            StatementKind::Uses { subject, dependency } => {
+                self.node(subject, types, depth);
+                self.node(dependency, types, depth);
+                let label = match guard {
+                    Some(guard) => format!("uses ({})", guard),
+                    None => "uses".to_string(),
+                };
+                self.line(depth, &format!("{} -> {} [label={}, style=dotted];", quote(subject), quote(dependency), quote(&label)));
+            }
+            StatementKind::Creation { subject, creator } => {
+                self.node(creator, types, depth);
+                self.node(subject, types, depth);
+                let label = match guard {
+                    Some(guard) => format!("creates ({})", guard),
+                    None => "creates".to_string(),
+                };
+                self.line(depth, &format!("{} -> {} [label={}];", quote(creator), quote(subject), quote(&label)));
+            }
+            StatementKind::Conditional { condition, then_branch, else_branch } => {
+                let guard = condition.to_string();
+                for stmt in then_branch {
+                    self.statement(stmt, types, Some(&guard), depth);
+                }
+                let negated = format!("NOT ({})", guard);
+                for stmt in else_branch.iter().flatten() {
+                    self.statement(stmt, types, Some(&negated), depth);
+                }
+            }
+            StatementKind::Parallel { branches, .. } => {
+                for stmt in branches.iter().flatten() {
+                    self.statement(stmt, types, guard, depth);
+                }
+            }
+            StatementKind::Loop { body, .. } => {
+                for stmt in body {
+                    self.statement(stmt, types, guard, depth);
+                }
+            }
+            StatementKind::Batch { size, body } => {
+                self.clusters += 1;
+                self.line(depth, &format!("subgraph cluster_{} {{", self.clusters));
+                self.line(depth + 1, &format!("label={};", quote(&batch_label(*size))));
+                self.line(depth + 1, "style=dashed;");
+                for stmt in body {
+                    self.statement(stmt, types, guard, depth + 1);
+                }
+                self.line(depth, "}");
+            }
+            StatementKind::Start { node } => {
+                self.starts += 1;
+                let marker = format!("start_{}", self.starts);
+                self.node(node, types, depth);
+                self.line(depth, &format!("{} [shape=point, label=\"\"];", marker));
+                self.line(depth, &format!("{} -> {};", marker, quote(node)));
+            }
+            StatementKind::Break
+            | StatementKind::Continue
+            | StatementKind::Ordering { .. }
+            | StatementKind::Endpoint { .. } => {}
+        }
+    }
+
+    /// Declare a node the first time an identifier is seen
+    fn node(&mut self, name: &str, types: &HashMap<String, TypeAnnotation>, depth: usize) {
+        if self.declared.insert(name.to_string()) {
+            let shape = node_shape(types.get(name));
+            self.line(depth, &format!("{} [label={}, shape={}];", quote(name), quote(name), shape));
+        }
+    }
+
+    fn edge(&mut self, from: &str, to: &str, label: Option<&str>, depth: usize) {
+        let attrs = match label {
+            Some(label) => format!(" [label={}]", quote(label)),
+            None => String::new(),
+        };
+        self.line(depth, &format!("{} -> {}{};", quote(from), quote(to), attrs));
+    }
+}
+
+fn node_shape(type_annotation: Option<&TypeAnnotation>) -> &'static str {
+    match type_annotation {
+        Some(TypeAnnotation::Entity) => "box",
+        Some(TypeAnnotation::Service) => "component",
+        Some(TypeAnnotation::Endpoint) => "cds",
+        Some(TypeAnnotation::Object) => "note",
+        Some(TypeAnnotation::List(_)) => "folder",
+        Some(TypeAnnotation::String | TypeAnnotation::Number | TypeAnnotation::Boolean) => "ellipse",
+        None => "ellipse",
+    }
+}
+
+fn verb_label(verb: InteractionVerb) -> &'static str {
+    match verb {
+        InteractionVerb::Calls => "calls",
+        InteractionVerb::Receives => "receives",
+        InteractionVerb::Returns => "returns",
+    }
+}
+
+/// Quote a DOT identifier or label, escaping embedded quotes and backslashes
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    fn generate(input: &str) -> String {
+        to_dot(&parse_program(input))
+    }
+
+    /// Minimal syntactic check: header, balanced braces and terminated statements
+    fn assert_valid_dot(output: &str) {
+        assert!(output.starts_with("digraph NaviLang {"));
+        assert!(output.trim_end().ends_with('}'));
+
+        let mut depth = 0i32;
+        let mut in_string = false;
+        let mut escaped = false;
+        for ch in output.chars() {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' if in_string => escaped = true,
+                '"' => in_string = !in_string,
+                '{' if !in_string => depth += 1,
+                '}' if !in_string => {
+                    depth -= 1;
+                    assert!(depth >= 0, "Unbalanced closing brace");
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(depth, 0, "Unbalanced braces");
+        assert!(!in_string, "Unterminated string");
+    }
+
+    #[test]
+    fn test_dot_output() {
+        let output = generate(r#"CONTEXT "User Authentication" {
+    VAR User:Entity
+    VAR AuthService:Service
+    User CALLS AuthService
+    User GOES TO Dashboard
+    IF User IS "invalid" THEN User GOES TO LoginPage
+}"#);
+
+        assert_valid_dot(&output);
+        assert!(output.contains("subgraph cluster_1 {"));
+        assert!(output.contains("label=\"User Authentication\";"));
+        assert!(output.contains("\"User\" [label=\"User\", shape=box];"));
+        assert!(output.contains("\"AuthService\" [label=\"AuthService\", shape=component];"));
+        assert!(output.contains("\"User\" -> \"AuthService\" [label=\"calls\"];"));
+        assert!(output.contains("\"User\" -> \"Dashboard\";"));
+        assert!(output.contains("\"User\" -> \"LoginPage\" [label=\"User IS \\\"invalid\\\"\"];"));
+    }
+
+    #[test]
+    fn test_nested_clusters_inherit_types() {
+        let output = generate(r#"CONTEXT Outer {
+    VAR Api:Endpoint
+    CONTEXT "Inner \"quoted\"" { Api GOES TO Done }
+}"#);
+
+        assert_valid_dot(&output);
+        assert!(output.contains("label=\"Inner \\\"quoted\\\"\";"));
+        assert!(output.contains("\"Api\" [label=\"Api\", shape=cds];"));
+        assert_eq!(output.matches("subgraph cluster_").count(), 2);
+    }
+
+    #[test]
+    fn test_async_interactions_are_dashed() {
+        let output = generate("CONTEXT Notify { User CALLS Api  ASYNC User CALLS Notifier }");
+
+        assert_valid_dot(&output);
+        assert!(output.contains("\"User\" -> \"Api\" [label=\"calls\"];"));
+        assert!(output.contains("\"User\" -> \"Notifier\" [label=\"calls\", style=dashed];"));
+    }
+
+    #[test]
+    fn test_composite_guards_label_edges() {
+        let output = generate("CONTEXT Flow { IF A IS 1 AND B IS 2 OR C IS 3 THEN A -> Done }");
+
+        assert_valid_dot(&output);
+        assert!(output.contains("\"A\" -> \"Done\" [label=\"A IS 1 AND B IS 2 OR C IS 3\"];"));
+    }
+
+    #[test]
+    fn test_flow_label_on_edge() {
+        let output = generate(r#"CONTEXT Auth {
+    User -> Dashboard LABELED "after login"
+    IF User IS "admin" THEN User -> Admin LABELED "elevated"
+}"#);
+
+        assert_valid_dot(&output);
+        assert!(output.contains("\"User\" -> \"Dashboard\" [label=\"after login\"];"));
+        assert!(output.contains("\"User\" -> \"Admin\" [label=\"elevated (User IS \\\"admin\\\")\"];"));
+    }
+
+    #[test]
+    fn test_dependency_graph() {
+        let program = "CONTEXT Backend {
+    VAR AuthService:Service
+    VAR Database
+    AuthService USES Database
+    CONTEXT Storage { Database USES Disk  Disk USES Database  Database USES Disk }
+}";
+        let output = to_dependency_graph(&parse_program(program));
+
+        assert_eq!(output, r#"digraph Dependencies {
+    rankdir=LR;
+    "AuthService" [label="AuthService", shape=component];
+    "Database" [label="Database", shape=ellipse];
+    "AuthService" -> "Database";
+    "Disk" [label="Disk", shape=ellipse];
+    "Database" -> "Disk" [color=red];
+    "Disk" -> "Database" [color=red];
+}
+"#);
+        assert!(generate(program).contains("\"AuthService\" -> \"Database\" [label=\"uses\", style=dotted];"));
+    }
+
+    #[test]
+    fn test_batch_becomes_labelled_cluster() {
+        let output = generate("CONTEXT Jobs { BATCH 50 { Worker CALLS Queue } }");
+
+        assert_valid_dot(&output);
+        assert!(output.contains("        subgraph cluster_2 {
+            label=\"batch of 50\";
+            style=dashed;
+            \"Worker\" [label=\"Worker\", shape=ellipse];"));
+        assert!(output.contains("            \"Worker\" -> \"Queue\" [label=\"calls\"];\n        }\n"));
+    }
+    #[test]
+    fn test_start_node_gets_an_entry_point() {
+        let output = to_dot(&parse_program("CONTEXT Auth { START Login  Login -> Home }"));
+        assert!(output.contains("start_1 [shape=point, label=\"\"];"));
+        assert!(output.contains("start_1 -> \"Login\";"));
+    }
+}
@@ -0,0 +1,417 @@
+// Source formatter - canonical NaviLang from an AST
+use crate::lexer::tokens::Token;
+use crate::parser::ast::{
+    Attribute, Condition, Context, InteractionVerb, OrderRelation, Predicate, Program, Statement, StatementKind, Value,
+};
+
+const INDENT: &str = "    ";
+
+/// Render a program back to canonical NaviLang source
+///
+/// Keywords are uppercase, blocks are indented by four spaces and every
+/// statement sits on its own line. `->` is written as `GOES TO` and `ASYNC`
+/// always comes before the subject. Comments attached to contexts and
+/// statements are kept, as are top-level `INCLUDE` directives in their place.
+/// Formatting the output again returns it unchanged.
+pub fn to_source(program: &Program) -> String {
+    let mut formatter = Formatter::default();
+    let mut includes = program.includes.iter().peekable();
+    for i in 0..=program.contexts.len() {
+        // Consecutive includes stay together, set apart from contexts
+        let mut first = true;
+        while let Some(include) = includes.next_if(|include| include.position <= i) {
+            if first && !formatter.out.is_empty() {
+                formatter.out.push('\n');
+            }
+            first = false;
+            formatter.comments(&include.comments, 0);
+            formatter.out.push_str(&format!("INCLUDE {}\n", quote(&include.path)));
+        }
+        if let Some(context) = program.contexts.get(i) {
+            if !formatter.out.is_empty() {
+                formatter.out.push('\n');
+            }
+            formatter.context(context, 0);
+        }
+    }
+    formatter.out
+}
+
+#[derive(Default)]
+struct Formatter {
+    out: String,
+}
+
+impl Formatter {
+    fn indent(&mut self, depth: usize) {
+        self.out.push_str(&INDENT.repeat(depth));
+    }
+
+    fn comments(&mut self, comments: &[String], depth: usize) {
+        for comment in comments {
+            self.indent(depth);
+            // Multi-line block comments stay block comments so they read back as one
+            if comment.contains('\n') && !comment.contains("*/") {
+                self.out.push_str(&format!("/* {} */\n", comment));
+            } else if comment.is_empty() {
+                self.out.push_str("//\n");
+            } else {
+                for line in comment.lines() {
+                    self.out.push_str(&format!("// {}\n", line.trim()));
+                }
+            }
+        }
+    }
+
+    fn context(&mut self, context: &Context, depth: usize) {
+        self.comments(&context.comments, depth);
+        self.indent(depth);
+
+        let name = match identifier(&context.name) {
+            name if name == context.name => name,
+            _ => quote(&context.name),
+        };
+        if context.statements.is_empty() && context.children.is_empty() {
+            self.out.push_str(&format!("CONTEXT {} {{}}\n", name));
+            return;
+        }
+
+        self.out.push_str(&format!("CONTEXT {} {{\n", name));
+        for stmt in &context.statements {
+            self.statement(stmt, depth + 1);
+        }
+        for (i, child) in context.children.iter().enumerate() {
+            if i > 0 || !context.statements.is_empty() {
+                self.out.push('\n');
+            }
+            self.context(child, depth + 1);
+        }
+        self.indent(depth);
+        self.out.push_str("}\n");
+    }
+
+    fn statement(&mut self, stmt: &Statement, depth: usize) {
+        self.comments(&stmt.comments, depth);
+        self.indent(depth);
+        self.kind(&stmt.kind, depth);
+        self.attributes(&stmt.attributes);
+        self.out.push('\n');
+    }
+
+    fn attributes(&mut self, attributes: &[Attribute]) {
+        if !attributes.is_empty() {
+            let pairs: Vec<String> = attributes.iter()
+                .map(|attribute| format!("{}={}", attribute.key, value(&attribute.value)))
+                .collect();
+            self.out.push_str(&format!(" [{}]", pairs.join(", ")));
+        }
+    }
+
+    /// Write a statement without its leading indentation or trailing newline
+    fn kind(&mut self, kind: &StatementKind, depth: usize) {
+        match kind {
+            StatementKind::VarDecl { name, type_annotation } => {
+                self.out.push_str(&format!("VAR {}", identifier(name)));
+                if let Some(type_annotation) = type_annotation {
+                    self.out.push_str(&format!(":{}", type_annotation));
+                }
+            }
+            StatementKind::Flow { from, to, label } => {
+                self.out.push_str(&format!("{} GOES TO {}", identifier(from), identifier(to)));
+                if let Some(label) = label {
+                    self.out.push_str(&format!(" LABELED {}", quote(label)));
+                }
+            }
+            StatementKind::Conditional { condition, then_branch, else_branch } => {
+                self.out.push_str(&format!("IF {} THEN", condition_source(condition)));
+                let mut wrapped = false;
+                for stmt in then_branch {
+                    wrapped = self.branch(stmt, depth);
+                }
+                for stmt in else_branch.iter().flatten() {
+                    if wrapped {
+                        self.out.push('\n');
+                        self.indent(depth);
+                        self.out.push_str("ELSE");
+                    } else {
+                        self.out.push_str(" ELSE");
+                    }
+                    self.branch(stmt, depth);
+                }
+            }
+            StatementKind::Parallel { branches, join } => {
+                let body: Vec<&Statement> = branches.iter().flatten().collect();
+                self.block("PARALLEL", &body, depth);
+                if let Some(join) = join {
+                    self.out.push_str(&format!(" JOIN AT {}", identifier(join)));
+                }
+            }
+            StatementKind::Loop { condition, body } => {
+                let header = match condition {
+                    Some(condition) => format!("WHILE {}", condition_source(condition)),
+                    None => "LOOP".to_string(),
+                };
+                let body: Vec<&Statement> = body.iter().collect();
+                self.block(&header, &body, depth);
+            }
+            StatementKind::Batch { size, body } => {
+                let header = match size {
+                    Some(size) => format!("BATCH {}", size),
+                    None => "BATCH".to_string(),
+                };
+                let body: Vec<&Statement> = body.iter().collect();
+                self.block(&header, &body, depth);
+            }
+            StatementKind::Uses { subject, dependency } => {
+                self.out.push_str(&format!("{} USES {}", identifier(subject), identifier(dependency)));
+            }
+            StatementKind::Creation { subject, creator } => {
+                self.out.push_str(&format!("{} CREATED BY {}", identifier(subject), identifier(creator)));
+            }
+            StatementKind::Ordering { subject, relation, other } => {
+                let relation = match relation {
+                    OrderRelation::Before => "BEFORE",
+                    OrderRelation::After => "AFTER",
+                };
+                self.out.push_str(&format!("{} {} {}", identifier(subject), relation, identifier(other)));
+            }
+            StatementKind::Endpoint { method, path, description } => {
+                self.out.push_str(&format!("ENDPOINT {} {}", method, quote(path)));
+                if let Some(description) = description {
+                    self.out.push_str(&format!(" {}", quote(description)));
+                }
+            }
+            StatementKind::Break => self.out.push_str("BREAK"),
+            StatementKind::Continue => self.out.push_str("CONTINUE"),
+            StatementKind::Start { node } => self.out.push_str(&format!("START {}", identifier(node))),
+            StatementKind::Action { actor, action } => {
+                let action = match identifier(action) {
+                    name if name == *action => name,
+                    _ => quote(action),
+                };
+                self.out.push_str(&format!("{} DOES {}", identifier(actor), action));
+            }
+            StatementKind::Interaction { subject, verb, object, retry, timeout, is_async } => {
+                if *is_async {
+                    self.out.push_str("ASYNC ");
+                }
+                let verb = match verb {
+                    InteractionVerb::Calls => "CALLS",
+                    InteractionVerb::Receives => "RECEIVES",
+                    InteractionVerb::Returns => "RETURNS",
+                };
+                self.out.push_str(&format!("{} {} {}", identifier(subject), verb, value(object)));
+                if let Some(retry) = retry {
+                    self.out.push_str(&format!(" RETRY {}", retry));
+                }
+                if let Some(timeout) = timeout {
+                    self.out.push_str(&format!(" TIMEOUT {}", timeout));
+                }
+            }
+        }
+    }
+
+    /// Write the statement after `THEN` or `ELSE`, on the same line unless it
+    /// has comments; returns whether it was moved to its own line
+    fn branch(&mut self, stmt: &Statement, depth: usize) -> bool {
+        if stmt.comments.is_empty() {
+            self.out.push(' ');
+            self.kind(&stmt.kind, depth);
+            self.attributes(&stmt.attributes);
+            false
+        } else {
+            self.out.push('\n');
+            self.comments(&stmt.comments, depth + 1);
+            self.indent(depth + 1);
+            self.kind(&stmt.kind, depth + 1);
+            self.attributes(&stmt.attributes);
+            true
+        }
+    }
+
+    fn block(&mut self, header: &str, body: &[&Statement], depth: usize) {
+        if body.is_empty() {
+            self.out.push_str(&format!("{} {{}}", header));
+            return;
+        }
+
+        self.out.push_str(&format!("{} {{\n", header));
+        for stmt in body {
+            self.statement(stmt, depth + 1);
+        }
+        self.indent(depth);
+        self.out.push('}');
+    }
+}
+
+/// An identifier as written in source, backtick-escaped if it is a keyword
+fn identifier(name: &str) -> String {
+    Token::Identifier(name.to_string()).to_string()
+}
+
+/// A guard with parentheses only where `AND`/`OR` precedence needs them,
+/// matching the `Display` impl of `Condition`
+fn condition_source(condition: &Condition) -> String {
+    let grouped = |condition: &Condition| format!("({})", condition_source(condition));
+    match condition {
+        Condition::Leaf(predicate) => predicate_source(predicate),
+        Condition::Or(left, right) => match right.as_ref() {
+            Condition::Or(..) => format!("{} OR {}", condition_source(left), grouped(right)),
+            _ => format!("{} OR {}", condition_source(left), condition_source(right)),
+        },
+        Condition::And(left, right) => {
+            let left = match left.as_ref() {
+                Condition::Or(..) => grouped(left),
+                _ => condition_source(left),
+            };
+            let right = match right.as_ref() {
+                Condition::Leaf(predicate) => predicate_source(predicate),
+                _ => grouped(right),
+            };
+            format!("{} AND {}", left, right)
+        }
+    }
+}
+
+fn predicate_source(predicate: &Predicate) -> String {
+    format!("{} {} {}", identifier(&predicate.left), predicate.comparison, value(&predicate.right))
+}
+
+fn value(value: &Value) -> String {
+    match value {
+        Value::Identifier(name) => identifier(name),
+        Value::String(s) => quote(s),
+        // Debug keeps the fractional part, so `1.0` doesn't read back as an integer
+        Value::Float(x) => format!("{:?}", x),
+        Value::List(items) => {
+            let items: Vec<String> = items.iter().map(self::value).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(fields) => {
+            let fields: Vec<String> = fields.iter()
+                .map(|(key, field)| format!("{}: {}", identifier(key), self::value(field)))
+                .collect();
+            format!("{{ {} }}", fields.join(", "))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// A double-quoted string literal using NaviLang escapes
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            ch if ch.is_control() => quoted.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    const CANONICAL: &str = r#"// Everything about signing in
+CONTEXT "User Authentication" {
+    VAR User:Entity
+    VAR AuthService:Service
+    VAR `Context`
+    START User
+    // Successful logins land here
+    User GOES TO Dashboard
+    Dashboard GOES TO Settings LABELED "from the menu"
+    ASYNC User CALLS AuthService RETRY 3 TIMEOUT 30s
+    AuthService RETURNS "token \"quoted\"\n"
+    AuthService RETURNS { token: String, roles: [String], ttl: 30s }
+    User RECEIVES [Dashboard, 2]
+    User DOES "Reset Password"
+    ENDPOINT POST "/sessions" "Sign in" [auth=false]
+    Dashboard AFTER LoginPage
+    AuthService USES Database
+    Session CREATED BY AuthService
+    IF User IS "invalid" THEN User GOES TO LoginPage ELSE User GOES TO Dashboard
+    IF (User IS Admin OR Attempts > 1) AND Locked = false THEN User GOES TO LoginPage
+    WHILE Attempts < 3 {
+        User GOES TO LoginPage
+        BREAK
+    }
+    PARALLEL {
+        User DOES Login
+        Admin DOES Audit
+    }
+    LOOP {}
+    BATCH 50 {
+        AuthService DOES Audit
+    }
+
+    CONTEXT Recovery {
+        IF Score >= 1.0 THEN
+            // Retry once
+            User GOES TO ResetPage
+        ELSE
+            // Give up
+            User DOES "Contact support"
+    }
+}
+
+CONTEXT Empty {}
+"#;
+
+    #[test]
+    fn test_canonical_source_is_unchanged() {
+        pretty_assertions::assert_eq!(to_source(&parse_program(CANONICAL)), CANONICAL);
+    }
+
+    #[test]
+    fn test_includes_are_kept_in_place() {
+        let source = r#"// Shared entities
+INCLUDE "common/entities.navi"
+INCLUDE "common/services.navi"
+
+CONTEXT Auth {
+    VAR User
+}
+
+INCLUDE "checkout.navi"
+
+CONTEXT Orders {
+    VAR Order
+}
+
+INCLUDE "footer.navi"
+"#;
+        pretty_assertions::assert_eq!(to_source(&parse_program(source)), source);
+    }
+
+    #[test]
+    fn test_parse_format_parse_round_trip() {
+        let program = parse_program(CANONICAL);
+        assert_eq!(parse_program(&to_source(&program)), program);
+    }
+
+    #[test]
+    fn test_formatting_is_idempotent() {
+        let messy = "context Auth{var user : entity user->Home
+  user calls Api timeout 5s async retry 2 IF x=true then Loop{continue}
+/* spans
+   lines */
+CONTEXT   Inner{}}";
+
+        let once = to_source(&parse_program(messy));
+        assert_eq!(to_source(&parse_program(&once)), once);
+        assert!(once.starts_with("CONTEXT Auth {\n    VAR user:Entity\n    user GOES TO Home\n"));
+        assert!(once.contains("    ASYNC user CALLS Api RETRY 2 TIMEOUT 5s\n"));
+        assert!(once.contains("    IF x = true THEN LOOP {\n        CONTINUE\n    }\n"));
+        assert!(once.contains("    /* spans\n   lines */\n    CONTEXT Inner {}\n"));
+    }
+}
@@ -0,0 +1,113 @@
+// GraphML generator - the flow graph for network tools such as Gephi or yEd
+use crate::parser::ast::{Program, Statement, StatementKind, TypeAnnotation, Visitor, walk_program};
+use super::edges;
+
+const INDENT: &str = "  ";
+
+/// Render a program's graph as a GraphML document
+///
+/// Declared variables come first as nodes, followed by any other name an
+/// edge touches. A node's `type` data is the type annotation of its first
+/// declaration, left out when there is none. Edges are the same as in
+/// [`super::csv::to_edge_list`], each with its `verb` (`flow`, `uses`,
+/// `creates`, `calls`, `receives` or `returns`) as data.
+pub fn to_graphml(program: &Program) -> String {
+    #[derive(Default)]
+    struct Nodes<'ast> {
+        found: Vec<(&'ast str, Option<&'ast TypeAnnotation>)>,
+    }
+
+    impl<'ast> Visitor<'ast> for Nodes<'ast> {
+        fn visit_statement(&mut self, stmt: &'ast Statement) {
+            if let StatementKind::VarDecl { name, type_annotation } = &stmt.kind
+                && !self.found.iter().any(|(declared, _)| declared == name)
+            {
+                self.found.push((name, type_annotation.as_ref()));
+            }
+        }
+    }
+
+    let mut nodes = Nodes::default();
+    walk_program(&mut nodes, program);
+    let edges = edges(program);
+    for name in edges.iter().flat_map(|edge| [edge.source, edge.target]) {
+        if !nodes.found.iter().any(|(declared, _)| *declared == name) {
+            nodes.found.push((name, None));
+        }
+    }
+
+    let mut out = String::new();
+    let mut line = |depth: usize, text: &str| {
+        out.push_str(&INDENT.repeat(depth));
+        out.push_str(text);
+        out.push('\n');
+    };
+    line(0, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    line(0, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#);
+    line(1, r#"<key id="type" for="node" attr.name="type" attr.type="string"/>"#);
+    line(1, r#"<key id="verb" for="edge" attr.name="verb" attr.type="string"/>"#);
+    line(1, r#"<graph id="NaviLang" edgedefault="directed">"#);
+    for (name, type_annotation) in &nodes.found {
+        match type_annotation {
+            Some(type_annotation) => {
+                line(2, &format!("<node id=\"{}\">", escape(name)));
+                line(3, &format!("<data key=\"type\">{}</data>", escape(&type_annotation.to_string())));
+                line(2, "</node>");
+            }
+            None => line(2, &format!("<node id=\"{}\"/>", escape(name))),
+        }
+    }
+    for (i, edge) in edges.iter().enumerate() {
+        line(2, &format!("<edge id=\"e{}\" source=\"{}\" target=\"{}\">", i, escape(edge.source), escape(edge.target)));
+        line(3, &format!("<data key=\"verb\">{}</data>", edge.kind));
+        line(2, "</edge>");
+    }
+    line(1, "</graph>");
+    line(0, "</graphml>");
+    out
+}
+
+/// Escape text for an XML attribute or element
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    fn generate(input: &str) -> String {
+        to_graphml(&parse_program(input))
+    }
+
+    #[test]
+    fn test_nodes_and_edges() {
+        let output = generate("CONTEXT Shop {
+    VAR User:Entity
+    VAR Cart
+    User -> Cart
+    Cart CALLS Payments
+}");
+
+        assert!(output.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<graphml xmlns="));
+        assert!(output.ends_with("</graphml>\n"));
+        assert!(output.contains("    <node id=\"User\">\n      <data key=\"type\">Entity</data>\n    </node>\n"));
+        assert!(output.contains("    <node id=\"Cart\"/>\n    <node id=\"Payments\"/>\n"));
+        assert!(output.contains("    <edge id=\"e0\" source=\"User\" target=\"Cart\">\n      <data key=\"verb\">flow</data>\n"));
+        assert!(output.contains("<edge id=\"e1\" source=\"Cart\" target=\"Payments\">"));
+        assert!(output.contains("<data key=\"verb\">calls</data>"));
+    }
+
+    #[test]
+    fn test_special_characters_are_escaped() {
+        assert_eq!(escape(r#"<A & "B">'"#), "&lt;A &amp; &quot;B&quot;&gt;&apos;");
+
+        let output = generate("CONTEXT Shop { VAR Items:[Object] }");
+        assert!(output.contains("<data key=\"type\">[Object]</data>"));
+    }
+}
@@ -0,0 +1,94 @@
+// JSON generator - machine-readable serialization of compiler artifacts
+use crate::error::Result;
+use crate::reader::SourceFile;
+use anyhow::Context;
+use serde::Serialize;
+use serde_json::json;
+
+/// Serialize an AST node (or any other compiler artifact) to JSON
+///
+/// Serialization failures are reported as `NaviLangError::GenericError`
+/// instead of panicking. Float literals that cannot be represented in JSON
+/// (NaN, infinity) never reach this point: the lexer already turns them into
+/// `Token::Float(None)`, which serializes as `null`.
+pub fn to_json<T: Serialize>(value: &T, pretty: bool) -> Result<String> {
+    let json = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+
+    Ok(json.context("Failed to serialize to JSON")?)
+}
+
+/// Serialize like [`to_json`], adding a `location` next to every `span`
+/// with the file, line and column where the span starts
+///
+/// Spans are relative to the combined source, includes and all, so they are
+/// mapped back through `source` to the file each node was written in. Keys
+/// of the result are sorted.
+pub fn to_json_with_locations<T: Serialize>(value: &T, source: &SourceFile, pretty: bool) -> Result<String> {
+    fn add_locations(value: &mut serde_json::Value, source: &SourceFile) {
+        match value {
+            serde_json::Value::Object(fields) => {
+                fields.values_mut().for_each(|field| add_locations(field, source));
+                if let Some(offset) = fields.get("span").and_then(|span| span["start"]["offset"].as_u64()) {
+                    fields.insert("location".to_string(), json!(source.location(offset as usize)));
+                }
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|item| add_locations(item, source)),
+            _ => {}
+        }
+    }
+
+    let mut tree = serde_json::to_value(value).context("Failed to serialize to JSON")?;
+    add_locations(&mut tree, source);
+    to_json(&tree, pretty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+    use crate::lexer::{Lexer, tokens::Token};
+
+    #[test]
+    fn test_program_to_json() {
+        let program = parse_program("");
+
+        let json = to_json(&program, true).unwrap();
+        assert!(json.contains("\"contexts\""));
+        assert!(json.contains('\n'));
+
+        let compact = to_json(&program, false).unwrap();
+        assert!(!compact.contains('\n'));
+    }
+
+    #[test]
+    fn test_locations_are_added_next_to_spans() {
+        let source = SourceFile::from_string("CONTEXT Auth {\n    VAR User\n}".to_string(), "auth.navi".to_string());
+        let program = parse_program(&source.content);
+
+        let json = to_json_with_locations(&program, &source, false).unwrap();
+        let tree: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let var = &tree["contexts"][0]["statements"][0];
+        assert_eq!(var["location"], json!({ "path": "auth.navi", "line": 2, "column": 5 }));
+        assert_eq!(var["names"][0]["location"]["column"], 9);
+        assert_eq!(tree["location"]["line"], 1);
+
+        assert!(!to_json(&program, false).unwrap().contains("location"));
+    }
+
+    #[test]
+    fn test_overflowing_float_serializes_as_null() {
+        let input = format!("TIMEOUT 1{}.5", "0".repeat(400));
+        let mut lexer = Lexer::new(&input);
+        let tokens = lexer.tokenize_filtered().unwrap();
+
+        assert_eq!(tokens[1].token, Token::Float(None));
+
+        let token_kinds: Vec<_> = tokens.iter().map(|t| &t.token).collect();
+        let json = to_json(&token_kinds, false).unwrap();
+        assert_eq!(json, r#"["Timeout",{"Float":null}]"#);
+    }
+}
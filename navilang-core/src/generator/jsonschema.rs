@@ -0,0 +1,219 @@
+// JSON Schema generator - data shapes of typed variables
+use crate::parser::ast::{InteractionVerb, Program, Statement, StatementKind, TypeAnnotation, Value, Visitor, walk_program};
+use serde_json::{Map, json};
+use std::collections::HashMap;
+
+/// Render the data-typed variables of a program as JSON Schema definitions
+///
+/// Each variable declared as `String`, `Number`, `Boolean`, `Object` or a
+/// list of these gets an entry under `$defs`; `Entity`, `Service` and
+/// `Endpoint` variables describe participants rather than data and are
+/// skipped. An `Object` variable takes its properties from the object
+/// payloads it `RECEIVES` or `RETURNS`. A field is required when every
+/// payload of that variable has it.
+pub fn to_schema(program: &Program) -> serde_json::Value {
+    let mut collector = SchemaCollector::default();
+    walk_program(&mut collector, program);
+
+    let mut defs = Map::new();
+    for (name, type_annotation) in &collector.variables {
+        if let Some(schema) = collector.type_schema(name, type_annotation) {
+            defs.insert(name.to_string(), schema);
+        }
+    }
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$defs": defs,
+    })
+}
+
+#[derive(Default)]
+struct SchemaCollector<'ast> {
+    /// Typed variables in declaration order; the first declaration of a name wins
+    variables: Vec<(&'ast str, &'ast TypeAnnotation)>,
+    /// Object payloads by the subject that receives or returns them
+    payloads: HashMap<&'ast str, Vec<&'ast [(String, Value)]>>,
+}
+
+impl<'ast> Visitor<'ast> for SchemaCollector<'ast> {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        match &stmt.kind {
+            StatementKind::VarDecl { name, type_annotation: Some(type_annotation) }
+                if !self.variables.iter().any(|(declared, _)| declared == name) =>
+            {
+                self.variables.push((name, type_annotation));
+            }
+            StatementKind::Interaction {
+                subject,
+                verb: InteractionVerb::Receives | InteractionVerb::Returns,
+                object: Value::Object(fields),
+                ..
+            } => self.payloads.entry(subject).or_default().push(fields),
+            _ => {}
+        }
+    }
+}
+
+impl SchemaCollector<'_> {
+    /// Schema of the variable `name` declared with `type_annotation`, or
+    /// `None` if the type is not a data type
+    fn type_schema(&self, name: &str, type_annotation: &TypeAnnotation) -> Option<serde_json::Value> {
+        let schema = match type_annotation {
+            TypeAnnotation::String => json!({ "type": "string" }),
+            TypeAnnotation::Number => json!({ "type": "number" }),
+            TypeAnnotation::Boolean => json!({ "type": "boolean" }),
+            TypeAnnotation::Object => {
+                let payloads = self.payloads.get(name).map(Vec::as_slice).unwrap_or_default();
+                self.object_schema(payloads)
+            }
+            TypeAnnotation::List(element) => json!({
+                "type": "array",
+                "items": self.type_schema(name, element)?,
+            }),
+            TypeAnnotation::Entity | TypeAnnotation::Service | TypeAnnotation::Endpoint => return None,
+        };
+        Some(schema)
+    }
+
+    /// Object schema merging the fields of several payloads; a later payload
+    /// overrides the schema of a field an earlier one already gave
+    fn object_schema(&self, payloads: &[&[(String, Value)]]) -> serde_json::Value {
+        let mut properties = Map::new();
+        for (key, value) in payloads.iter().copied().flatten() {
+            properties.insert(key.clone(), self.value_schema(value));
+        }
+
+        let required: Vec<&String> = properties.keys()
+            .filter(|key| payloads.iter().all(|fields| fields.iter().any(|(field, _)| field == *key)))
+            .collect();
+
+        let mut schema = json!({ "type": "object", "properties": properties });
+        if !required.is_empty() {
+            schema["required"] = json!(required);
+        }
+        schema
+    }
+
+    /// Schema of a field value; identifiers take the schema of the variable
+    /// they name, if it has one
+    ///
+    /// Object variables are referred to by `$ref` rather than inlined, since
+    /// their payloads may name them again, directly or through another object.
+    fn value_schema(&self, value: &Value) -> serde_json::Value {
+        match value {
+            Value::String(_) | Value::Duration(_) => json!({ "type": "string" }),
+            Value::Number(_) => json!({ "type": "integer" }),
+            Value::Float(_) => json!({ "type": "number" }),
+            Value::Boolean(_) => json!({ "type": "boolean" }),
+            Value::List(items) => {
+                let items = items.first().map_or_else(|| json!({}), |item| self.value_schema(item));
+                json!({ "type": "array", "items": items })
+            }
+            Value::Object(fields) => self.object_schema(&[fields]),
+            Value::Type(type_annotation) => self.type_schema("", type_annotation).unwrap_or_else(|| json!({})),
+            Value::Identifier(name) => match self.variables.iter().find(|(declared, _)| declared == name) {
+                Some((declared, type_annotation)) if holds_objects(type_annotation) => {
+                    json!({ "$ref": format!("#/$defs/{}", declared) })
+                }
+                Some((declared, type_annotation)) => self.type_schema(declared, type_annotation).unwrap_or_else(|| json!({})),
+                None => json!({}),
+            },
+        }
+    }
+}
+
+/// Whether values of `type_annotation` are objects or lists of them
+fn holds_objects(type_annotation: &TypeAnnotation) -> bool {
+    match type_annotation {
+        TypeAnnotation::Object => true,
+        TypeAnnotation::List(element) => holds_objects(element),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    fn generate(input: &str) -> serde_json::Value {
+        to_schema(&parse_program(input))
+    }
+
+    #[test]
+    fn test_object_variable_gets_properties() {
+        let doc = generate(r#"CONTEXT Auth {
+    VAR Session:Object
+    VAR Roles:[String]
+    VAR User:Entity
+    Session RETURNS { token: String, ttl: 30s, roles: Roles }
+    Session RETURNS { token: "guest", expires: true }
+}"#);
+
+        let defs = doc["$defs"].as_object().unwrap();
+        assert_eq!(defs.keys().collect::<Vec<_>>(), vec!["Roles", "Session"]);
+        assert_eq!(defs["Roles"], json!({ "type": "array", "items": { "type": "string" } }));
+
+        let session = &defs["Session"];
+        assert_eq!(session["type"], "object");
+        assert_eq!(session["properties"], json!({
+            "token": { "type": "string" },
+            "ttl": { "type": "string" },
+            "roles": { "type": "array", "items": { "type": "string" } },
+            "expires": { "type": "boolean" },
+        }));
+        // Only `token` appears in both payloads
+        assert_eq!(session["required"], json!(["token"]));
+    }
+
+    #[test]
+    fn test_nested_objects_and_lists() {
+        let doc = generate("CONTEXT Shop {
+    VAR Order:Object
+    Order RECEIVES { items: [{ sku: String, qty: 1 }], total: 9.5 }
+}");
+
+        let order = &doc["$defs"]["Order"];
+        assert_eq!(order["required"], json!(["items", "total"]));
+        assert_eq!(order["properties"]["total"], json!({ "type": "number" }));
+        assert_eq!(order["properties"]["items"], json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": { "qty": { "type": "integer" }, "sku": { "type": "string" } },
+                "required": ["qty", "sku"],
+            },
+        }));
+
+        // Without payloads an object has no known properties
+        let doc = generate("CONTEXT A { VAR Blob:Object }");
+        assert_eq!(doc["$defs"]["Blob"], json!({ "type": "object", "properties": {} }));
+    }
+
+    #[test]
+    fn test_self_referential_object_uses_ref() {
+        let doc = generate("CONTEXT A { VAR Node:Object  Node RETURNS { next: Node } }");
+        assert_eq!(doc["$defs"]["Node"]["properties"]["next"], json!({ "$ref": "#/$defs/Node" }));
+
+        // Lists of objects are referred to the same way
+        let doc = generate("CONTEXT A { VAR Nodes:[Object]  Nodes RETURNS { rest: Nodes } }");
+        assert_eq!(doc["$defs"]["Nodes"]["items"]["properties"]["rest"], json!({ "$ref": "#/$defs/Nodes" }));
+    }
+
+    #[test]
+    fn test_mutually_referential_objects_use_refs() {
+        let doc = generate("CONTEXT A {
+    VAR Parent:Object
+    VAR Child:Object
+    Parent RETURNS { child: Child }
+    Child RETURNS { parent: Parent, name: String }
+}");
+
+        assert_eq!(doc["$defs"]["Parent"]["properties"]["child"], json!({ "$ref": "#/$defs/Child" }));
+        assert_eq!(doc["$defs"]["Child"]["properties"], json!({
+            "parent": { "$ref": "#/$defs/Parent" },
+            "name": { "type": "string" },
+        }));
+    }
+}
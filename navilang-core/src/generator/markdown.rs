@@ -0,0 +1,208 @@
+// Markdown generator - human-readable documentation from contexts and comments
+use crate::parser::ast::{Context, InteractionVerb, OrderRelation, Program, Statement, StatementKind};
+
+/// Render a program as Markdown documentation
+///
+/// Every context gets a heading (H1 at the top level, one level deeper per
+/// nesting), followed by its declared variables and a prose description of
+/// its flows. Comments written above a context or statement are carried over
+/// as descriptive text.
+pub fn to_docs(program: &Program) -> String {
+    let mut out = String::new();
+    for context in &program.contexts {
+        document_context(context, 1, &mut out);
+    }
+    out
+}
+
+fn document_context(context: &Context, level: usize, out: &mut String) {
+    heading(level, &context.name, out);
+    for comment in &context.comments {
+        out.push_str(comment);
+        out.push('\n');
+    }
+    if !context.comments.is_empty() {
+        out.push('\n');
+    }
+
+    let variables: Vec<_> = context.statements.iter()
+        .filter(|stmt| matches!(stmt.kind, StatementKind::VarDecl { .. }))
+        .collect();
+    if !variables.is_empty() {
+        heading(level + 1, "Variables", out);
+        for stmt in variables {
+            if let StatementKind::VarDecl { name, type_annotation } = &stmt.kind {
+                let item = match type_annotation {
+                    Some(type_annotation) => format!("`{}`: {}", name, type_annotation),
+                    None => format!("`{}`", name),
+                };
+                bullet(0, &item, &stmt.comments, out);
+            }
+        }
+        out.push('\n');
+    }
+
+    let flows: Vec<_> = context.statements.iter()
+        .filter(|stmt| !matches!(stmt.kind, StatementKind::VarDecl { .. }))
+        .collect();
+    if !flows.is_empty() {
+        heading(level + 1, "Flows", out);
+        for stmt in flows {
+            describe(stmt, 0, out);
+        }
+        out.push('\n');
+    }
+
+    for child in &context.children {
+        document_context(child, level + 1, out);
+    }
+}
+
+/// Describe a statement as a bullet, nesting the bodies of compound statements
+fn describe(stmt: &Statement, indent: usize, out: &mut String) {
+    match &stmt.kind {
+        StatementKind::Conditional { condition, then_branch, else_branch } => {
+            bullet(indent, &format!("If {}:", condition), &stmt.comments, out);
+            describe_all(then_branch, indent + 1, out);
+            if let Some(else_branch) = else_branch {
+                bullet(indent, "Otherwise:", &[], out);
+                describe_all(else_branch, indent + 1, out);
+            }
+        }
+        StatementKind::Parallel { branches, join } => {
+            let text = match join {
+                Some(join) => format!("In parallel, joining at {}:", join),
+                None => "In parallel:".to_string(),
+            };
+            bullet(indent, &text, &stmt.comments, out);
+            for branch in branches {
+                describe_all(branch, indent + 1, out);
+            }
+        }
+        StatementKind::Loop { condition, body } => {
+            let text = match condition {
+                Some(condition) => format!("While {}:", condition),
+                None => "Repeatedly:".to_string(),
+            };
+            bullet(indent, &text, &stmt.comments, out);
+            describe_all(body, indent + 1, out);
+        }
+        StatementKind::Batch { size, body } => {
+            let text = match size {
+                Some(size) => format!("In batches of {}:", size),
+                None => "In batches:".to_string(),
+            };
+            bullet(indent, &text, &stmt.comments, out);
+            describe_all(body, indent + 1, out);
+        }
+        kind => {
+            if let Some(sentence) = sentence(kind) {
+                bullet(indent, &sentence, &stmt.comments, out);
+            }
+        }
+    }
+}
+
+fn describe_all(statements: &[Statement], indent: usize, out: &mut String) {
+    for stmt in statements {
+        describe(stmt, indent, out);
+    }
+}
+
+/// Prose for a simple statement, e.g. "User goes to Dashboard"
+fn sentence(kind: &StatementKind) -> Option<String> {
+    match kind {
+        StatementKind::Flow { from, to, label: Some(label) } => Some(format!("{} goes to {} ({})", from, to, label)),
+        StatementKind::Flow { from, to, label: None } => Some(format!("{} goes to {}", from, to)),
+        StatementKind::Action { actor, action } => Some(format!("{} does {}", actor, action)),
+        StatementKind::Interaction { subject, verb, object, is_async, .. } => {
+            let verb = match verb {
+                InteractionVerb::Calls => "calls",
+                InteractionVerb::Receives => "receives",
+                InteractionVerb::Returns => "returns",
+            };
+            let mode = if *is_async { " asynchronously" } else { "" };
+            Some(format!("{} {} {}{}", subject, verb, object, mode))
+        }
+        StatementKind::Uses { subject, dependency } => Some(format!("{} uses {}", subject, dependency)),
+        StatementKind::Creation { subject, creator } => Some(format!("{} is created by {}", subject, creator)),
+        StatementKind::Ordering { subject, relation, other } => {
+            let relation = match relation {
+                OrderRelation::Before => "before",
+                OrderRelation::After => "after",
+            };
+            Some(format!("{} happens {} {}", subject, relation, other))
+        }
+        StatementKind::Endpoint { method, path, description } => Some(match description {
+            Some(description) => format!("Exposes `{} {}`: {}", method, path, description),
+            None => format!("Exposes `{} {}`", method, path),
+        }),
+        StatementKind::Break => Some("Stop repeating".to_string()),
+        StatementKind::Continue => Some("Continue with the next iteration".to_string()),
+        StatementKind::Start { node } => Some(format!("Starts at {}", node)),
+        _ => None,
+    }
+}
+
+fn heading(level: usize, text: &str, out: &mut String) {
+    out.push_str(&"#".repeat(level.min(6)));
+    out.push(' ');
+    out.push_str(text);
+    out.push_str("\n\n");
+}
+
+/// Write a list item, appending any leading comments as its description
+fn bullet(indent: usize, text: &str, comments: &[String], out: &mut String) {
+    out.push_str(&"  ".repeat(indent));
+    out.push_str("- ");
+    out.push_str(text);
+    if !comments.is_empty() {
+        out.push_str(" — ");
+        out.push_str(&comments.join(" "));
+    }
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    fn generate(input: &str) -> String {
+        to_docs(&parse_program(input))
+    }
+
+    #[test]
+    fn test_headings_variables_and_flows() {
+        let output = generate(r#"// Everything about signing in
+CONTEXT "User Authentication" {
+    VAR User:Entity
+    VAR Token
+    // Successful logins land here
+    User GOES TO Dashboard
+    User CALLS AuthService
+    User DOES "Reset Password"
+    ENDPOINT POST "/sessions"
+    CONTEXT Recovery { User -> ResetPage }
+}"#);
+
+        assert!(output.starts_with("# User Authentication\n\nEverything about signing in\n"));
+        assert!(output.contains("## Variables\n\n- `User`: Entity\n- `Token`\n"));
+        assert!(output.contains("## Flows\n\n- User goes to Dashboard — Successful logins land here\n"));
+        assert!(output.contains("- User calls AuthService\n"));
+        assert!(output.contains("- User does Reset Password\n"));
+        assert!(output.contains("- Exposes `POST /sessions`\n"));
+        assert!(output.contains("## Recovery\n\n### Flows\n\n- User goes to ResetPage\n"));
+    }
+
+    #[test]
+    fn test_compound_statements_nest() {
+        let output = generate(r#"CONTEXT Flow {
+    IF User IS "invalid" THEN User GOES TO LoginPage
+    WHILE Attempts < 3 { User -> LoginPage }
+}"#);
+
+        assert!(output.contains("- If User IS \"invalid\":\n  - User goes to LoginPage\n"));
+        assert!(output.contains("- While Attempts < 3:\n  - User goes to LoginPage\n"));
+    }
+}
@@ -0,0 +1,269 @@
+// Mermaid generator - flowchart diagrams from flow statements
+use crate::parser::ast::{Context, InteractionVerb, Program, Statement, StatementKind};
+use super::batch_label;
+use std::collections::HashSet;
+
+/// Render a program as a Mermaid `flowchart TD`
+///
+/// Each context becomes a `subgraph`, each `GOES TO` an `A --> B` edge and
+/// each conditional a diamond node linked to the statements it guards
+/// (`yes` edges for `THEN`, `no` edges for `ELSE`).
+/// `USES` dependencies and interactions with an identifier object become
+/// labelled edges, dashed (`A -.->|calls| B`) when the interaction is `ASYNC`.
+/// `BATCH` blocks become nested subgraphs labelled "batch of N". A `START`
+/// node is drawn as a stadium, `Login([Login])`.
+pub fn to_flowchart(program: &Program) -> String {
+    let mut builder = FlowchartBuilder::default();
+    builder.line(0, "flowchart TD");
+
+    for context in &program.contexts {
+        builder.context(context, 1);
+    }
+
+    builder.out
+}
+
+#[derive(Default)]
+struct FlowchartBuilder {
+    out: String,
+    declared: HashSet<String>,
+    subgraphs: usize,
+    conditions: usize,
+    batches: usize,
+}
+
+impl FlowchartBuilder {
+    fn line(&mut self, depth: usize, text: &str) {
+        self.out.push_str(&"    ".repeat(depth));
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn context(&mut self, context: &Context, depth: usize) {
+        self.subgraphs += 1;
+        let header = format!("subgraph ctx_{} [\"{}\"]", self.subgraphs, escape_label(&context.name));
+        self.line(depth, &header);
+
+        for stmt in &context.statements {
+            self.statement(stmt, depth + 1);
+        }
+        for child in &context.children {
+            self.context(child, depth + 1);
+        }
+
+        self.line(depth, "end");
+    }
+
+    /// Render a statement, returning the node control enters it through
+    fn statement(&mut self, stmt: &Statement, depth: usize) -> Option<String> {
+        match &stmt.kind {
+            StatementKind::VarDecl { name, .. } => {
+                self.node(name, depth);
+                Some(name.clone())
+            }
+            StatementKind::Action { actor, .. } => {
+                self.node(actor, depth);
+                Some(actor.clone())
+            }
+            StatementKind::Flow { from, to, label } => {
+                self.node(from, depth);
+                self.node(to, depth);
+                match label {
+                    Some(label) => self.line(depth, &format!("{} -->|\"{}\"| {}", from, escape_label(label), to)),
+                    None => self.line(depth, &format!("{} --> {}", from, to)),
+                }
+                Some(from.clone())
+            }
+            StatementKind::Uses { subject, dependency } => {
+                self.node(subject, depth);
+                self.node(dependency, depth);
+                self.line(depth, &format!("{} -->|uses| {}", subject, dependency));
+                Some(subject.clone())
+            }
+            StatementKind::Creation { subject, creator } => {
+                self.node(creator, depth);
+                self.node(subject, depth);
+                self.line(depth, &format!("{} -->|creates| {}", creator, subject));
+                Some(creator.clone())
+            }
+            StatementKind::Interaction { subject, verb, object, is_async, .. } => {
+                let object = object.as_identifier()?;
+                self.node(subject, depth);
+                self.node(object, depth);
+                let arrow = if *is_async { "-.->" } else { "-->" };
+                self.line(depth, &format!("{} {}|{}| {}", subject, arrow, verb_label(*verb), object));
+                Some(subject.clone())
+            }
+            StatementKind::Conditional { condition, then_branch, else_branch } => {
+                self.conditions += 1;
+                let id = format!("cond_{}", self.conditions);
+                let label = escape_label(&condition.to_string());
+                self.line(depth, &format!("{}{{\"{}\"}}", id, label));
+
+                for branch_stmt in then_branch {
+                    if let Some(entry) = self.statement(branch_stmt, depth) {
+                        self.line(depth, &format!("{} -->|yes| {}", id, entry));
+                    }
+                }
+                for branch_stmt in else_branch.iter().flatten() {
+                    if let Some(entry) = self.statement(branch_stmt, depth) {
+                        self.line(depth, &format!("{} -->|no| {}", id, entry));
+                    }
+                }
+                Some(id)
+            }
+            StatementKind::Parallel { branches, .. } => {
+                for stmt in branches.iter().flatten() {
+                    self.statement(stmt, depth);
+                }
+                None
+            }
+            StatementKind::Loop { body, .. } => {
+                for stmt in body {
+                    self.statement(stmt, depth);
+                }
+                None
+            }
+            StatementKind::Batch { size, body } => {
+                self.batches += 1;
+                let id = format!("batch_{}", self.batches);
+                self.line(depth, &format!("subgraph {} [\"{}\"]", id, batch_label(*size)));
+                for stmt in body {
+                    self.statement(stmt, depth + 1);
+                }
+                self.line(depth, "end");
+                Some(id)
+            }
+            StatementKind::Start { node } => {
+                self.declared.insert(node.clone());
+                self.line(depth, &format!("{}([{}])", node, node));
+                Some(node.clone())
+            }
+            StatementKind::Break
+            | StatementKind::Continue
+            | StatementKind::Ordering { .. }
+            | StatementKind::Endpoint { .. } => None,
+        }
+    }
+
+    /// Declare a node the first time an identifier is seen
+    fn node(&mut self, name: &str, depth: usize) {
+        if self.declared.insert(name.to_string()) {
+            self.line(depth, name);
+        }
+    }
+}
+
+fn verb_label(verb: InteractionVerb) -> &'static str {
+    match verb {
+        InteractionVerb::Calls => "calls",
+        InteractionVerb::Receives => "receives",
+        InteractionVerb::Returns => "returns",
+    }
+}
+
+/// Escape text for use inside a quoted Mermaid label
+fn escape_label(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    fn generate(input: &str) -> String {
+        to_flowchart(&parse_program(input))
+    }
+
+    #[test]
+    fn test_simple_flowchart() {
+        let output = generate(r#"CONTEXT "User Authentication" {
+    VAR User:Entity
+    User GOES TO Dashboard
+}"#);
+
+        assert!(output.starts_with("flowchart TD\n"));
+        assert!(output.contains("subgraph ctx_1 [\"User Authentication\"]"));
+        assert!(output.contains("        User --> Dashboard\n"));
+        assert!(output.trim_end().ends_with("end"));
+        // Nodes are declared once
+        assert_eq!(output.matches("        User\n").count(), 1);
+    }
+
+    #[test]
+    fn test_conditional_diamond() {
+        let output = generate(r#"CONTEXT Auth {
+    IF User IS "invalid" THEN User GOES TO LoginPage
+}"#);
+
+        assert!(output.contains("cond_1{\"User IS #quot;invalid#quot;\"}"));
+        assert!(output.contains("cond_1 -->|yes| User"));
+        assert!(output.contains("User --> LoginPage"));
+    }
+
+    #[test]
+    fn test_nested_contexts_become_nested_subgraphs() {
+        let output = generate("CONTEXT Outer { A -> B CONTEXT Inner { B -> C } }");
+
+        let outer = output.find("subgraph ctx_1 [\"Outer\"]").unwrap();
+        let inner = output.find("    subgraph ctx_2 [\"Inner\"]").unwrap();
+        assert!(outer < inner);
+        assert_eq!(output.matches("end\n").count(), 2);
+    }
+
+    #[test]
+    fn test_interaction_edges() {
+        let output = generate(r#"CONTEXT Notify {
+    User CALLS Api
+    ASYNC User CALLS Notifier
+    Api RETURNS "ok"
+}"#);
+
+        assert!(output.contains("        User -->|calls| Api\n"));
+        assert!(output.contains("        User -.->|calls| Notifier\n"));
+        assert!(!output.contains("ok"));
+    }
+
+    #[test]
+    fn test_else_branch_gets_a_no_edge() {
+        let output = generate(r#"CONTEXT Auth {
+    IF User IS "valid" THEN User GOES TO Dashboard ELSE Visitor GOES TO LoginPage
+}"#);
+
+        assert!(output.contains("cond_1 -->|yes| User\n"));
+        assert!(output.contains("cond_1 -->|no| Visitor\n"));
+        assert!(output.contains("Visitor --> LoginPage\n"));
+    }
+
+    #[test]
+    fn test_batch_becomes_nested_subgraph() {
+        let output = generate("CONTEXT Jobs { IF Queue IS full THEN BATCH 50 { Worker GOES TO Done } }");
+
+        assert!(output.contains("        subgraph batch_1 [\"batch of 50\"]
+            Worker
+            Done
+            Worker --> Done
+        end
+"));
+        assert!(output.contains("cond_1 -->|yes| batch_1\n"));
+    }
+    #[test]
+    fn test_start_node_is_a_stadium() {
+        let output = generate("CONTEXT Auth { VAR Login START Login Login -> Home }");
+        assert!(output.contains("        Login([Login])\n"));
+        assert!(output.contains("        Login --> Home\n"));
+    }
+
+    #[test]
+    fn test_created_by_points_from_creator() {
+        let output = generate("CONTEXT Orders { Order CREATED BY User }");
+        assert!(output.contains("        User -->|creates| Order\n"));
+    }
+
+    #[test]
+    fn test_flow_label_on_edge() {
+        let output = generate(r#"CONTEXT Auth { User -> Dashboard LABELED "after \"login\"" }"#);
+        assert!(output.contains("        User -->|\"after #quot;login#quot;\"| Dashboard\n"));
+    }
+}
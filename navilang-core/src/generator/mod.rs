@@ -0,0 +1,86 @@
+// Generator module - Code Generation Stage
+// This module will implement diagram generation, API generation, documentation generation, and code generation
+
+pub mod csv;
+#[cfg(feature = "graphviz")]
+pub mod dot;
+pub mod format;
+pub mod graphml;
+pub mod json;
+pub mod jsonschema;
+pub mod markdown;
+#[cfg(feature = "mermaid")]
+pub mod mermaid;
+pub mod openapi;
+pub mod plantuml;
+
+use crate::parser::ast::{InteractionVerb, Program, Statement, StatementKind, Visitor, walk_program};
+
+/// Caption for a `BATCH` block in diagrams, e.g. "batch of 50"
+pub(crate) fn batch_label(size: Option<i64>) -> String {
+    match size {
+        Some(size) => format!("batch of {}", size),
+        None => "batch".to_string(),
+    }
+}
+
+/// A directed edge of the flow graph, as exported by the graph formats
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Edge<'ast> {
+    pub source: &'ast str,
+    pub target: &'ast str,
+    /// `flow`, `uses`, `creates`, or the lowercase interaction verb
+    pub kind: &'static str,
+}
+
+/// Every flow, `USES` dependency, `CREATED BY` origin (from creator to
+/// entity) and interaction with an identifier object, in source order,
+/// including those inside blocks
+pub(crate) fn edges(program: &Program) -> Vec<Edge<'_>> {
+    #[derive(Default)]
+    struct Collector<'ast> {
+        edges: Vec<Edge<'ast>>,
+    }
+
+    impl<'ast> Visitor<'ast> for Collector<'ast> {
+        fn visit_statement(&mut self, stmt: &'ast Statement) {
+            let (source, target, kind) = match &stmt.kind {
+                StatementKind::Flow { from, to, .. } => (from, to.as_str(), "flow"),
+                StatementKind::Uses { subject, dependency } => (subject, dependency.as_str(), "uses"),
+                StatementKind::Creation { subject, creator } => (creator, subject.as_str(), "creates"),
+                StatementKind::Interaction { subject, verb, object, .. } => {
+                    let Some(object) = object.as_identifier() else {
+                        return;
+                    };
+                    let kind = match verb {
+                        InteractionVerb::Calls => "calls",
+                        InteractionVerb::Receives => "receives",
+                        InteractionVerb::Returns => "returns",
+                    };
+                    (subject, object, kind)
+                }
+                _ => return,
+            };
+            self.edges.push(Edge { source, target, kind });
+        }
+    }
+
+    let mut collector = Collector::default();
+    walk_program(&mut collector, program);
+    collector.edges
+}
+
+// Placeholder for now
+pub struct CodeGenerator;
+
+impl CodeGenerator {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for CodeGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
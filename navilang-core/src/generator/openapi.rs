@@ -0,0 +1,117 @@
+// OpenAPI generator - API skeletons from ENDPOINT statements
+use crate::parser::ast::{Context, HttpMethod, Program, Statement, StatementKind, Visitor, walk_program};
+use serde_json::{Map, Value, json};
+
+/// Render the endpoints of a program as a minimal OpenAPI 3.0 document
+///
+/// Each `ENDPOINT` adds an operation under its path with a placeholder `200`
+/// response, tagged with the context that declares it. A quoted description
+/// after the path becomes the operation's `summary`. Several methods on the
+/// same path share one path item; a repeated method keeps the last one.
+pub fn to_openapi(program: &Program) -> Value {
+    let mut collector = EndpointCollector::default();
+    walk_program(&mut collector, program);
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "NaviLang API",
+            "version": "1.0.0",
+        },
+        "paths": collector.paths,
+    })
+}
+
+#[derive(Default)]
+struct EndpointCollector<'ast> {
+    paths: Map<String, Value>,
+    contexts: Vec<&'ast str>,
+}
+
+impl<'ast> Visitor<'ast> for EndpointCollector<'ast> {
+    fn visit_context(&mut self, context: &'ast Context) {
+        self.contexts.push(&context.name);
+    }
+
+    fn leave_context(&mut self, _context: &'ast Context) {
+        self.contexts.pop();
+    }
+
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        let StatementKind::Endpoint { method, path, description } = &stmt.kind else {
+            return;
+        };
+
+        let mut operation = Map::new();
+        if let Some(description) = description {
+            operation.insert("summary".to_string(), json!(description));
+        }
+        if let Some(context) = self.contexts.last() {
+            operation.insert("tags".to_string(), json!([context]));
+        }
+        operation.insert("responses".to_string(), json!({
+            "200": { "description": "Successful response" },
+        }));
+
+        let item = self.paths.entry(path.clone()).or_insert_with(|| json!({}));
+        item[method_key(*method)] = Value::Object(operation);
+    }
+}
+
+/// Lowercase method name used as the operation key of a path item
+fn method_key(method: HttpMethod) -> &'static str {
+    match method {
+        HttpMethod::Get => "get",
+        HttpMethod::Post => "post",
+        HttpMethod::Put => "put",
+        HttpMethod::Delete => "delete",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    fn generate(input: &str) -> Value {
+        to_openapi(&parse_program(input))
+    }
+
+    #[test]
+    fn test_endpoints_become_paths() {
+        let doc = generate(r#"CONTEXT Users {
+    ENDPOINT GET "/users" "List users"
+    CONTEXT Admin { ENDPOINT DELETE "/users/{id}" }
+}"#);
+
+        assert_eq!(doc["openapi"], "3.0.3");
+        let paths = doc["paths"].as_object().unwrap();
+        assert_eq!(paths.len(), 2);
+
+        let list = &paths["/users"]["get"];
+        assert_eq!(list["summary"], "List users");
+        assert_eq!(list["tags"], json!(["Users"]));
+        assert_eq!(list["responses"]["200"]["description"], "Successful response");
+
+        let remove = &paths["/users/{id}"]["delete"];
+        assert!(remove.get("summary").is_none());
+        assert_eq!(remove["tags"], json!(["Admin"]));
+        assert!(paths["/users/{id}"].get("get").is_none());
+    }
+
+    #[test]
+    fn test_methods_share_a_path_item() {
+        let doc = generate(r#"CONTEXT Api { ENDPOINT GET "/items"  ENDPOINT POST "/items" }"#);
+
+        let item = doc["paths"]["/items"].as_object().unwrap();
+        let methods: Vec<_> = item.keys().collect();
+        assert_eq!(methods, vec!["get", "post"]);
+    }
+
+    #[test]
+    fn test_program_without_endpoints() {
+        let doc = generate("CONTEXT Flow { User -> Home }");
+        assert_eq!(doc["paths"], json!({}));
+        assert_eq!(doc["info"]["title"], "NaviLang API");
+    }
+}
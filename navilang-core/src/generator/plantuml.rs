@@ -0,0 +1,252 @@
+// PlantUML generator - sequence diagrams from interaction statements
+use crate::parser::ast::{Context, InteractionVerb, Program, Statement, StatementKind};
+use super::batch_label;
+use std::collections::HashMap;
+
+/// Render the interactions of a program as a PlantUML sequence diagram
+///
+/// `A DOES X` is drawn as a self-message `A -> A : X`.
+/// `A CALLS B` becomes `A -> B : Calls`. A later `B RETURNS X` answers the
+/// most recent caller of `B` with `B --> A : Returns X`, and `B RECEIVES X`
+/// shows the data arriving from that caller. Without a known caller, returns
+/// and receives are drawn as notes over the participant. `ASYNC` interactions
+/// use the open arrowheads `->>` and `-->>`, and `BATCH` blocks become groups.
+pub fn to_sequence(program: &Program) -> String {
+    let mut builder = SequenceBuilder::default();
+    builder.line(0, "@startuml");
+
+    for context in &program.contexts {
+        builder.collect_participants(context);
+    }
+    for participant in builder.participants.clone() {
+        builder.line(0, &format!("participant {}", participant));
+    }
+
+    for context in &program.contexts {
+        builder.context(context);
+    }
+
+    builder.line(0, "@enduml");
+    builder.out
+}
+
+#[derive(Default)]
+struct SequenceBuilder {
+    out: String,
+    participants: Vec<String>,
+    /// Most recent caller of each participant, used to route replies
+    callers: HashMap<String, String>,
+}
+
+impl SequenceBuilder {
+    fn line(&mut self, depth: usize, text: &str) {
+        self.out.push_str(&"    ".repeat(depth));
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn collect_participants(&mut self, context: &Context) {
+        let mut stack: Vec<&Statement> = context.statements.iter().rev().collect();
+
+        while let Some(stmt) = stack.pop() {
+            match &stmt.kind {
+                StatementKind::Interaction { subject, verb, object, .. } => {
+                    self.participant(subject);
+                    if let (InteractionVerb::Calls, Some(target)) = (verb, object.as_identifier()) {
+                        self.participant(target);
+                    }
+                }
+                StatementKind::Action { actor, .. } => self.participant(actor),
+                StatementKind::Conditional { then_branch, else_branch, .. } => {
+                    stack.extend(then_branch.iter().chain(else_branch.iter().flatten()).rev());
+                }
+                StatementKind::Loop { body, .. } | StatementKind::Batch { body, .. } => {
+                    stack.extend(body.iter().rev());
+                }
+                StatementKind::Parallel { branches, .. } => {
+                    stack.extend(branches.iter().flatten().rev());
+                }
+                _ => {}
+            }
+        }
+
+        for child in &context.children {
+            self.collect_participants(child);
+        }
+    }
+
+    fn participant(&mut self, name: &str) {
+        if !self.participants.iter().any(|p| p == name) {
+            self.participants.push(name.to_string());
+        }
+    }
+
+    fn context(&mut self, context: &Context) {
+        self.line(0, &format!("== {} ==", context.name));
+        for stmt in &context.statements {
+            self.statement(stmt, 0);
+        }
+        for child in &context.children {
+            self.context(child);
+        }
+    }
+
+    fn statement(&mut self, stmt: &Statement, depth: usize) {
+        match &stmt.kind {
+            StatementKind::Interaction { subject, verb, object, is_async, .. } => {
+                self.interaction(subject, *verb, &object.to_string(), *is_async, depth);
+            }
+            StatementKind::Action { actor, action } => {
+                self.line(depth, &format!("{} -> {} : {}", actor, actor, action));
+            }
+            StatementKind::Conditional { condition, then_branch, else_branch } => {
+                self.line(depth, &format!("alt {}", condition));
+                for stmt in then_branch {
+                    self.statement(stmt, depth + 1);
+                }
+                if let Some(else_branch) = else_branch {
+                    self.line(depth, "else");
+                    for stmt in else_branch {
+                        self.statement(stmt, depth + 1);
+                    }
+                }
+                self.line(depth, "end");
+            }
+            StatementKind::Loop { condition, body } => {
+                match condition {
+                    Some(condition) => self.line(depth, &format!("loop {}", condition)),
+                    None => self.line(depth, "loop"),
+                }
+                for stmt in body {
+                    self.statement(stmt, depth + 1);
+                }
+                self.line(depth, "end");
+            }
+            StatementKind::Batch { size, body } => {
+                self.line(depth, &format!("group {}", batch_label(*size)));
+                for stmt in body {
+                    self.statement(stmt, depth + 1);
+                }
+                self.line(depth, "end");
+            }
+            StatementKind::Parallel { branches, .. } => {
+                for (i, branch) in branches.iter().enumerate() {
+                    self.line(depth, if i == 0 { "par" } else { "else" });
+                    for stmt in branch {
+                        self.statement(stmt, depth + 1);
+                    }
+                }
+                self.line(depth, "end");
+            }
+            StatementKind::VarDecl { .. }
+            | StatementKind::Flow { .. }
+            | StatementKind::Break
+            | StatementKind::Continue
+            | StatementKind::Start { .. }
+            | StatementKind::Ordering { .. }
+            | StatementKind::Uses { .. }
+            | StatementKind::Creation { .. }
+            | StatementKind::Endpoint { .. } => {}
+        }
+    }
+
+    fn interaction(&mut self, subject: &str, verb: InteractionVerb, object: &str, is_async: bool, depth: usize) {
+        let caller = self.callers.get(subject).cloned();
+        let (arrow, reply) = if is_async { ("->>", "-->>") } else { ("->", "-->") };
+
+        let text = match (verb, caller) {
+            (InteractionVerb::Calls, _) => {
+                self.callers.insert(object.to_string(), subject.to_string());
+                format!("{} {} {} : Calls", subject, arrow, object)
+            }
+            (InteractionVerb::Returns, Some(caller)) => {
+                format!("{} {} {} : Returns {}", subject, reply, caller, object)
+            }
+            (InteractionVerb::Receives, Some(caller)) => {
+                format!("{} {} {} : Receives {}", caller, arrow, subject, object)
+            }
+            (InteractionVerb::Returns, None) => format!("note over {} : Returns {}", subject, object),
+            (InteractionVerb::Receives, None) => format!("note over {} : Receives {}", subject, object),
+        };
+
+        self.line(depth, &text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    fn generate(input: &str) -> String {
+        to_sequence(&parse_program(input))
+    }
+
+    #[test]
+    fn test_calls_and_returns() {
+        let output = generate("CONTEXT Auth {
+    User CALLS AuthService
+    AuthService RETURNS Session
+}");
+
+        assert!(output.starts_with("@startuml\n"));
+        assert!(output.trim_end().ends_with("@enduml"));
+        assert!(output.contains("participant User\nparticipant AuthService\n"));
+        assert!(output.contains("== Auth =="));
+
+        let call = output.find("User -> AuthService : Calls").unwrap();
+        let reply = output.find("AuthService --> User : Returns Session").unwrap();
+        assert!(call < reply);
+    }
+
+    #[test]
+    fn test_receives_and_unanswered_returns() {
+        let output = generate(r#"CONTEXT Orders {
+    Order CALLS PaymentService
+    PaymentService RECEIVES "payment_data"
+    Inventory RETURNS Stock
+}"#);
+
+        assert!(output.contains("Order -> PaymentService : Receives \"payment_data\""));
+        assert!(output.contains("note over Inventory : Returns Stock"));
+    }
+
+    #[test]
+    fn test_compound_statements_become_fragments() {
+        let output = generate(r#"CONTEXT Flow {
+    IF User IS "valid" THEN User CALLS Api
+    PARALLEL { A CALLS B C CALLS D }
+}"#);
+
+        assert!(output.contains("alt User IS \"valid\"\n    User -> Api : Calls\nend"));
+        assert!(output.contains("par\n    A -> B : Calls\nelse\n    C -> D : Calls\nend"));
+    }
+
+    #[test]
+    fn test_actions_are_self_messages() {
+        let output = generate(r#"CONTEXT Auth { User DOES "Log in" }"#);
+
+        assert!(output.contains("participant User\n"));
+        assert!(output.contains("User -> User : Log in\n"));
+    }
+
+    #[test]
+    fn test_async_interactions_use_open_arrows() {
+        let output = generate("CONTEXT Notify {
+    ASYNC User CALLS Notifier
+    ASYNC Notifier RETURNS Ack
+    User CALLS Api
+}");
+
+        assert!(output.contains("User ->> Notifier : Calls\n"));
+        assert!(output.contains("Notifier -->> User : Returns Ack\n"));
+        assert!(output.contains("User -> Api : Calls\n"));
+    }
+
+    #[test]
+    fn test_else_branch_splits_alt() {
+        let output = generate(r#"CONTEXT Flow { IF User IS "valid" THEN User CALLS Api ELSE User CALLS Help }"#);
+
+        assert!(output.contains("alt User IS \"valid\"\n    User -> Api : Calls\nelse\n    User -> Help : Calls\nend"));
+    }
+}
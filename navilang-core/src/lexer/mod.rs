@@ -3,8 +3,9 @@
 
 pub mod tokens;
 
-use crate::error::{NaviLangError, Position, Span};
+use crate::error::{ErrorCollector, NaviLangError, Position, Span};
 use logos::Logos;
+use std::collections::HashMap;
 use tokens::Token;
 
 /// Token with associated span information for error reporting
@@ -12,12 +13,56 @@ use tokens::Token;
 pub struct TokenWithSpan {
     pub token: Token,
     pub span: Span,
+    /// Comments directly preceding this token (only filled by `tokenize_with_trivia`)
+    pub leading_comments: Vec<String>,
+}
+
+/// Lexer options, such as a team's preferred verbs for keywords
+///
+/// The default configuration has no aliases and no error limit.
+#[derive(Debug, Clone, Default)]
+pub struct LexerConfig {
+    /// Uppercased alias spelling mapped to its canonical keyword token
+    aliases: HashMap<String, Token>,
+    max_errors: Option<usize>,
+}
+
+impl LexerConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lex `alias` as `keyword`, e.g. `SENDS` as `Token::Calls`
+    ///
+    /// Like keywords, aliases match case-insensitively. Only plain identifiers
+    /// are remapped, so a backtick-escaped alias stays an identifier.
+    pub fn alias(mut self, alias: &str, keyword: Token) -> Self {
+        debug_assert!(keyword.is_keyword(), "alias target must be a keyword, got {:?}", keyword);
+        self.aliases.insert(alias.to_uppercase(), keyword);
+        self
+    }
+
+    /// Make `tokenize_recovering` stop after `max_errors` lexical errors,
+    /// ending its errors with a `TooManyErrors` note
+    pub fn max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = Some(max_errors);
+        self
+    }
+
+    /// The keyword an identifier stands for, if it is a configured alias
+    fn resolve(&self, name: &str) -> Option<&Token> {
+        if self.aliases.is_empty() {
+            return None;
+        }
+        self.aliases.get(&name.to_uppercase())
+    }
 }
 
 /// The main lexer struct that converts source text into tokens
 pub struct Lexer<'a> {
     input: &'a str,
     lexer: logos::Lexer<'a, Token>,
+    config: LexerConfig,
     line: usize,
     column: usize,
     offset: usize,
@@ -26,9 +71,15 @@ pub struct Lexer<'a> {
 impl<'a> Lexer<'a> {
     /// Create a new lexer for the given input
     pub fn new(input: &'a str) -> Self {
+        Self::with_config(input, LexerConfig::default())
+    }
+    
+    /// Create a lexer that applies the aliases and limits in `config`
+    pub fn with_config(input: &'a str, config: LexerConfig) -> Self {
         Self {
             input,
             lexer: Token::lexer(input),
+            config,
             line: 1,
             column: 1,
             offset: 0,
@@ -37,28 +88,60 @@ impl<'a> Lexer<'a> {
     
     /// Tokenize the entire input and return all tokens (including whitespace)
     pub fn tokenize(&mut self) -> Result<Vec<TokenWithSpan>, NaviLangError> {
+        self.collect()
+    }
+    
+    /// Tokenize the entire input, recording lexical errors instead of stopping
+    /// at the first one
+    ///
+    /// Each unrecognized slice is reported as a `SyntaxError` and skipped, so
+    /// the returned tokens (including whitespace) cover everything else. With
+    /// [`LexerConfig::max_errors`] set, lexing ends at the first error past
+    /// the limit and the tokens stop there.
+    pub fn tokenize_recovering(&mut self) -> (Vec<TokenWithSpan>, Vec<NaviLangError>) {
         let mut tokens = Vec::new();
+        let mut collector = ErrorCollector::with_optional_limit(self.config.max_errors);
         
-        while let Some(result) = self.lexer.next() {
-            let span = self.current_span();
-            
+        while !collector.is_full() && let Some(result) = self.next() {
             match result {
-                Ok(token) => {
-                    tokens.push(TokenWithSpan { token, span });
-                }
-                Err(_) => {
-                    return Err(NaviLangError::syntax_error(
-                        format!("Unexpected character: '{}'", self.lexer.slice()),
-                        self.input.to_string(),
-                        span,
-                    ));
-                }
+                Ok(token) => tokens.push(token),
+                Err(error) => collector.add_error(error),
             }
-            
-            self.update_position();
         }
         
-        Ok(tokens)
+        (tokens, collector.into_errors())
+    }
+    
+    /// Build the error for the slice logos failed to recognize
+    fn lexical_error(&self, span: Span) -> NaviLangError {
+        let slice = self.lexer.slice();
+        let message = if slice.starts_with("/*") {
+            "Unterminated block comment".to_string()
+        } else if slice.starts_with(['"', '\'']) {
+            format!("Invalid escape sequence in string literal: {}", slice)
+        } else {
+            format!("Unexpected character: '{}'", slice)
+        };
+        NaviLangError::syntax_error(message, self.input.to_string(), span)
+    }
+    
+    /// Report a string literal with no closing quote, spanning from the quote
+    /// to the end of its line, and resume lexing on the next line
+    ///
+    /// Logos has already consumed the rest of the input trying to match the
+    /// literal, so the underlying lexer is restarted at the line end.
+    fn unterminated_string(&mut self, start: Position) -> NaviLangError {
+        let line_end = self.input[start.offset..].find('\n')
+            .map_or(self.input.len(), |i| start.offset + i);
+        let span = Span::new(start, advance_position(start, &self.input[start.offset..line_end]));
+        
+        self.lexer = Token::lexer(self.input);
+        self.lexer.bump(line_end);
+        self.line = span.end.line;
+        self.column = span.end.column;
+        self.offset = span.end.offset;
+        
+        NaviLangError::syntax_error("Unterminated string literal".to_string(), self.input.to_string(), span)
     }
     
     /// Tokenize input and filter out whitespace/comments
@@ -69,41 +152,45 @@ impl<'a> Lexer<'a> {
             .collect())
     }
     
-    /// Get the current span for the token being processed
-    fn current_span(&self) -> Span {
-        let start = Position::new(self.line, self.column, self.offset);
-        let slice_len = self.lexer.slice().len();
-        let end_offset = self.offset + slice_len;
-        
-        // Calculate end position considering newlines
-        let mut end_line = self.line;
-        let mut end_column = self.column;
-        
-        for ch in self.lexer.slice().chars() {
-            if ch == '\n' {
-                end_line += 1;
-                end_column = 1;
-            } else {
-                end_column += 1;
+    /// Tokenize input, dropping whitespace but attaching comments as leading
+    /// trivia to the next significant token
+    ///
+    /// Comment text is stored without its `//` or `/* */` delimiters. Comments
+    /// at the end of the input, with no token following them, are discarded.
+    pub fn tokenize_with_trivia(&mut self) -> Result<Vec<TokenWithSpan>, NaviLangError> {
+        let tokens = self.tokenize()?;
+        let mut result = Vec::new();
+        let mut pending = Vec::new();
+        
+        for mut token in tokens {
+            match &token.token {
+                Token::Comment | Token::BlockComment => {
+                    let text = &self.input[token.span.start.offset..token.span.end.offset];
+                    pending.push(comment_text(text));
+                }
+                t if t.is_whitespace() => {}
+                _ => {
+                    token.leading_comments = std::mem::take(&mut pending);
+                    result.push(token);
+                }
             }
         }
         
-        let end = Position::new(end_line, end_column, end_offset);
-        Span::new(start, end)
+        Ok(result)
+    }
+    
+    /// Get the current span for the token being processed
+    fn current_span(&self) -> Span {
+        let start = self.current_position();
+        Span::new(start, advance_position(start, self.lexer.slice()))
     }
     
     /// Update internal position tracking
     fn update_position(&mut self) {
-        let slice = self.lexer.slice();
-        for ch in slice.chars() {
-            if ch == '\n' {
-                self.line += 1;
-                self.column = 1;
-            } else {
-                self.column += 1;
-            }
-        }
-        self.offset += slice.len();
+        let end = advance_position(self.current_position(), self.lexer.slice());
+        self.line = end.line;
+        self.column = end.column;
+        self.offset = end.offset;
     }
     
     /// Get the current lexer slice (for debugging)
@@ -117,6 +204,118 @@ impl<'a> Lexer<'a> {
     }
 }
 
+/// Lazily yields tokens (including whitespace) with their spans
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<TokenWithSpan, NaviLangError>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.lexer.next()?;
+        let span = self.current_span();
+        
+        if result.is_err() && is_unterminated_string(self.lexer.slice()) {
+            return Some(Err(self.unterminated_string(span.start)));
+        }
+        
+        let item = match result {
+            Ok(Token::Identifier(name)) if !self.lexer.slice().starts_with('`') => {
+                let token = self.config.resolve(&name).cloned().unwrap_or(Token::Identifier(name));
+                Ok(TokenWithSpan { token, span, leading_comments: Vec::new() })
+            }
+            Ok(token) => Ok(TokenWithSpan { token, span, leading_comments: Vec::new() }),
+            Err(_) => Err(self.lexical_error(span)),
+        };
+        
+        self.update_position();
+        Some(item)
+    }
+}
+
+/// Compute the position reached after consuming `text` from `start`
+///
+/// Columns count Unicode scalar values while offsets count bytes, so spans
+/// stay valid for miette on non-ASCII input.
+fn advance_position(start: Position, text: &str) -> Position {
+    let mut end = start;
+    for ch in text.chars() {
+        if ch == '\n' {
+            end.advance_line();
+        } else {
+            end.advance_column();
+        }
+    }
+    end.advance_offset(text.len());
+    end
+}
+
+/// Whether `slice`, starting at a quote, never reaches its closing quote
+///
+/// Escapes are skipped in single- and double-quoted strings; triple-quoted
+/// strings end at the next `"""`.
+fn is_unterminated_string(slice: &str) -> bool {
+    if let Some(body) = slice.strip_prefix(r#"""""#) {
+        return !body.contains(r#"""""#);
+    }
+    let Some(quote) = slice.chars().next().filter(|c| matches!(c, '"' | '\'')) else {
+        return false;
+    };
+    
+    let mut chars = slice[1..].chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                chars.next();
+            }
+            ch if ch == quote => return false,
+            _ => {}
+        }
+    }
+    true
+}
+
+/// Strip the delimiters from a line or block comment
+fn comment_text(comment: &str) -> String {
+    let body = match comment.strip_prefix("//") {
+        Some(line) => line,
+        None => comment
+            .strip_prefix("/*")
+            .and_then(|c| c.strip_suffix("*/"))
+            .unwrap_or(comment),
+    };
+    body.trim().to_string()
+}
+
+/// Rebuild source text from `tokens`, concatenating each token's slice of
+/// `input`
+///
+/// Nothing but the token slices is copied, so the spans must tile `input`:
+/// each token starts where the previous one ended and the last ends at the
+/// end of `input`. That holds for [`Lexer::tokenize`], which keeps whitespace
+/// and comments; a lexer change that drops or shifts a span fails with a
+/// syntax error at the gap, overlap or untokenized tail.
+pub fn reconstruct(input: &str, tokens: &[TokenWithSpan]) -> Result<String, NaviLangError> {
+    let error = |message: &str, span: Span| NaviLangError::syntax_error(message.to_string(), input.to_string(), span);
+    let mut out = String::with_capacity(input.len());
+    let mut end = Position::new(1, 1, 0);
+    for token in tokens {
+        let span = &token.span;
+        if span.start.offset > end.offset {
+            return Err(error("Text between tokens is not covered by any span", Span::new(end, span.start)));
+        }
+        if span.start.offset < end.offset {
+            return Err(error("Token overlaps the previous one", span.clone()));
+        }
+        let Some(slice) = input.get(span.start.offset..span.end.offset) else {
+            return Err(error("Token span is outside the input", span.clone()));
+        };
+        out.push_str(slice);
+        end = span.end;
+    }
+    if end.offset < input.len() {
+        return Err(error("Text after the last token is not covered by any span", Span::single_char(end)));
+    }
+    Ok(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,6 +365,31 @@ mod tests {
         assert!(matches!(tokens[3].token, Token::Identifier(_)));
     }
     
+    #[test]
+    fn test_arrow_shorthand() {
+        let input = "A -> B";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize_filtered().unwrap();
+        
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[0].token, Token::Identifier("A".to_string()));
+        assert_eq!(tokens[1].token, Token::Arrow);
+        assert_eq!(tokens[2].token, Token::Identifier("B".to_string()));
+        assert_eq!(tokens[1].token.to_string(), "->");
+    }
+    
+    #[test]
+    fn test_arrow_does_not_swallow_signs() {
+        let input = "A->B -> -1";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize_filtered().unwrap();
+        
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokens[1].token, Token::Arrow);
+        assert_eq!(tokens[3].token, Token::Arrow);
+        assert_eq!(tokens[4].token, Token::Number(Some(-1)));
+    }
+    
     #[test]
     fn test_type_annotations() {
         let input = "VAR User:Entity";
@@ -190,6 +414,96 @@ mod tests {
         assert!(matches!(tokens[1].token, Token::Duration(_)));
     }
     
+    #[test]
+    fn test_hex_and_binary_numbers() {
+        let input = "RETRY 0x1F 0XfF 0b1010 0B11";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize_filtered().unwrap();
+        
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokens[0].token, Token::Retry);
+        assert_eq!(tokens[1].token, Token::Number(Some(31)));
+        assert_eq!(tokens[2].token, Token::Number(Some(255)));
+        assert_eq!(tokens[3].token, Token::Number(Some(10)));
+        assert_eq!(tokens[4].token, Token::Number(Some(3)));
+    }
+    
+    #[test]
+    fn test_malformed_radix_numbers() {
+        let input = "0xGG 0b102 0xFFFFFFFFFFFFFFFFFF";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize_filtered().unwrap();
+        
+        assert_eq!(tokens.len(), 3);
+        assert!(tokens.iter().all(|t| t.token == Token::Number(None)));
+    }
+    
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn test_signed_numbers() {
+        let input = "-5 +5 -3.14 -10s -0x1F";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize_filtered().unwrap();
+        
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokens[0].token, Token::Number(Some(-5)));
+        assert_eq!(tokens[1].token, Token::Number(Some(5)));
+        assert_eq!(tokens[2].token, Token::Float(Some(-3.14)));
+        assert_eq!(tokens[3].token, Token::Duration("-10s".to_string()));
+        assert_eq!(tokens[4].token, Token::Number(Some(-31)));
+    }
+    
+    #[test]
+    fn test_sign_binds_to_following_digits() {
+        let input = "A-1";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize_filtered().unwrap();
+        
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].token, Token::Identifier("A".to_string()));
+        assert_eq!(tokens[1].token, Token::Number(Some(-1)));
+    }
+    
+    #[test]
+    fn test_scientific_notation_floats() {
+        let input = "1.5e3 2E-4 6.022e23 2e10 1e999";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize_filtered().unwrap();
+        
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokens[0].token, Token::Float(Some(1500.0)));
+        assert_eq!(tokens[1].token, Token::Float(Some(0.0002)));
+        assert_eq!(tokens[2].token, Token::Float(Some(6.022e23)));
+        assert_eq!(tokens[3].token, Token::Float(Some(2e10)));
+        assert_eq!(tokens[4].token, Token::Float(None));
+    }
+    
+    #[test]
+    fn test_digit_separators() {
+        let input = "1_000 1_000_000 1_000.5 0xFF_FF";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize_filtered().unwrap();
+        
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(tokens[0].token, Token::Number(Some(1000)));
+        assert_eq!(tokens[1].token, Token::Number(Some(1_000_000)));
+        assert_eq!(tokens[2].token, Token::Float(Some(1000.5)));
+        assert_eq!(tokens[3].token, Token::Number(Some(0xFFFF)));
+    }
+    
+    #[test]
+    fn test_malformed_digit_separators() {
+        for input in ["1__0", "5_", "1_.5", "0x_FF"] {
+            let mut lexer = Lexer::new(input);
+            assert!(lexer.tokenize_filtered().is_err(), "Expected error for input: {}", input);
+        }
+        
+        // A leading underscore makes it an identifier, not a number
+        let mut lexer = Lexer::new("_5");
+        let tokens = lexer.tokenize_filtered().unwrap();
+        assert_eq!(tokens[0].token, Token::Identifier("_5".to_string()));
+    }
+    
     #[test]
     fn test_quoted_strings() {
         let input = r#"CONTEXT "My Context""#;
@@ -206,6 +520,71 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_string_escape_sequences() {
+        let input = r#""a\tb" "\u0041" "line\nbreak" "say \"hi\" \\ done""#;
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize_filtered().unwrap();
+        
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(tokens[0].token, Token::QuotedString("a\tb".to_string()));
+        assert_eq!(tokens[1].token, Token::QuotedString("A".to_string()));
+        assert_eq!(tokens[2].token, Token::QuotedString("line\nbreak".to_string()));
+        assert_eq!(tokens[3].token, Token::QuotedString("say \"hi\" \\ done".to_string()));
+    }
+    
+    #[test]
+    fn test_single_quoted_strings() {
+        let input = r#"'hello "world"' 'it\'s' 'tab\there'"#;
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize_filtered().unwrap();
+        
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[0].token, Token::QuotedString("hello \"world\"".to_string()));
+        assert_eq!(tokens[1].token, Token::QuotedString("it's".to_string()));
+        assert_eq!(tokens[2].token, Token::QuotedString("tab\there".to_string()));
+    }
+    
+    #[test]
+    fn test_triple_quoted_strings() {
+        let input = "CONTEXT \"\"\"First line\n  \"quoted\" second\nthird\"\"\" {\n}";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize_filtered().unwrap();
+        
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(
+            tokens[1].token,
+            Token::QuotedString("First line\n  \"quoted\" second\nthird".to_string())
+        );
+        assert_eq!(tokens[1].span.start.line, 1);
+        assert_eq!(tokens[1].span.end.line, 3);
+        
+        // The brace after the closing delimiter is still on line 3
+        assert_eq!(tokens[2].token, Token::LeftBrace);
+        assert_eq!(tokens[2].span.start.line, 3);
+        assert_eq!(tokens[2].span.start.column, 10);
+        assert_eq!(tokens[3].span.start.line, 4);
+    }
+    
+    #[test]
+    fn test_invalid_escape_sequence() {
+        let input = r#"VAR "bad\q""#;
+        let mut lexer = Lexer::new(input);
+        let result = lexer.tokenize_filtered();
+        
+        if let Err(NaviLangError::SyntaxError { message, span, .. }) = result {
+            assert!(message.contains("Invalid escape sequence"));
+            assert_eq!(span.offset(), 4);
+            assert_eq!(span.len(), 7);
+        } else {
+            panic!("Expected SyntaxError for invalid escape");
+        }
+        
+        for bad in [r#""\u+041""#, r#""\u-041""#, r#""\u12""#, r#""\u00é9""#] {
+            assert!(Lexer::new(bad).tokenize_filtered().is_err(), "{} should not lex", bad);
+        }
+    }
+    
     #[test]
     fn test_error_handling() {
         let input = "VAR User @invalid";
@@ -218,6 +597,71 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_recovering_tokenizer() {
+        let input = "VAR @ User # Session";
+        let mut lexer = Lexer::new(input);
+        let (tokens, errors) = lexer.tokenize_recovering();
+        
+        assert_eq!(errors.len(), 2);
+        for error in &errors {
+            assert!(matches!(error, NaviLangError::SyntaxError { message, .. }
+                if message.contains("Unexpected character")));
+        }
+        if let NaviLangError::SyntaxError { span, .. } = &errors[1] {
+            assert_eq!(span.offset(), 11);
+        }
+        
+        let significant: Vec<_> = tokens.iter()
+            .filter(|t| !t.token.is_whitespace())
+            .map(|t| t.token.clone())
+            .collect();
+        assert_eq!(significant, vec![
+            Token::Var,
+            Token::Identifier("User".to_string()),
+            Token::Identifier("Session".to_string()),
+        ]);
+    }
+    
+    #[test]
+    fn test_recovering_tokenizer_stops_after_max_errors() {
+        let input = "VAR @ User # Session $ Home % Cart";
+        let mut lexer = Lexer::with_config(input, LexerConfig::new().max_errors(1));
+        let (tokens, errors) = lexer.tokenize_recovering();
+        
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(errors[1], NaviLangError::TooManyErrors { limit: 1 }));
+        // Lexing ended at the second error, before `Session`
+        assert!(tokens.iter().any(|t| t.token == Token::Identifier("User".to_string())));
+        assert!(!tokens.iter().any(|t| t.token == Token::Identifier("Session".to_string())));
+    }
+    
+    #[test]
+    fn test_iterator_matches_tokenize() {
+        let input = "CONTEXT \"Auth\" {\n  VAR User:Entity // doc\n  User GOES TO Dashboard\n}";
+        let expected = Lexer::new(input).tokenize().unwrap();
+        
+        let streamed: Vec<TokenWithSpan> = Lexer::new(input)
+            .map(|result| result.unwrap())
+            .collect();
+        
+        assert_eq!(streamed.len(), expected.len());
+        for (a, b) in streamed.iter().zip(expected.iter()) {
+            assert_eq!(a.token, b.token);
+            assert_eq!(a.span, b.span);
+        }
+    }
+    
+    #[test]
+    fn test_iterator_is_lazy_after_errors() {
+        let mut lexer = Lexer::new("VAR @ User");
+        
+        assert_eq!(lexer.next().unwrap().unwrap().token, Token::Var);
+        assert_eq!(lexer.next().unwrap().unwrap().token, Token::Whitespace);
+        assert!(lexer.next().unwrap().is_err());
+        assert_eq!(lexer.current_position().offset, 5);
+    }
+    
     #[test]
     fn test_position_tracking() {
         let input = "CONTEXT Test {\n  VAR User\n}";
@@ -232,4 +676,295 @@ mod tests {
         let var_token = tokens.iter().find(|t| t.token == Token::Var).unwrap();
         assert_eq!(var_token.span.start.line, 2);
     }
+    
+    #[test]
+    fn test_multibyte_columns() {
+        let input = "CONTEXT \"café\" {\n  VAR \"🚀\" User\n}";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize_filtered().unwrap();
+        
+        // "café" is 6 characters but 7 bytes
+        let name = &tokens[1].span;
+        assert_eq!((name.start.column, name.end.column), (9, 15));
+        assert_eq!((name.start.offset, name.end.offset), (8, 15));
+        assert_eq!(name.to_miette_span().len(), 7);
+        
+        let brace = &tokens[2].span;
+        assert_eq!(brace.start.column, 16);
+        assert_eq!(brace.start.offset, 16);
+        
+        // "🚀" is 3 characters but 6 bytes
+        let rocket = &tokens[4].span;
+        assert_eq!((rocket.start.line, rocket.start.column, rocket.end.column), (2, 7, 10));
+        assert_eq!(&input[rocket.start.offset..rocket.end.offset], "\"🚀\"");
+        
+        let user = &tokens[5].span;
+        assert_eq!(user.start.column, 11);
+        assert_eq!(&input[user.start.offset..user.end.offset], "User");
+    }
+    
+    #[test]
+    fn test_comments_as_leading_trivia() {
+        let input = "// doc\nVAR User\n/* the session */\n// more\nVAR Session";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize_with_trivia().unwrap();
+        
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(tokens[0].token, Token::Var);
+        assert_eq!(tokens[0].leading_comments, vec!["doc"]);
+        assert!(tokens[1].leading_comments.is_empty());
+        assert_eq!(tokens[2].token, Token::Var);
+        assert_eq!(tokens[2].leading_comments, vec!["the session", "more"]);
+    }
+    
+    #[test]
+    fn test_keyword_lists_match_tokens() {
+        for word in tokens::keywords() {
+            let tokens = Lexer::new(word).tokenize_filtered().unwrap();
+            assert_eq!(tokens.len(), 1, "Expected one token for keyword: {}", word);
+            assert!(tokens[0].token.is_keyword(), "Not a keyword: {}", word);
+            assert_eq!(&tokens[0].token.to_string(), word);
+        }
+        
+        for word in tokens::types() {
+            let tokens = Lexer::new(word).tokenize_filtered().unwrap();
+            assert_eq!(tokens.len(), 1, "Expected one token for type: {}", word);
+            assert!(tokens[0].token.is_type(), "Not a type: {}", word);
+            assert!(tokens::keywords().contains(word));
+        }
+    }
+    
+    #[test]
+    fn test_backtick_escaped_identifiers() {
+        let input = "VAR `Service`:Service `is` -> `User`";
+        let tokens = Lexer::new(input).tokenize_filtered().unwrap();
+        let kinds: Vec<_> = tokens.iter().map(|t| t.token.clone()).collect();
+        
+        assert_eq!(kinds, vec![
+            Token::Var,
+            Token::Identifier("Service".to_string()),
+            Token::Colon,
+            Token::Service,
+            Token::Identifier("is".to_string()),
+            Token::Arrow,
+            Token::Identifier("User".to_string()),
+        ]);
+        assert_eq!(tokens[1].span.end.offset, 13);
+        
+        // Reserved words keep their backticks when rendered; plain names don't need them
+        assert_eq!(kinds[1].to_string(), "`Service`");
+        assert_eq!(kinds[4].to_string(), "`is`");
+        assert_eq!(kinds[6].to_string(), "User");
+        
+        assert!(Lexer::new("`Two words`").tokenize().is_err());
+    }
+    
+    #[test]
+    fn test_configured_aliases() {
+        let config = LexerConfig::new()
+            .alias("SENDS", Token::Calls)
+            .alias("navigates", Token::Goes);
+        let input = "User SENDS X  user sends `Sends`  A NAVIGATES TO B";
+        let kinds: Vec<_> = Lexer::with_config(input, config).tokenize_filtered().unwrap()
+            .into_iter()
+            .map(|t| t.token)
+            .collect();
+        
+        assert_eq!(kinds, vec![
+            Token::Identifier("User".to_string()),
+            Token::Calls,
+            Token::Identifier("X".to_string()),
+            Token::Identifier("user".to_string()),
+            Token::Calls,
+            Token::Identifier("Sends".to_string()),
+            Token::Identifier("A".to_string()),
+            Token::Goes,
+            Token::To,
+            Token::Identifier("B".to_string()),
+        ]);
+        
+        // Without a config, aliases are ordinary identifiers
+        let tokens = Lexer::new("User SENDS X").tokenize_filtered().unwrap();
+        assert_eq!(tokens[1].token, Token::Identifier("SENDS".to_string()));
+    }
+    
+    #[test]
+    fn test_dotted_identifiers() {
+        let input = "A.B.C -> User.Profile Order.Is 1.5";
+        let tokens = Lexer::new(input).tokenize_filtered().unwrap();
+        let kinds: Vec<_> = tokens.iter().map(|t| t.token.clone()).collect();
+        
+        assert_eq!(kinds, vec![
+            Token::Identifier("A.B.C".to_string()),
+            Token::Arrow,
+            Token::Identifier("User.Profile".to_string()),
+            Token::Identifier("Order.Is".to_string()),
+            Token::Float(Some(1.5)),
+        ]);
+        assert_eq!(tokens[0].span.end.offset, 5);
+        assert_eq!(kinds[3].to_string(), "Order.Is");
+        
+        // Names can't end in (or be followed by) a dot
+        assert!(Lexer::new("User. X").tokenize().is_err());
+        assert!(Lexer::new("A.B.").tokenize().is_err());
+    }
+    
+    #[test]
+    fn test_inline_block_comments_keep_spans() {
+        let tokens = Lexer::new("VAR /*id*/ User").tokenize_filtered().unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[1].token, Token::Identifier("User".to_string()));
+        assert_eq!(tokens[1].span, Span::new(Position::new(1, 12, 11), Position::new(1, 16, 15)));
+        
+        // A newline inside the comment moves the following token to the next line
+        let input = "VAR /* x\ny */ User\nUser -> Home";
+        let tokens = Lexer::new(input).tokenize_filtered().unwrap();
+        assert_eq!(tokens[1].span.start, Position::new(2, 6, 14));
+        assert_eq!(tokens[1].span.end, Position::new(2, 10, 18));
+        assert_eq!(&input[tokens[1].span.start.offset..tokens[1].span.end.offset], "User");
+        assert_eq!(tokens[2].span.start, Position::new(3, 1, 19));
+        
+        let trivia = Lexer::new(input).tokenize_with_trivia().unwrap();
+        assert_eq!(trivia[1].leading_comments, vec!["x\ny"]);
+        assert_eq!(trivia[1].span, tokens[1].span);
+    }
+    
+    #[test]
+    fn test_long_block_comments_lex_quickly() {
+        let stars = "*".repeat(100_000);
+        let started = std::time::Instant::now();
+        
+        let closed = format!("/*{}*/ User", stars);
+        let tokens = Lexer::new(&closed).tokenize_filtered().unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert!(Lexer::new(&format!("/*{}", stars)).tokenize().is_err());
+        
+        assert!(started.elapsed() < std::time::Duration::from_secs(2), "took {:?}", started.elapsed());
+        
+        // Stars right before the closing delimiter still close the comment
+        let tokens = Lexer::new("/* a **/ User /***/").tokenize().unwrap();
+        assert_eq!(tokens.iter().filter(|t| t.token == Token::BlockComment).count(), 2);
+    }
+    
+    #[test]
+    fn test_unterminated_block_comment() {
+        let input = "VAR User\n/* never\nclosed";
+        let (tokens, errors) = Lexer::new(input).tokenize_recovering();
+        
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            NaviLangError::SyntaxError { message, span, .. } => {
+                assert_eq!(message, "Unterminated block comment");
+                assert_eq!(span.offset(), input.find("/*").unwrap());
+                assert_eq!(span.len(), input.len() - span.offset());
+            }
+            other => panic!("Expected SyntaxError, got {:?}", other),
+        }
+        // Nothing after the opening delimiter is lexed as code
+        assert!(!tokens.iter().any(|t| t.token == Token::Identifier("closed".to_string())));
+    }
+    
+    #[test]
+    fn test_unterminated_string_literal() {
+        let input = "VAR User\nUser RETURNS \"abc\nUser -> Home";
+        let (tokens, errors) = Lexer::new(input).tokenize_recovering();
+        
+        assert_eq!(errors.len(), 1);
+        let quote = input.find('"').unwrap();
+        match &errors[0] {
+            NaviLangError::SyntaxError { message, span, .. } => {
+                assert_eq!(message, "Unterminated string literal");
+                assert_eq!(span.offset(), quote);
+                assert_eq!(span.len(), "\"abc".len());
+            }
+            other => panic!("Expected SyntaxError, got {:?}", other),
+        }
+        
+        // Lexing resumes on the next line with correct positions
+        let significant: Vec<_> = tokens.iter().filter(|t| !t.token.is_whitespace()).collect();
+        let last = significant.last().unwrap();
+        assert_eq!(last.token, Token::Identifier("Home".to_string()));
+        assert_eq!(last.span.start, Position::new(3, 9, input.rfind("Home").unwrap()));
+        
+        for unterminated in ["'abc", "\"ab\\\"", "\"\"\"abc\ndef"] {
+            let errors = Lexer::new(unterminated).tokenize_recovering().1;
+            assert_eq!(errors[0].to_string(), "Syntax error: Unterminated string literal", "{}", unterminated);
+        }
+        // A closed literal with a bad escape is still reported as such
+        let errors = Lexer::new("\"a\\qb\" X").tokenize_recovering().1;
+        assert!(errors[0].to_string().contains("Invalid escape sequence"));
+    }
+    
+    #[test]
+    fn test_final_span_reaches_end_without_trailing_newline() {
+        for input in [
+            "VAR User",
+            "VAR User\n",
+            "CONTEXT A { User -> Home }\n// last line",
+            "User RETURNS \"Café\" // note é",
+            "VAR User\r\n/* block\ncomment */",
+        ] {
+            let tokens = Lexer::new(input).tokenize().unwrap();
+            let last = tokens.last().unwrap();
+            assert_eq!(last.span.end.offset, input.len(), "{:?}", input);
+        }
+        
+        let tokens = Lexer::new("VAR User\n// last").tokenize().unwrap();
+        let last = tokens.last().unwrap();
+        assert_eq!(last.token, Token::Comment);
+        assert_eq!((last.span.start.line, last.span.start.column), (2, 1));
+        assert_eq!((last.span.end.line, last.span.end.column), (2, 8));
+    }
+    
+    #[test]
+    fn test_comment_only_input() {
+        let input = "// nothing but a comment";
+        let tokens = Lexer::new(input).tokenize().unwrap();
+        
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].token, Token::Comment);
+        assert_eq!(tokens[0].span.start.offset, 0);
+        assert_eq!(tokens[0].span.end.offset, input.len());
+        assert_eq!(tokens[0].span.end.column, input.len() + 1);
+        
+        assert!(Lexer::new(input).tokenize_filtered().unwrap().is_empty());
+        assert!(Lexer::new(input).tokenize_with_trivia().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reconstruct_round_trips_source() {
+        let input = "// Checkout flow\r\nCONTEXT \"Check out\" {\n\tVAR Cart:[Object]  /* block\n comment */\n    Cart -> Paid TIMEOUT 1_000ms\n    IF Cart.Total >= 9.5 THEN Cart DOES \"Pay \\\"now\\\"\"\n}\n\n";
+        let tokens = Lexer::new(input).tokenize().unwrap();
+        assert_eq!(reconstruct(input, &tokens).unwrap(), input);
+
+        // Spans of every token, whitespace included, tile the input without gaps
+        let mut end = 0;
+        for token in &tokens {
+            assert_eq!(token.span.start.offset, end);
+            end = token.span.end.offset;
+        }
+        assert_eq!(end, input.len());
+    }
+
+    #[test]
+    fn test_reconstruct_rejects_spans_that_do_not_tile() {
+        let input = "VAR User";
+        let tokens = Lexer::new(input).tokenize().unwrap();
+        let message = |tokens: &[TokenWithSpan]| match reconstruct(input, tokens) {
+            Err(NaviLangError::SyntaxError { message, .. }) => message,
+            other => panic!("Expected SyntaxError, got {:?}", other),
+        };
+
+        // The whitespace token shrunk by a byte leaves a gap
+        let mut gap = tokens.clone();
+        gap[1].span.end.offset -= 1;
+        assert_eq!(message(&gap), "Text between tokens is not covered by any span");
+
+        let mut overlap = tokens.clone();
+        overlap[2].span.start.offset -= 1;
+        assert_eq!(message(&overlap), "Token overlaps the previous one");
+
+        assert_eq!(message(&tokens[..2]), "Text after the last token is not covered by any span");
+        assert_eq!(reconstruct(input, &tokens).unwrap(), input);
+    }
 }
@@ -1,6 +1,7 @@
 // Token definitions using logos for the NaviLang lexer (Case-insensitive)
 use logos::Logos;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Logos, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Token {
@@ -29,6 +30,9 @@ pub enum Token {
     #[regex(r"(?i)then")]
     Then,
     
+    #[regex(r"(?i)else")]
+    Else,
+    
     #[regex(r"(?i)when")]
     When,
     
@@ -90,6 +94,16 @@ pub enum Token {
     #[regex(r"(?i)continue")]
     Continue,
     
+    #[regex(r"(?i)start")]
+    Start,
+    
+    #[regex(r"(?i)labeled")]
+    Labeled,
+    
+    // Preprocessor directives
+    #[regex(r"(?i)include")]
+    Include,
+    
     // Punctuation and operators
     #[token("{")]
     LeftBrace,
@@ -133,23 +147,65 @@ pub enum Token {
     #[token(">=")]
     GreaterEqual,
     
+    // Shorthand for `GOES TO`; longest match keeps it apart from signed numbers
+    #[token("->")]
+    Arrow,
+    
     // Identifiers and literals
-    #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*", |lex| lex.slice().to_string())]
+    //
+    // Dotted member access (`User.Profile`) lexes as one identifier, so the
+    // qualified name is just a longer name: it never resolves to `User`. The
+    // longest match wins, which also lets a segment be a keyword (`Order.Is`).
+    // Logos doesn't backtrack out of the repetition, so `User.` matches with
+    // its dot; that is rejected here as there is no standalone `.` token.
+    #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*(\.[a-zA-Z_][a-zA-Z0-9_]*)*", |lex| {
+        let slice = lex.slice();
+        (!slice.ends_with('.')).then(|| slice.to_string())
+    })]
+    // Backticks escape reserved words: `Service` is an identifier, not a type
+    #[regex(r"`[a-zA-Z_][a-zA-Z0-9_]*`", |lex| {
+        let slice = lex.slice();
+        slice[1..slice.len() - 1].to_string()
+    })]
     Identifier(String),
     
     #[regex(r#""([^"\\]|\\.)*""#, |lex| {
         let s = lex.slice();
-        s[1..s.len()-1].to_string() // Remove quotes
+        unescape(&s[1..s.len()-1]) // Remove quotes
+    })]
+    #[regex(r#"'([^'\\]|\\.)*'"#, |lex| {
+        let s = lex.slice();
+        unescape(&s[1..s.len()-1])
+    })]
+    // Triple-quoted strings may span lines and keep their raw interior text
+    #[regex(r#""""([^"]|"[^"]|""[^"])*""""#, |lex| {
+        let s = lex.slice();
+        s[3..s.len()-3].to_string()
     })]
     QuotedString(String),
     
-    #[regex(r"[0-9]+", |lex| lex.slice().parse::<i64>().ok())]
+    // Numeric literals may carry a leading `+`/`-`. Disambiguation rule: a sign
+    // immediately followed by a digit always belongs to the literal, so `A-1`
+    // lexes as `A` and `-1`. A future minus operator must be written with
+    // surrounding whitespace (`A - 1`) to be recognized as such.
+    //
+    // Hex/binary digits are matched loosely so malformed literals like `0xGG`
+    // still lex as a single `Number(None)` instead of splitting apart.
+    //
+    // Underscores are allowed as digit separators (`1_000`). They are matched
+    // loosely too and validated in the callback, so `5_` or `1__0` become a
+    // lexer error rather than a number followed by an identifier.
+    #[regex(r"[+-]?[0-9][0-9_]*", |lex| parse_decimal(lex.slice()))]
+    #[regex(r"[+-]?0[xX][0-9a-zA-Z_]+", |lex| parse_radix(lex.slice(), 16))]
+    #[regex(r"[+-]?0[bB][0-9a-zA-Z_]+", |lex| parse_radix(lex.slice(), 2))]
     Number(Option<i64>),
     
-    #[regex(r"[0-9]+\.[0-9]+", |lex| lex.slice().parse::<f64>().ok())]
+    // An exponent makes any literal a float, including bare integers (`2e10`)
+    #[regex(r"[+-]?[0-9][0-9_]*\.[0-9][0-9_]*([eE][+-]?[0-9][0-9_]*)?", |lex| parse_float(lex.slice()))]
+    #[regex(r"[+-]?[0-9][0-9_]*[eE][+-]?[0-9][0-9_]*", |lex| parse_float(lex.slice()))]
     Float(Option<f64>),
     
-    #[regex(r"[0-9]+s|[0-9]+ms|[0-9]+m|[0-9]+h", |lex| lex.slice().to_string())]
+    #[regex(r"[+-]?[0-9]+(s|ms|m|h)", |lex| lex.slice().to_string())]
     Duration(String),
     
     // Type annotations (Case-insensitive)
@@ -191,21 +247,139 @@ pub enum Token {
     #[regex(r"//[^\r\n]*")]
     Comment,
     
-    #[regex(r"/\*([^*]|\*[^/])*\*/")]
+    #[token("/*", block_comment)]
     BlockComment,
 }
 
+/// Consume a block comment body up to and including the first `*/`
+///
+/// A plain scan rather than a regex keeps long runs of `*` linear. Without a
+/// closing `*/` the rest of the input is consumed and the token fails, which
+/// the lexer reports as an unterminated block comment.
+fn block_comment(lex: &mut logos::Lexer<Token>) -> bool {
+    match lex.remainder().find("*/") {
+        Some(end) => {
+            lex.bump(end + 2);
+            true
+        }
+        None => {
+            lex.bump(lex.remainder().len());
+            false
+        }
+    }
+}
+
+/// Decode the escape sequences of a string literal body (`\n`, `\t`, `\r`,
+/// `\\`, `\"`, `\'` and `\uXXXX`). Returns `None` on an invalid escape.
+fn unescape(raw: &str) -> Option<String> {
+    let mut result = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        
+        match chars.next()? {
+            'n' => result.push('\n'),
+            't' => result.push('\t'),
+            'r' => result.push('\r'),
+            '\\' => result.push('\\'),
+            '"' => result.push('"'),
+            '\'' => result.push('\''),
+            'u' => {
+                let hex: String = chars.by_ref().take(4).collect();
+                // `from_str_radix` alone would also take a sign, as in `\u+041`
+                if hex.chars().count() != 4 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return None;
+                }
+                let code = u32::from_str_radix(&hex, 16).ok()?;
+                result.push(char::from_u32(code)?);
+            }
+            _ => return None,
+        }
+    }
+    
+    Some(result)
+}
+
+/// Remove `_` digit separators, rejecting any underscore that is not placed
+/// between two digits (leading, trailing or doubled separators)
+fn strip_separators(literal: &str, is_digit: fn(&char) -> bool) -> Result<String, ()> {
+    let chars: Vec<char> = literal.chars().collect();
+    
+    for (i, ch) in chars.iter().enumerate() {
+        if *ch == '_' {
+            let prev = i.checked_sub(1).and_then(|j| chars.get(j));
+            let next = chars.get(i + 1);
+            if !(prev.is_some_and(is_digit) && next.is_some_and(is_digit)) {
+                return Err(());
+            }
+        }
+    }
+    
+    Ok(literal.replace('_', ""))
+}
+
+/// Parse a decimal integer literal with an optional sign
+fn parse_decimal(slice: &str) -> Result<Option<i64>, ()> {
+    Ok(strip_separators(slice, char::is_ascii_digit)?.parse::<i64>().ok())
+}
+
+/// Parse a prefixed (`0x`/`0b`) integer literal with an optional sign
+fn parse_radix(slice: &str, radix: u32) -> Result<Option<i64>, ()> {
+    let (sign, rest) = match slice.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", slice.strip_prefix('+').unwrap_or(slice)),
+    };
+    let digits = strip_separators(&rest[2..], char::is_ascii_alphanumeric)?;
+    Ok(i64::from_str_radix(&format!("{}{}", sign, digits), radix).ok())
+}
+
+/// Parse a float literal; non-finite values (e.g. `1e999`) become `None`,
+/// since serde_json cannot represent NaN or infinity
+fn parse_float(slice: &str) -> Result<Option<f64>, ()> {
+    let literal = strip_separators(slice, char::is_ascii_digit)?;
+    Ok(literal.parse::<f64>().ok().filter(|f| f.is_finite()))
+}
+
+/// Canonical spelling of every keyword, including type and boolean keywords
+pub fn keywords() -> &'static [&'static str] {
+    &[
+        "VAR", "CONTEXT", "GOES", "TO", "CREATED", "BY", "IF", "THEN", "ELSE",
+        "WHEN", "CALLS", "RECEIVES", "RETURNS", "DOES", "USES", "IS", "AFTER",
+        "BEFORE", "PARALLEL", "AND", "OR", "RETRY", "TIMEOUT", "ASYNC", "BATCH",
+        "LOOP", "WHILE", "BREAK", "CONTINUE", "START", "LABELED", "INCLUDE",
+        "Entity", "Service", "Endpoint", "Object", "String", "Number", "Boolean",
+        "true", "false",
+    ]
+}
+
+/// Whether `name` lexes as an identifier without backtick escaping
+fn is_plain_identifier(name: &str) -> bool {
+    let mut lexer = Token::lexer(name);
+    matches!(lexer.next(), Some(Ok(Token::Identifier(ref lexed))) if lexed == name)
+        && lexer.next().is_none()
+}
+
+/// Canonical spelling of every type annotation keyword
+pub fn types() -> &'static [&'static str] {
+    &["Entity", "Service", "Endpoint", "Object", "String", "Number", "Boolean"]
+}
+
 impl Token {
     /// Check if this token is a keyword
     pub fn is_keyword(&self) -> bool {
         matches!(self, 
             Token::Var | Token::Context | Token::Goes | Token::To |
             Token::Created | Token::By | Token::If | Token::Then |
-            Token::When | Token::Calls | Token::Receives | Token::Returns |
+            Token::Else | Token::When | Token::Calls | Token::Receives | Token::Returns |
             Token::Does | Token::Uses | Token::Is | Token::After |
             Token::Before | Token::Parallel | Token::And | Token::Or |
             Token::Retry | Token::Timeout | Token::Async | Token::Batch |
             Token::Loop | Token::While | Token::Break | Token::Continue |
+            Token::Start | Token::Labeled | Token::Include |
             Token::Entity | Token::Service | Token::Endpoint | Token::Object |
             Token::StringType | Token::NumberType | Token::BooleanType |
             Token::True | Token::False
@@ -235,10 +409,12 @@ impl Token {
             Token::Duration(_) | Token::True | Token::False
         )
     }
-    
+}
+
+impl fmt::Display for Token {
     /// Get the string representation of the token (for error messages)
-    pub fn to_string(&self) -> String {
-        match self {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
             Token::Var => "VAR".to_string(),
             Token::Context => "CONTEXT".to_string(),
             Token::Goes => "GOES".to_string(),
@@ -247,6 +423,7 @@ impl Token {
             Token::By => "BY".to_string(),
             Token::If => "IF".to_string(),
             Token::Then => "THEN".to_string(),
+            Token::Else => "ELSE".to_string(),
             Token::When => "WHEN".to_string(),
             Token::Calls => "CALLS".to_string(),
             Token::Receives => "RECEIVES".to_string(),
@@ -267,6 +444,9 @@ impl Token {
             Token::While => "WHILE".to_string(),
             Token::Break => "BREAK".to_string(),
             Token::Continue => "CONTINUE".to_string(),
+            Token::Start => "START".to_string(),
+            Token::Labeled => "LABELED".to_string(),
+            Token::Include => "INCLUDE".to_string(),
             Token::LeftBrace => "{".to_string(),
             Token::RightBrace => "}".to_string(),
             Token::Colon => ":".to_string(),
@@ -281,7 +461,9 @@ impl Token {
             Token::GreaterThan => ">".to_string(),
             Token::LessEqual => "<=".to_string(),
             Token::GreaterEqual => ">=".to_string(),
-            Token::Identifier(name) => name.clone(),
+            Token::Arrow => "->".to_string(),
+            Token::Identifier(name) if is_plain_identifier(name) => name.clone(),
+            Token::Identifier(name) => format!("`{}`", name),
             Token::QuotedString(s) => format!("\"{}\"", s),
             Token::Number(Some(n)) => n.to_string(),
             Token::Number(None) => "invalid number".to_string(),
@@ -301,6 +483,7 @@ impl Token {
             Token::Newline => "newline".to_string(),
             Token::Comment => "comment".to_string(),
             Token::BlockComment => "block comment".to_string(),
-        }
+        };
+        f.write_str(&text)
     }
 }
@@ -21,7 +21,7 @@
 //!     
 //!     // Tokenize
 //!     let mut lexer = Lexer::new(&source.content);
-//!     let tokens = lexer.tokenize_filtered()?;
+//!     let tokens = lexer.tokenize_with_trivia()?;
 //!     
 //!     // Parse
 //!     let mut parser = Parser::new(tokens);
@@ -32,44 +32,200 @@
 //! ```
 
 pub mod reader;
+pub mod compiler;
 pub mod lexer;
 pub mod parser;
 pub mod analyzer;
 pub mod generator;
 pub mod error;
 pub mod utils;
+#[cfg(feature = "lsp")]
+pub mod lsp;
+#[cfg(feature = "watch")]
+pub mod watch;
 
 // Re-export commonly used types
+pub use compiler::Compiler;
 pub use error::{NaviLangError, Result};
 pub use reader::{SourceFile, read_source};
 
 /// Version information
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-/// Compile a NaviLang source file through the complete pipeline
+/// Read a NaviLang source file, inlining its `INCLUDE`s, and run it through
+/// the lexer and parser; see [`compile_source`]
 pub fn compile_file<P: AsRef<std::path::Path>>(
     path: P,
 ) -> Result<CompilationResult> {
-    // TODO: Implement full compilation pipeline
+    compile_source(load_file(path.as_ref())?)
+}
+
+/// Like [`compile_file`], stopping after `max_errors` syntax errors; see
+/// [`compile_source_with_max_errors`]
+pub fn compile_file_with_max_errors<P: AsRef<std::path::Path>>(
+    path: P,
+    max_errors: Option<usize>,
+) -> Result<CompilationResult> {
+    compile_source_with_max_errors(load_file(path.as_ref())?, max_errors)
+}
+
+/// Like [`compile_file`], keeping whatever parses; see [`compile_source_partial`]
+pub fn compile_file_partial<P: AsRef<std::path::Path>>(
+    path: P,
+) -> Result<PartialCompilation> {
+    compile_source_partial(load_file(path.as_ref())?)
+}
+
+/// Compile every `.navi` file under `dir`, descending into subdirectories
+///
+/// Each file is compiled on its own, so a broken file does not stop the
+/// others; results are sorted by path. A subdirectory that cannot be read is
+/// reported as a failed entry for that directory. Symbolic links to
+/// directories are not followed.
+pub fn compile_dir(dir: &std::path::Path) -> Vec<(std::path::PathBuf, Result<CompilationResult>)> {
+    fn navi_files(dir: &std::path::Path, found: &mut Vec<(std::path::PathBuf, Option<std::io::Error>)>) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(error) => return found.push((dir.to_path_buf(), Some(error))),
+        };
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(error) => return found.push((dir.to_path_buf(), Some(error))),
+            };
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() => navi_files(&path, found),
+                Ok(_) if path.extension().is_some_and(|ext| ext == "navi") => found.push((path, None)),
+                Ok(_) => {}
+                Err(error) => found.push((path, Some(error))),
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    navi_files(dir, &mut files);
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    files.into_iter()
+        .map(|(path, error)| {
+            let result = match error {
+                Some(error) => Err(error.into()),
+                None => compile_file(&path),
+            };
+            (path, result)
+        })
+        .collect()
+}
+
+/// Read a source file and inline its `INCLUDE`s, relative to its directory
+pub(crate) fn load_file(path: &std::path::Path) -> Result<SourceFile> {
     let source = read_source(path)?;
+    let base_dir = path.parent().unwrap_or(std::path::Path::new("."));
     
+    Ok(reader::resolve_includes(source, base_dir)?)
+}
+
+/// Compile in-memory source through the same pipeline as `compile_file`
+///
+/// `name` is used as the source path in diagnostics. `INCLUDE` directives are
+/// not resolved, since there is no directory to resolve them against.
+///
+/// ```rust
+/// let result = navilang::compile_string(
+///     r#"CONTEXT "User Authentication" {
+///         VAR User:Entity
+///         User GOES TO Dashboard
+///     }"#,
+///     "auth.navi",
+/// )?;
+///
+/// assert_eq!(result.ast.contexts[0].name, "User Authentication");
+/// assert_eq!(result.source.path, "auth.navi");
+/// # Ok::<(), navilang::NaviLangError>(())
+/// ```
+pub fn compile_string(content: &str, name: &str) -> Result<CompilationResult> {
+    compile_source(SourceFile::from_string(content.to_string(), name.to_string()))
+}
+
+/// Compile already-read source through the lexer and parser
+///
+/// Only syntax is checked. Semantic analysis is a separate step, run with
+/// [`analyzer::SemanticAnalyzer`] on `result.ast`, so that formatting,
+/// parsing and generation still work on files with undeclared names and
+/// other semantic errors.
+pub fn compile_source(source: SourceFile) -> Result<CompilationResult> {
+    compile_source_with_max_errors(source, None)
+}
+
+/// Like [`compile_source`], with the parser stopping at the first syntax
+/// error past `max_errors` instead of reporting every one; `None` keeps them
+/// all
+pub fn compile_source_with_max_errors(source: SourceFile, max_errors: Option<usize>) -> Result<CompilationResult> {
     // Stage 1: Lexical Analysis
     let mut lexer = lexer::Lexer::new(&source.content);
-    let tokens = lexer.tokenize_filtered()?;
+    let tokens = lexer.tokenize_with_trivia()?;
     
     // Stage 2: Syntax Analysis
-    let mut parser = parser::Parser::new(tokens);
+    let mut parser = parser::Parser::new(tokens.clone()).with_source(&source.content);
+    if let Some(max_errors) = max_errors {
+        parser = parser.with_max_errors(max_errors);
+    }
     let ast = parser.parse()?;
     
     Ok(CompilationResult {
         ast,
         source,
+        tokens,
     })
 }
 
+/// Compile already-read source, keeping whatever parses
+///
+/// Unlike [`compile_source`], syntax errors don't fail the compilation: the
+/// result holds the contexts the parser recovered and the errors are returned
+/// alongside them. Lexical errors still fail, since recovery starts from a
+/// complete token stream.
+pub fn compile_source_partial(source: SourceFile) -> Result<PartialCompilation> {
+    let tokens = lexer::Lexer::new(&source.content).tokenize_with_trivia()?;
+    
+    let mut parser = parser::Parser::new(tokens.clone()).with_source(&source.content);
+    let (ast, errors) = parser.parse_partial();
+    
+    Ok(PartialCompilation {
+        result: CompilationResult { ast, source, tokens },
+        errors,
+        failed_contexts: parser.failed_contexts(),
+    })
+}
+
+/// Outcome of [`compile_source_partial`]
+#[derive(Debug)]
+pub struct PartialCompilation {
+    pub result: CompilationResult,
+    /// Syntax errors, in source order
+    pub errors: Vec<NaviLangError>,
+    /// Top-level contexts that were left out or kept with errors inside them
+    pub failed_contexts: usize,
+}
+
 /// Result of compilation process
 #[derive(Debug)]
 pub struct CompilationResult {
     pub ast: parser::ast::Program,
     pub source: SourceFile,
+    /// Significant tokens fed to the parser, with comments attached as trivia
+    pub tokens: Vec<lexer::TokenWithSpan>,
+}
+
+impl CompilationResult {
+    /// Top-level contexts of the parsed program
+    pub fn contexts(&self) -> &[parser::ast::Context] {
+        &self.ast.contexts
+    }
+    
+    /// Tokens produced by the lexer stage
+    pub fn tokens(&self) -> &[lexer::TokenWithSpan] {
+        &self.tokens
+    }
 }
@@ -0,0 +1,252 @@
+// Language server - compiler diagnostics for editors, over stdio
+use crate::analyzer::SemanticAnalyzer;
+use crate::error::{Diagnostic, NaviLangError, Severity};
+use crate::reader::{self, SourceFile};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Response};
+use lsp_types::notification::{DidChangeTextDocument, DidOpenTextDocument, Notification as _, PublishDiagnostics};
+use lsp_types::{
+    DiagnosticSeverity, DidChangeTextDocumentParams, DidOpenTextDocumentParams, NumberOrString,
+    PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use miette::Diagnostic as _;
+use std::path::Path;
+
+/// Serve the language server protocol on stdin and stdout until the client
+/// shuts it down
+///
+/// Documents are synced in full. Every `didOpen` and `didChange` recompiles
+/// the document and publishes its errors and warnings. Notifications whose
+/// parameters don't parse are logged to stderr and skipped.
+pub fn run() -> anyhow::Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        ..ServerCapabilities::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    break;
+                }
+                let response = Response::new_err(
+                    request.id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("Unsupported request: {}", request.method),
+                );
+                connection.sender.send(response.into())?;
+            }
+            Message::Notification(notification) => match notification.method.as_str() {
+                DidOpenTextDocument::METHOD => {
+                    let params: DidOpenTextDocumentParams = match serde_json::from_value(notification.params) {
+                        Ok(params) => params,
+                        Err(error) => {
+                            eprintln!("Ignoring malformed {} notification: {}", notification.method, error);
+                            continue;
+                        }
+                    };
+                    publish(&connection, params.text_document.uri, &params.text_document.text)?;
+                }
+                DidChangeTextDocument::METHOD => {
+                    let params: DidChangeTextDocumentParams = match serde_json::from_value(notification.params) {
+                        Ok(params) => params,
+                        Err(error) => {
+                            eprintln!("Ignoring malformed {} notification: {}", notification.method, error);
+                            continue;
+                        }
+                    };
+                    // With full sync the last change holds the whole document
+                    if let Some(change) = params.content_changes.into_iter().last() {
+                        publish(&connection, params.text_document.uri, &change.text)?;
+                    }
+                }
+                _ => {}
+            },
+            Message::Response(_) => {}
+        }
+    }
+
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+fn publish(connection: &Connection, uri: Url, text: &str) -> anyhow::Result<()> {
+    let params = PublishDiagnosticsParams { diagnostics: diagnostics(&uri, text), uri, version: None };
+    let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+    connection.sender.send(notification.into())?;
+    Ok(())
+}
+
+/// Lex, parse and analyze `text`, the document at `uri`, returning every
+/// problem as LSP diagnostics
+///
+/// When `uri` is a file path, `INCLUDE` directives are resolved relative to
+/// it. Problems inside an included file are left to that file's own
+/// diagnostics. An include that can't be resolved is reported at the start
+/// of the document, which is then checked on its own.
+pub fn diagnostics(uri: &Url, text: &str) -> Vec<lsp_types::Diagnostic> {
+    let document = SourceFile::from_string(text.to_string(), uri.to_string());
+    let mut diagnostics = Vec::new();
+    let mut source = document.clone();
+    if let Ok(path) = uri.to_file_path() {
+        let file = SourceFile::from_string(text.to_string(), path.to_string_lossy().to_string());
+        match reader::resolve_includes(file, path.parent().unwrap_or(Path::new("."))) {
+            Ok(resolved) => source = resolved,
+            Err(error) => diagnostics.extend(to_lsp_diagnostics(&error.into(), Severity::Error, &document)),
+        }
+    }
+
+    let problems = match crate::compile_source(source.clone()) {
+        Ok(result) => match SemanticAnalyzer::new().analyze(&result.ast, &result.source.content) {
+            Ok(warnings) => warnings,
+            Err(error) => vec![Diagnostic::error(error)],
+        },
+        Err(error) => vec![Diagnostic::error(error)],
+    };
+
+    // Offsets into the combined source, mapped back to the document
+    let position = |offset: usize| {
+        if source.includes.is_empty() {
+            return Some(lsp_position(&document, offset));
+        }
+        if source.origin(offset) != source.path {
+            return None;
+        }
+        let location = source.location(offset);
+        document.offset_at(location.line, location.column).map(|offset| lsp_position(&document, offset))
+    };
+    diagnostics.extend(problems.iter().flat_map(|problem| lsp_diagnostics(&problem.error, problem.severity, &position)));
+    diagnostics
+}
+
+/// Convert an error to LSP diagnostics, one per error inside `MultipleErrors`
+///
+/// The range is the error's primary span, in the 0-based lines and UTF-16
+/// characters LSP uses. Errors without a span point at the start of the file.
+pub fn to_lsp_diagnostics(error: &NaviLangError, severity: Severity, source: &SourceFile) -> Vec<lsp_types::Diagnostic> {
+    lsp_diagnostics(error, severity, &|offset| Some(lsp_position(source, offset)))
+}
+
+/// Like `to_lsp_diagnostics`, with `position` mapping a byte offset to its
+/// LSP position; errors whose span it maps to `None` are left out
+fn lsp_diagnostics(
+    error: &NaviLangError,
+    severity: Severity,
+    position: &dyn Fn(usize) -> Option<lsp_types::Position>,
+) -> Vec<lsp_types::Diagnostic> {
+    if let NaviLangError::MultipleErrors { errors } = error {
+        return errors.iter().flat_map(|error| lsp_diagnostics(error, severity, position)).collect();
+    }
+
+    let (offset, len) = error.labels()
+        .and_then(|mut labels| labels.next())
+        .map_or((0, 0), |label| (label.offset(), label.len()));
+    let Some(start) = position(offset) else {
+        return Vec::new();
+    };
+    let end = position(offset + len).unwrap_or(start);
+    let mut message = crate::error::message_with_severity(severity, error);
+    if let Some(help) = error.help() {
+        message.push_str(&format!("\nhelp: {}", help));
+    }
+
+    vec![lsp_types::Diagnostic {
+        range: Range::new(start, end),
+        severity: Some(match severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+        }),
+        code: error.code().map(|code| NumberOrString::String(code.to_string())),
+        source: Some("navilang".to_string()),
+        message,
+        ..lsp_types::Diagnostic::default()
+    }]
+}
+
+/// LSP position of a byte offset; offsets past the end clamp to it
+fn lsp_position(source: &SourceFile, offset: usize) -> lsp_types::Position {
+    let offset = offset.min(source.content.len());
+    let Some(position) = source.position_at(offset) else {
+        return lsp_types::Position::default();
+    };
+    let line_start = source.offset_at(position.line, 1).unwrap_or(offset);
+    let character = source.content[line_start..offset].encode_utf16().count();
+    lsp_types::Position::new((position.line - 1) as u32, character as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{Position, Span};
+
+    #[test]
+    fn test_errors_map_to_lsp_ranges() {
+        let text = "CONTEXT \"Ünïcode\" {\n    VAR User\n    User GOES TO Dashbord\n}";
+        let diagnostics = diagnostics(&Url::parse("untitled:test.navi").unwrap(), text);
+
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        // Undeclared names only fail analysis in strict mode
+        assert_eq!(diagnostic.message, "Warning: Unknown identifier: Dashbord");
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostic.code, Some(NumberOrString::String("semantic::unknown_identifier".to_string())));
+        assert_eq!(diagnostic.range.start.line, 2);
+        // Unknown identifiers are reported on the identifier itself
+        assert_eq!(diagnostic.range.start.character, "    User GOES TO ".len() as u32);
+        assert_eq!(diagnostic.range.end.character, "    User GOES TO Dashbord".len() as u32);
+    }
+
+    #[test]
+    fn test_warnings_are_not_called_errors() {
+        let text = "CONTEXT Flow {\n    VAR Begin\n    VAR Done\n    VAR Orphan\n    Begin -> Done\n    Orphan -> Done\n}";
+        let diagnostics = diagnostics(&Url::parse("untitled:test.navi").unwrap(), text);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostics[0].message, "Warning: State 'Orphan' is unreachable from 'Begin'");
+    }
+
+    #[test]
+    fn test_multiple_errors_and_warnings() {
+        let source = SourceFile::from_string("CONTEXT \"😀\" VAR".to_string(), "test.navi".to_string());
+        let var = source.content.find("VAR").unwrap();
+        let span = Span::new(Position::new(1, 13, var), Position::new(1, 16, var + 3));
+        let error = NaviLangError::MultipleErrors {
+            errors: vec![
+                NaviLangError::syntax_error("first".to_string(), source.content.clone(), span),
+                NaviLangError::IoError(std::io::Error::other("no span")),
+            ],
+        };
+
+        let diagnostics = to_lsp_diagnostics(&error, Severity::Warning, &source);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|d| d.severity == Some(DiagnosticSeverity::WARNING)));
+        // The emoji is four bytes but two UTF-16 code units
+        assert_eq!(diagnostics[0].range, Range::new(lsp_types::Position::new(0, 13), lsp_types::Position::new(0, 16)));
+        assert_eq!(diagnostics[1].range, Range::default());
+    }
+
+    #[test]
+    fn test_includes_resolve_relative_to_the_document() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("shared.navi"), "CONTEXT Shared {\n    VAR Admin\n    Admin -> Missing\n}").unwrap();
+        let uri = Url::from_file_path(dir.path().join("main.navi")).unwrap();
+        let text = "INCLUDE \"shared.navi\"\nCONTEXT Main {\n    VAR User\n    User -> Nowhere\n}";
+
+        let diagnostics = diagnostics(&uri, text);
+        // The included file's own warning stays with it, and positions skip its text
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert_eq!(diagnostics[0].message, "Warning: Unknown identifier: Nowhere");
+        assert_eq!(diagnostics[0].range.start, lsp_types::Position::new(3, "    User -> ".len() as u32));
+
+        // A missing include is reported, and the rest of the document still checked
+        let diagnostics = super::diagnostics(&uri, &text.replace("shared.navi", "gone.navi"));
+        assert_eq!(diagnostics[0].range, Range::default());
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+        assert!(diagnostics.iter().any(|d| d.message == "Warning: Unknown identifier: Nowhere"));
+    }
+}
@@ -1,5 +1,13 @@
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use clap::{Parser, Subcommand, ValueEnum};
+use navilang::analyzer::{AnalyzerConfig, SemanticAnalyzer, metrics};
+use navilang::parser::ast;
+use navilang::error::{DEFAULT_MAX_ERRORS, Diagnostic, ErrorCollector};
+use navilang::lexer::Lexer;
+use navilang::utils::color::{self, Style};
+use navilang::utils::duration::Duration;
+use navilang::{CompilationResult, PartialCompilation, SourceFile, generator, reader};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
 #[derive(Parser)]
 #[command(name = "navilang")]
@@ -7,56 +15,557 @@ use std::path::PathBuf;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// How to report errors
+    #[arg(long, global = true, value_enum, default_value_t = ErrorFormat::Human)]
+    error_format: ErrorFormat,
+    /// Stop reporting after this many errors per file (0 for no limit)
+    #[arg(long, global = true, default_value_t = DEFAULT_MAX_ERRORS)]
+    max_errors: usize,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ErrorFormat {
+    /// Rendered diagnostics with source context
+    Human,
+    /// One JSON document per failed run
+    Json,
+    /// A SARIF 2.1.0 log, for code scanning tools
+    Sarif,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Parse NaviLang file and output AST
     Parse {
+        /// Input file, or `-` to read from stdin
         #[arg(short, long)]
         file: PathBuf,
         #[arg(short, long)]
         output: Option<PathBuf>,
+        /// Emit single-line JSON instead of pretty-printed output
+        #[arg(long)]
+        compact: bool,
+        /// Add the file, line and column of every span
+        #[arg(long)]
+        with_locations: bool,
     },
     /// Generate diagrams from NaviLang
     Generate {
+        /// Input file, or `-` to read from stdin
         #[arg(short, long)]
         file: PathBuf,
-        #[arg(short, long)]
+        #[arg(long)]
         format: String,
         #[arg(short, long)]
         output: Option<PathBuf>,
+        /// Emit single-line JSON when `--format json`, `openapi` or `jsonschema` is used
+        #[arg(long)]
+        compact: bool,
+        /// Add the file, line and column of every span when `--format json` is used
+        #[arg(long)]
+        with_locations: bool,
+    },
+    /// Format NaviLang source canonically
+    Fmt {
+        /// Input file, or `-` to read from stdin
+        #[arg(short, long)]
+        file: PathBuf,
+        /// Rewrite the file in place instead of printing the result
+        #[arg(short, long)]
+        write: bool,
     },
-    /// Validate NaviLang syntax
+    /// Validate NaviLang syntax and semantics
     Check {
+        /// Input file, or `-` to read from stdin
         #[arg(short, long)]
         file: PathBuf,
+        /// Print nothing when the file is valid
+        #[arg(short, long)]
+        quiet: bool,
+        /// Treat warnings as errors
+        #[arg(long)]
+        deny_warnings: bool,
+        /// Check every `.navi` file under the directory given as `--file`
+        #[arg(short, long)]
+        recursive: bool,
+        /// Fail on undeclared identifiers instead of warning, and require each
+        /// identifier to be declared before it is used
+        #[arg(long)]
+        strict: bool,
+        /// Warn about contexts whose retries and timeouts may add up to more
+        /// than this, e.g. `2m`
+        #[arg(long)]
+        timeout_budget: Option<Duration>,
+    },
+    /// Report size and complexity metrics of a NaviLang file
+    Metrics {
+        /// Input file, or `-` to read from stdin
+        #[arg(short, long)]
+        file: PathBuf,
+        /// Print the metrics as a JSON object
+        #[arg(long)]
+        json: bool,
     },
+    /// Dump the token stream of a NaviLang file
+    Tokens {
+        /// Input file, or `-` to read from stdin
+        #[arg(short, long)]
+        file: PathBuf,
+        /// Print the tokens as a JSON array
+        #[arg(long)]
+        json: bool,
+        /// Keep whitespace and comment tokens
+        #[arg(long)]
+        raw: bool,
+        /// Highlight keywords, types, literals and identifiers (ignored when NO_COLOR is set)
+        #[arg(long)]
+        color: bool,
+    },
+    /// Summarize the contexts, variables and flows that differ between two
+    /// versions of a file
+    Diff {
+        /// The earlier version, or `-` to read it from stdin
+        #[arg(long)]
+        old: PathBuf,
+        /// The later version, or `-` to read it from stdin
+        #[arg(long)]
+        new: PathBuf,
+    },
+    /// Run a language server over stdio, publishing diagnostics as documents change
+    #[cfg(feature = "lsp")]
+    Lsp,
+    /// Check, or generate, again whenever the file or one of its includes changes
+    #[cfg(feature = "watch")]
+    Watch {
+        #[arg(short, long)]
+        file: PathBuf,
+        /// Print this `generate` format on each change instead of checking
+        #[arg(long)]
+        format: Option<String>,
+    },
+}
+
+impl Commands {
+    /// The input file the command reads, if it reads one
+    fn file(&self) -> Option<&Path> {
+        let file = match self {
+            Commands::Parse { file, .. }
+            | Commands::Generate { file, .. }
+            | Commands::Fmt { file, .. }
+            | Commands::Check { file, .. }
+            | Commands::Metrics { file, .. }
+            | Commands::Tokens { file, .. } => file,
+            Commands::Diff { new, .. } => new,
+            #[cfg(feature = "watch")]
+            Commands::Watch { file, .. } => file,
+            #[cfg(feature = "lsp")]
+            Commands::Lsp => return None,
+        };
+        Some(file)
+    }
+}
+
+/// Path reported in SARIF logs, with `-` shown as stdin
+fn artifact_path(file: &Path) -> String {
+    if file == Path::new("-") {
+        reader::STDIN_PATH.to_string()
+    } else {
+        file.display().to_string()
+    }
 }
 
-fn main() -> anyhow::Result<()> {
+/// Read the file named on the command line, reading stdin for `-`
+fn read(file: &Path) -> navilang::Result<SourceFile> {
+    if file == Path::new("-") {
+        Ok(reader::read_stdin()?)
+    } else {
+        Ok(reader::read_source(file)?)
+    }
+}
+
+/// Compile the file named on the command line, reading stdin for `-`
+///
+/// Parsing stops after `max_errors` syntax errors, or never for 0.
+fn compile(file: &Path, max_errors: usize) -> navilang::Result<CompilationResult> {
+    if file == Path::new("-") {
+        let source = reader::resolve_includes(reader::read_stdin()?, Path::new("."))?;
+        navilang::compile_source_with_max_errors(source, error_limit(max_errors))
+    } else {
+        navilang::compile_file_with_max_errors(file, error_limit(max_errors))
+    }
+}
+
+/// The `--max-errors` value as a limit, where 0 means there is none
+fn error_limit(max_errors: usize) -> Option<usize> {
+    (max_errors > 0).then_some(max_errors)
+}
+
+/// Like `compile`, keeping the contexts that parse when others don't
+fn compile_partial(file: &Path) -> navilang::Result<PartialCompilation> {
+    if file == Path::new("-") {
+        let source = reader::read_stdin()?;
+        navilang::compile_source_partial(reader::resolve_includes(source, Path::new("."))?)
+    } else {
+        navilang::compile_file_partial(file)
+    }
+}
+
+fn main() -> ExitCode {
     let cli = Cli::parse();
+    let error_format = cli.error_format;
+    let max_errors = cli.max_errors;
+    let path = cli.command.file().map(artifact_path).unwrap_or_default();
+    
+    match run(cli) {
+        Ok(code) => code,
+        Err(error) => {
+            report_error(error, error_format, max_errors, &path);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn report_error(error: navilang::NaviLangError, error_format: ErrorFormat, max_errors: usize, path: &str) {
+    let error = cap_errors(error, max_errors);
+    match error_format {
+        ErrorFormat::Human => eprintln!("{:?}", miette::Report::new(error)),
+        ErrorFormat::Json => eprintln!("{}", navilang::error::to_json(&error)),
+        ErrorFormat::Sarif => eprintln!("{}", navilang::error::to_sarif(&[error], path)),
+    }
+}
+
+/// Keep the first `max_errors` errors of `error`, followed by a note that the
+/// rest were dropped; 0 keeps them all
+///
+/// The parser and analyzer already stop at the limit; this caps errors from
+/// the stages that don't, such as a `check --recursive` file.
+fn cap_errors(error: navilang::NaviLangError, max_errors: usize) -> navilang::NaviLangError {
+    if max_errors == 0 || error_count(&error) <= max_errors {
+        return error;
+    }
+    let mut errors = ErrorCollector::with_limit(max_errors);
+    errors.add_result::<()>(Err(error));
+    // Over the limit, so at least one error is kept along with the note
+    navilang::NaviLangError::MultipleErrors { errors: errors.into_errors() }
+}
+
+fn run(cli: Cli) -> navilang::Result<ExitCode> {
+    let error_format = cli.error_format;
+    let max_errors = cli.max_errors;
     
     match cli.command {
-        Commands::Parse { file, output } => {
-            // TODO: Implement parsing
-            println!("Parsing file: {:?}", file);
-            if let Some(output) = output {
-                println!("Output to: {:?}", output);
+        Commands::Parse { file, output, compact, with_locations } => {
+            let result = compile(&file, max_errors)?;
+            let json = if with_locations {
+                generator::json::to_json_with_locations(&result.ast, &result.source, !compact)?
+            } else {
+                generator::json::to_json(&result.ast, !compact)?
+            };
+            
+            match output {
+                Some(output) => std::fs::write(&output, json)?,
+                None => println!("{}", json),
+            }
+        }
+        Commands::Generate { file, format, output, compact, with_locations } => {
+            let PartialCompilation { result, errors, failed_contexts } = compile_partial(&file)?;
+            let mut parse_errors = ErrorCollector::new();
+            for error in errors {
+                parse_errors.add_error(error);
             }
+            
+            let generated = if with_locations && format == "json" {
+                generator::json::to_json_with_locations(&result.ast, &result.source, !compact)?
+            } else {
+                generate(&result, &format, compact)?
+            };
+            // Formats without comments have nowhere to say the output is partial
+            let generated = if parse_errors.has_errors() {
+                let plural = if failed_contexts == 1 { "context" } else { "contexts" };
+                let note = format!("{} {} failed to parse", failed_contexts, plural);
+                match with_banner(&format, generated, &note) {
+                    Some(generated) => generated,
+                    None => return parse_errors.into_result(ExitCode::SUCCESS),
+                }
+            } else {
+                generated
+            };
+            
+            match output {
+                Some(output) => std::fs::write(&output, generated)?,
+                None => print!("{}", generated),
+            }
+            // The partial output is written, but the run still fails
+            parse_errors.into_result(())?;
         }
-        Commands::Generate { file, format, output } => {
-            // TODO: Implement generation
-            println!("Generating {} from {:?}", format, file);
-            if let Some(output) = output {
-                println!("Output to: {:?}", output);
+        Commands::Fmt { file, write } => {
+            if write && file == Path::new("-") {
+                return Err(anyhow::anyhow!("Cannot use --write when reading from stdin").into());
+            }
+            // Includes are kept as directives rather than inlined, so each file is formatted on its own
+            let result = navilang::compile_source(read(&file)?)?;
+            let formatted = generator::format::to_source(&result.ast);
+            
+            if write {
+                std::fs::write(&file, formatted)?;
+            } else {
+                print!("{}", formatted);
             }
         }
-        Commands::Check { file } => {
-            // TODO: Implement validation
-            println!("Checking file: {:?}", file);
+        Commands::Check { file, quiet, deny_warnings, recursive: true, strict, timeout_budget } => {
+            let config = AnalyzerConfig { strict, timeout_budget, max_errors: error_limit(max_errors) };
+            let results = navilang::compile_dir(&file);
+            let files = results.len();
+            let mut errors = 0;
+            let mut failed = 0;
+            let mut reports = Vec::new();
+            
+            for (path, result) in results {
+                let diagnostics = match result.and_then(|result| check(&result, &config, deny_warnings)) {
+                    Ok(warnings) => warnings,
+                    Err(error) => {
+                        errors += error_count(&error);
+                        failed += 1;
+                        vec![Diagnostic::error(cap_errors(error, max_errors))]
+                    }
+                };
+                reports.push((artifact_path(&path), diagnostics));
+            }
+            report_files(reports, error_format);
+            
+            if failed > 0 {
+                // The summary would be a second document after the JSON or SARIF one
+                if !matches!(error_format, ErrorFormat::Human) {
+                    return Ok(ExitCode::FAILURE);
+                }
+                let summary = format!("{} in {} of {}", plural(errors, "error"), failed, plural(files, "file"));
+                return Err(anyhow::anyhow!(summary).into());
+            }
+            if !quiet {
+                println!("OK: {}", plural(files, "file"));
+            }
+        }
+        Commands::Check { file, quiet, deny_warnings, recursive: false, strict, timeout_budget } => {
+            let result = compile(&file, max_errors)?;
+            let config = AnalyzerConfig { strict, timeout_budget, max_errors: error_limit(max_errors) };
+            let warnings = check(&result, &config, deny_warnings)?;
+            report_warnings(warnings, error_format, &file);
+            
+            if !quiet {
+                let metrics = metrics::compute(&result.ast);
+                println!("OK: {} contexts, {} statements", metrics.contexts, metrics.statements);
+            }
+        }
+        Commands::Metrics { file, json } => {
+            let result = compile(&file, max_errors)?;
+            let metrics = metrics::compute(&result.ast);
+            
+            if json {
+                println!("{}", generator::json::to_json(&metrics, true)?);
+            } else {
+                let rows = [
+                    ("Contexts", metrics.contexts),
+                    ("Statements", metrics.statements),
+                    ("Variables", metrics.variables),
+                    ("Flows", metrics.flows),
+                    ("Interactions", metrics.interactions),
+                    ("Conditionals", metrics.conditionals),
+                    ("Loops", metrics.loops),
+                    ("Max nesting depth", metrics.max_nesting_depth),
+                    ("Complexity", metrics.complexity),
+                ];
+                for (name, value) in rows {
+                    println!("{:<18} {:>6}", name, value);
+                }
+            }
+        }
+        Commands::Tokens { file, json, raw, color } => {
+            let source = read(&file)?;
+            let mut lexer = Lexer::new(&source.content);
+            let tokens = if raw { lexer.tokenize()? } else { lexer.tokenize_filtered()? };
+            
+            if json {
+                let kinds: Vec<_> = tokens.iter().map(|t| &t.token).collect();
+                println!("{}", generator::json::to_json(&kinds, true)?);
+            } else {
+                let color = color::enabled(color);
+                for token in &tokens {
+                    let slice = &source.content[token.span.start.offset..token.span.end.offset];
+                    let position = format!("{}:{}", token.span.start.line, token.span.start.column);
+                    // Pad before painting so escape sequences don't skew the columns
+                    let kind = color::paint(&format!("{:<24}", format!("{:?}", token.token)), Style::of(&token.token), color);
+                    println!("{:<8}  {}  {:?}", position, kind, slice);
+                }
+            }
+        }
+        Commands::Diff { old, new } => {
+            if old == Path::new("-") && new == Path::new("-") {
+                return Err(anyhow::anyhow!("Only one of --old and --new can read from stdin").into());
+            }
+            let changes = ast::diff(&compile(&old, max_errors)?.ast, &compile(&new, max_errors)?.ast);
+            
+            if changes.is_empty() {
+                println!("No changes");
+            }
+            for change in changes {
+                println!("{}", change);
+            }
+        }
+        #[cfg(feature = "lsp")]
+        Commands::Lsp => navilang::lsp::run()?,
+        #[cfg(feature = "watch")]
+        Commands::Watch { file, format } => {
+            if file == Path::new("-") {
+                return Err(anyhow::anyhow!("Cannot watch stdin").into());
+            }
+            let config = AnalyzerConfig { max_errors: error_limit(max_errors), ..AnalyzerConfig::default() };
+            navilang::watch::watch(|| {
+                let (result, files) = match compile(&file, max_errors) {
+                    Ok(result) => {
+                        let files = std::iter::once(file.clone())
+                            .chain(result.source.includes.iter().map(|region| PathBuf::from(&region.path)))
+                            .collect();
+                        (Ok(result), files)
+                    }
+                    Err(error) => (Err(error), vec![file.clone()]),
+                };
+                
+                let outcome = result.and_then(|result| match &format {
+                    Some(format) => generate(&result, format, false).map(|generated| print!("{}", generated)),
+                    None => check(&result, &config, false).map(|warnings| {
+                        report_warnings(warnings, error_format, &file);
+                        let metrics = metrics::compute(&result.ast);
+                        println!("OK: {} contexts, {} statements", metrics.contexts, metrics.statements);
+                    }),
+                });
+                if let Err(error) = outcome {
+                    report_error(error, error_format, max_errors, &artifact_path(&file));
+                }
+                files
+            })?;
         }
     }
     
-    Ok(())
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Render a compiled file in one of the `generate` formats
+fn generate(result: &CompilationResult, format: &str, compact: bool) -> navilang::Result<String> {
+    let generated = match format {
+        #[cfg(feature = "mermaid")]
+        "mermaid" => generator::mermaid::to_flowchart(&result.ast),
+        #[cfg(feature = "graphviz")]
+        "dot" => generator::dot::to_dot(&result.ast),
+        "md" => generator::markdown::to_docs(&result.ast),
+        "json" => generator::json::to_json(&result.ast, !compact)?,
+        "plantuml" => generator::plantuml::to_sequence(&result.ast),
+        "openapi" => generator::json::to_json(&generator::openapi::to_openapi(&result.ast), !compact)?,
+        "jsonschema" => generator::json::to_json(&generator::jsonschema::to_schema(&result.ast), !compact)?,
+        "csv" => generator::csv::to_edge_list(&result.ast),
+        "graphml" => generator::graphml::to_graphml(&result.ast),
+        _ => return Err(anyhow::anyhow!("Unsupported output format: {}", format).into()),
+    };
+    Ok(generated)
+}
+
+/// Put `note` in a comment at the top of `generated`, or `None` for formats
+/// that have no comment syntax
+fn with_banner(format: &str, generated: String, note: &str) -> Option<String> {
+    let banner = match format {
+        "mermaid" => format!("%% {}\n", note),
+        "dot" => format!("// {}\n", note),
+        "plantuml" => format!("' {}\n", note),
+        "md" => format!("<!-- {} -->\n", note),
+        // The comment can't precede the XML declaration
+        "graphml" => {
+            let (declaration, rest) = generated.split_once('\n').unwrap_or((&generated, ""));
+            return Some(format!("{}\n<!-- {} -->\n{}", declaration, note, rest));
+        }
+        _ => return None,
+    };
+    Some(banner + &generated)
+}
+
+/// Run the semantic checks on a compiled file, returning its warnings
+///
+/// With `deny_warnings`, any warning fails the check instead.
+fn check(result: &CompilationResult, config: &AnalyzerConfig, deny_warnings: bool) -> navilang::Result<Vec<Diagnostic>> {
+    let warnings = SemanticAnalyzer::with_config(*config).analyze(&result.ast, &result.source.content)?;
+    
+    if deny_warnings && !warnings.is_empty() {
+        let mut errors = ErrorCollector::new();
+        for warning in warnings {
+            errors.add_error(warning.error);
+        }
+        return errors.into_result(Vec::new());
+    }
+    Ok(warnings)
+}
+
+/// Number of individual errors, counting each one inside `MultipleErrors`
+fn error_count(error: &navilang::NaviLangError) -> usize {
+    match error {
+        navilang::NaviLangError::MultipleErrors { errors } => errors.iter().map(error_count).sum(),
+        _ => 1,
+    }
+}
+
+fn report_warnings(warnings: Vec<Diagnostic>, error_format: ErrorFormat, file: &Path) {
+    if warnings.is_empty() {
+        return;
+    }
+    match error_format {
+        ErrorFormat::Human => {
+            for warning in warnings {
+                eprintln!("{:?}", miette::Report::new(warning));
+            }
+        }
+        ErrorFormat::Json => {
+            for warning in warnings {
+                eprintln!("{}", warning.to_json());
+            }
+        }
+        // `check` returns warnings only when there are no errors, so this is
+        // the file's only log
+        ErrorFormat::Sarif => {
+            eprintln!("{}", navilang::error::diagnostics_to_sarif(&warnings, &artifact_path(file)));
+        }
+    }
+}
+
+/// Report the diagnostics of every file of a `check --recursive` run, as a
+/// single document in the machine-readable formats
+fn report_files(files: Vec<(String, Vec<Diagnostic>)>, error_format: ErrorFormat) {
+    match error_format {
+        ErrorFormat::Human => {
+            for diagnostic in files.into_iter().flat_map(|(_, diagnostics)| diagnostics) {
+                eprintln!("{:?}", miette::Report::new(diagnostic));
+            }
+        }
+        ErrorFormat::Json => {
+            let mut items = Vec::new();
+            for (path, diagnostics) in &files {
+                for diagnostic in diagnostics {
+                    let json = diagnostic.to_json();
+                    let start = items.len();
+                    match json {
+                        serde_json::Value::Array(json) => items.extend(json),
+                        json => items.push(json),
+                    }
+                    for item in &mut items[start..] {
+                        item["file"] = path.as_str().into();
+                    }
+                }
+            }
+            if !items.is_empty() {
+                eprintln!("{}", serde_json::Value::Array(items));
+            }
+        }
+        ErrorFormat::Sarif => eprintln!("{}", navilang::error::files_to_sarif(&files)),
+    }
+}
+
+/// `count` followed by `noun`, made plural unless `count` is 1
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 { format!("1 {}", noun) } else { format!("{} {}s", count, noun) }
 }
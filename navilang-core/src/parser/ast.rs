@@ -1,10 +1,30 @@
 // AST (Abstract Syntax Tree) definitions
-use crate::error::Span;
+use crate::error::{NaviLangError, Result, Span};
+use crate::utils::duration::Duration;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Program {
     pub contexts: Vec<Context>,
+    /// `INCLUDE` directives, only present when the source was parsed without
+    /// inlining them first
+    #[serde(default)]
+    pub includes: Vec<Include>,
+    pub span: Span,
+}
+
+/// A top-level `INCLUDE "file.navi"` directive kept in the tree, e.g. so that
+/// the formatter can write it back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Include {
+    pub path: String,
+    /// Comments written directly above the `INCLUDE` keyword
+    #[serde(default)]
+    pub comments: Vec<String>,
+    /// Number of top-level contexts written before the directive
+    pub position: usize,
     pub span: Span,
 }
 
@@ -12,17 +32,1059 @@ pub struct Program {
 pub struct Context {
     pub name: String,
     pub statements: Vec<Statement>,
+    /// Sub-contexts declared inside this one
+    pub children: Vec<Context>,
+    /// Comments written directly above the `CONTEXT` keyword
+    #[serde(default)]
+    pub comments: Vec<String>,
+    /// File the context was parsed from, once set by `Program::with_source_id`;
+    /// `span` is relative to that file
+    #[serde(default)]
+    pub source_id: Option<String>,
     pub span: Span,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Statement {
     pub kind: StatementKind,
+    /// Comments written directly above the statement
+    #[serde(default)]
+    pub comments: Vec<String>,
+    /// Identifiers written in the statement itself, in source order; those of
+    /// nested statements belong to the nested statement
+    #[serde(default)]
+    pub names: Vec<Name>,
+    /// `[key=value, ...]` written after the statement, in source order
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+    pub span: Span,
+}
+
+/// One `key=value` pair of a statement's attribute list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attribute {
+    pub key: String,
+    pub value: Value,
+    pub span: Span,
+}
+
+/// An identifier as it appears in source, with the span of its token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Name {
+    pub name: String,
     pub span: Span,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum StatementKind {
-    // Placeholder for now
-    Placeholder,
+    /// `VAR Name` or `VAR Name:Type`
+    VarDecl {
+        name: String,
+        type_annotation: Option<TypeAnnotation>,
+    },
+    /// `From GOES TO To` (or the `From -> To` shorthand), optionally followed
+    /// by `LABELED "text"`, shown on the edge in diagrams
+    Flow {
+        from: String,
+        to: String,
+        #[serde(default)]
+        label: Option<String>,
+    },
+    /// `IF <condition> THEN <statement>`, optionally followed by `ELSE <statement>`
+    Conditional {
+        condition: Condition,
+        then_branch: Vec<Statement>,
+        #[serde(default)]
+        else_branch: Option<Vec<Statement>>,
+    },
+    /// `PARALLEL { ... }`, where each branch runs concurrently, optionally
+    /// followed by `JOIN AT <name>`, the state every branch must lead to
+    Parallel {
+        branches: Vec<Vec<Statement>>,
+        #[serde(default)]
+        join: Option<String>,
+    },
+    /// `LOOP { ... }` (no condition) or `WHILE <condition> { ... }`
+    Loop {
+        condition: Option<Condition>,
+        body: Vec<Statement>,
+    },
+    /// `BATCH [N] { ... }`, where the enclosed interactions are processed in
+    /// groups of N (or in unspecified groups when N is omitted)
+    Batch {
+        size: Option<i64>,
+        body: Vec<Statement>,
+    },
+    Break,
+    Continue,
+    /// `START <name>`, the node the context's flows begin at
+    Start {
+        node: String,
+    },
+    /// `A USES B`: A depends on B, e.g. a service on its database
+    Uses {
+        subject: String,
+        dependency: String,
+    },
+    /// `Entity CREATED BY Creator`, where the entity originates
+    Creation {
+        subject: String,
+        creator: String,
+    },
+    /// `Actor DOES Action` or `Actor DOES "Multi-word action"`
+    Action {
+        actor: String,
+        action: String,
+    },
+    /// `A CALLS B`, `A RECEIVES X` or `A RETURNS Y`, optionally followed by
+    /// `RETRY <count>` and `TIMEOUT <duration>` in either order. `ASYNC`
+    /// before the subject or among the clauses marks a fire-and-forget call;
+    /// a trailing `ASYNC` directly followed by another statement prefixes that
+    /// statement instead.
+    Interaction {
+        subject: String,
+        verb: InteractionVerb,
+        object: Value,
+        #[serde(default)]
+        retry: Option<i64>,
+        #[serde(default)]
+        timeout: Option<Duration>,
+        #[serde(default)]
+        is_async: bool,
+    },
+    /// `A BEFORE B` or `A AFTER B`, sequencing two steps without a flow
+    Ordering {
+        subject: String,
+        relation: OrderRelation,
+        other: String,
+    },
+    /// `ENDPOINT GET "/users"`, an HTTP route exposed by the context, with an
+    /// optional quoted description after the path
+    Endpoint {
+        method: HttpMethod,
+        path: String,
+        #[serde(default)]
+        description: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderRelation {
+    Before,
+    After,
+}
+
+/// HTTP method of an `ENDPOINT` statement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InteractionVerb {
+    Calls,
+    Receives,
+    Returns,
+}
+
+/// A guard of a conditional or `WHILE` loop: comparisons combined with
+/// `AND`/`OR`
+///
+/// `AND` binds tighter than `OR` and both associate to the left, so
+/// `A AND B OR C` is `Or(And(A, B), C)`. Parentheses override this.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    Leaf(Predicate),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+/// A single comparison, e.g. `User IS "valid"`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Predicate {
+    pub left: String,
+    pub comparison: Comparison,
+    pub right: Value,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    Is,
+    Equals,
+    NotEquals,
+    LessThan,
+    GreaterThan,
+    LessEqual,
+    GreaterEqual,
+}
+
+/// A literal or identifier in value position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Value {
+    Identifier(String),
+    String(String),
+    Number(i64),
+    Float(f64),
+    Boolean(bool),
+    Duration(Duration),
+    /// `[a, b, ...]`
+    List(Vec<Value>),
+    /// `{ key: value, ... }`, with fields in source order
+    Object(Vec<(String, Value)>),
+    /// A type name standing for any value of that type, as in the object
+    /// field `token: String`
+    Type(TypeAnnotation),
+}
+
+/// Type given to a variable after the colon in a `VAR` declaration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TypeAnnotation {
+    Entity,
+    Service,
+    Endpoint,
+    Object,
+    String,
+    Number,
+    Boolean,
+    /// `[T]`, a list of `T`
+    List(Box<TypeAnnotation>),
+}
+
+impl Statement {
+    /// Span of the first occurrence of `name` among the statement's own
+    /// identifiers, or of the whole statement if it has none by that name
+    pub fn span_of(&self, name: &str) -> &Span {
+        self.names.iter()
+            .find(|written| written.name == name)
+            .map_or(&self.span, |written| &written.span)
+    }
+}
+
+impl Program {
+    /// Tag every context, including nested ones, with the file it came from
+    ///
+    /// Contexts that already carry a source id keep it.
+    pub fn with_source_id(mut self, source_id: &str) -> Self {
+        fn tag(contexts: &mut [Context], source_id: &str) {
+            for context in contexts {
+                context.source_id.get_or_insert_with(|| source_id.to_string());
+                tag(&mut context.children, source_id);
+            }
+        }
+        tag(&mut self.contexts, source_id);
+        self
+    }
+
+    /// Append the contexts of `other`, e.g. a program parsed from another file
+    ///
+    /// Top-level context names must be unique across both programs; a clash is
+    /// a `SemanticError` at the span of the second definition, naming the
+    /// `source_id` of both. Contexts keep their own spans, so tag each program
+    /// with `with_source_id` first to tell the files apart. The merged program
+    /// keeps the span of `self`.
+    ///
+    /// The error carries no source text; use `merge_with_source` to render it
+    /// with a snippet.
+    pub fn merge(self, other: Program) -> Result<Program> {
+        self.merge_with_source(other, "")
+    }
+
+    /// Like `merge`, attaching `other_src`, the text `other` was parsed from,
+    /// to a clash so it renders against the second definition
+    pub fn merge_with_source(mut self, other: Program, other_src: &str) -> Result<Program> {
+        for context in &other.contexts {
+            if let Some(existing) = self.contexts.iter().find(|c| c.name == context.name) {
+                let origin = |context: &Context| context.source_id.clone().unwrap_or_else(|| "<unknown>".to_string());
+                return Err(NaviLangError::semantic_error(
+                    format!(
+                        "Context '{}' is defined in both {} and {}",
+                        context.name,
+                        origin(existing),
+                        origin(context)
+                    ),
+                    other_src.to_string(),
+                    context.span.clone(),
+                ));
+            }
+        }
+
+        self.contexts.extend(other.contexts);
+        Ok(self)
+    }
+}
+
+impl Value {
+    /// The referenced name, if this value is an identifier
+    pub fn as_identifier(&self) -> Option<&str> {
+        match self {
+            Value::Identifier(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl Condition {
+    /// Source span from the first comparison to the last
+    pub fn span(&self) -> Span {
+        match self {
+            Condition::Leaf(predicate) => predicate.span.clone(),
+            Condition::And(left, right) | Condition::Or(left, right) => left.span().combine(&right.span()),
+        }
+    }
+
+    /// The comparisons of this condition, left to right
+    pub fn predicates(&self) -> Vec<&Predicate> {
+        match self {
+            Condition::Leaf(predicate) => vec![predicate],
+            Condition::And(left, right) | Condition::Or(left, right) => {
+                let mut predicates = left.predicates();
+                predicates.extend(right.predicates());
+                predicates
+            }
+        }
+    }
+}
+
+/// Renders with the fewest parentheses that keep the tree shape
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Leaf(predicate) => predicate.fmt(f),
+            Condition::Or(left, right) => match right.as_ref() {
+                Condition::Or(..) => write!(f, "{} OR ({})", left, right),
+                _ => write!(f, "{} OR {}", left, right),
+            },
+            Condition::And(left, right) => {
+                match left.as_ref() {
+                    Condition::Or(..) => write!(f, "({})", left)?,
+                    _ => write!(f, "{}", left)?,
+                }
+                match right.as_ref() {
+                    Condition::Leaf(_) => write!(f, " AND {}", right),
+                    _ => write!(f, " AND ({})", right),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.left, self.comparison, self.right)
+    }
+}
+
+impl Comparison {
+    /// Whether this is `<`, `>`, `<=` or `>=`, which only make sense on numbers
+    pub fn is_ordering(self) -> bool {
+        matches!(
+            self,
+            Comparison::LessThan | Comparison::GreaterThan | Comparison::LessEqual | Comparison::GreaterEqual
+        )
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Comparison::Is => "IS",
+            Comparison::Equals => "=",
+            Comparison::NotEquals => "!=",
+            Comparison::LessThan => "<",
+            Comparison::GreaterThan => ">",
+            Comparison::LessEqual => "<=",
+            Comparison::GreaterEqual => ">=",
+        };
+        f.write_str(text)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Identifier(name) => f.write_str(name),
+            Value::String(s) => write!(f, "{:?}", s),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Duration(d) => write!(f, "{}", d),
+            Value::List(items) => {
+                let items: Vec<String> = items.iter().map(Value::to_string).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Value::Object(fields) => {
+                let fields: Vec<String> = fields.iter().map(|(key, value)| format!("{}: {}", key, value)).collect();
+                write!(f, "{{ {} }}", fields.join(", "))
+            }
+            Value::Type(annotation) => write!(f, "{}", annotation),
+        }
+    }
+}
+
+impl HttpMethod {
+    /// Parse a method name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "GET" => Some(HttpMethod::Get),
+            "POST" => Some(HttpMethod::Post),
+            "PUT" => Some(HttpMethod::Put),
+            "DELETE" => Some(HttpMethod::Delete),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Post => "POST",
+            HttpMethod::Put => "PUT",
+            HttpMethod::Delete => "DELETE",
+        };
+        f.write_str(text)
+    }
+}
+
+impl fmt::Display for TypeAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            TypeAnnotation::Entity => "Entity",
+            TypeAnnotation::Service => "Service",
+            TypeAnnotation::Endpoint => "Endpoint",
+            TypeAnnotation::Object => "Object",
+            TypeAnnotation::String => "String",
+            TypeAnnotation::Number => "Number",
+            TypeAnnotation::Boolean => "Boolean",
+            TypeAnnotation::List(element) => return write!(f, "[{}]", element),
+        };
+        f.write_str(text)
+    }
+}
+
+/// One line per top-level context, in the compact form of [`Context`]'s
+/// `Display`; handy for snapshot tests, where the derived `Debug` is too noisy
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, context) in self.contexts.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", context)?;
+        }
+        Ok(())
+    }
+}
+
+/// `Context "Auth" { VarDecl User:Entity; Flow User->Dashboard }`, with
+/// sub-contexts after the statements; spans and comments are left out
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut items: Vec<String> = self.statements.iter().map(Statement::to_string).collect();
+        items.extend(self.children.iter().map(Context::to_string));
+        write!(f, "Context {:?} {}", self.name, block(&items))
+    }
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.kind.fmt(f)?;
+        if !self.attributes.is_empty() {
+            let pairs: Vec<String> = self.attributes.iter()
+                .map(|attribute| format!("{}={}", attribute.key, attribute.value))
+                .collect();
+            write!(f, " [{}]", pairs.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for StatementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let body = |statements: &[Statement]| {
+            let items: Vec<String> = statements.iter().map(Statement::to_string).collect();
+            block(&items)
+        };
+        match self {
+            StatementKind::VarDecl { name, type_annotation: Some(annotation) } => {
+                write!(f, "VarDecl {}:{}", name, annotation)
+            }
+            StatementKind::VarDecl { name, type_annotation: None } => write!(f, "VarDecl {}", name),
+            StatementKind::Flow { from, to, label: Some(label) } => write!(f, "Flow {}->{} {:?}", from, to, label),
+            StatementKind::Flow { from, to, label: None } => write!(f, "Flow {}->{}", from, to),
+            StatementKind::Conditional { condition, then_branch, else_branch } => {
+                write!(f, "If {} {}", condition, body(then_branch))?;
+                if let Some(else_branch) = else_branch {
+                    write!(f, " Else {}", body(else_branch))?;
+                }
+                Ok(())
+            }
+            StatementKind::Parallel { branches, join } => {
+                f.write_str("Parallel")?;
+                for branch in branches {
+                    write!(f, " {}", body(branch))?;
+                }
+                if let Some(join) = join {
+                    write!(f, " JoinAt {}", join)?;
+                }
+                Ok(())
+            }
+            StatementKind::Loop { condition: Some(condition), body: statements } => {
+                write!(f, "While {} {}", condition, body(statements))
+            }
+            StatementKind::Loop { condition: None, body: statements } => write!(f, "Loop {}", body(statements)),
+            StatementKind::Batch { size, body: statements } => match size {
+                Some(size) => write!(f, "Batch {} {}", size, body(statements)),
+                None => write!(f, "Batch {}", body(statements)),
+            },
+            StatementKind::Break => f.write_str("Break"),
+            StatementKind::Continue => f.write_str("Continue"),
+            StatementKind::Start { node } => write!(f, "Start {}", node),
+            StatementKind::Uses { subject, dependency } => write!(f, "Uses {}->{}", subject, dependency),
+            StatementKind::Creation { subject, creator } => write!(f, "Creation {}<-{}", subject, creator),
+            StatementKind::Action { actor, action } => write!(f, "Action {} {:?}", actor, action),
+            StatementKind::Interaction { subject, verb, object, retry, timeout, is_async } => {
+                let verb = match verb {
+                    InteractionVerb::Calls => "Calls",
+                    InteractionVerb::Receives => "Receives",
+                    InteractionVerb::Returns => "Returns",
+                };
+                write!(f, "{} {} {}", verb, subject, object)?;
+                if let Some(retry) = retry {
+                    write!(f, " Retry {}", retry)?;
+                }
+                if let Some(timeout) = timeout {
+                    write!(f, " Timeout {}", timeout)?;
+                }
+                if *is_async {
+                    f.write_str(" Async")?;
+                }
+                Ok(())
+            }
+            StatementKind::Ordering { subject, relation, other } => {
+                let relation = match relation {
+                    OrderRelation::Before => "Before",
+                    OrderRelation::After => "After",
+                };
+                write!(f, "{} {} {}", relation, subject, other)
+            }
+            StatementKind::Endpoint { method, path, description } => {
+                write!(f, "Endpoint {} {:?}", method, path)?;
+                if let Some(description) = description {
+                    write!(f, " {:?}", description)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// `{ a; b }`, or `{}` when empty
+fn block(items: &[String]) -> String {
+    if items.is_empty() {
+        "{}".to_string()
+    } else {
+        format!("{{ {} }}", items.join("; "))
+    }
+}
+
+/// Read-only traversal of a program
+///
+/// Every method defaults to a no-op; override the ones a pass cares about and
+/// let [`walk_program`] do the recursion. Contexts are entered before their
+/// statements and left after their children; compound statements are visited
+/// before the statements they contain and left after them.
+pub trait Visitor<'ast> {
+    fn visit_context(&mut self, _context: &'ast Context) {}
+
+    /// Called once a context's statements and children have been walked
+    fn leave_context(&mut self, _context: &'ast Context) {}
+
+    fn visit_statement(&mut self, _stmt: &'ast Statement) {}
+
+    /// Called once everything nested in a statement has been walked
+    fn leave_statement(&mut self, _stmt: &'ast Statement) {}
+
+    /// Called for the guard of a conditional or `WHILE` loop, after the
+    /// statement itself
+    fn visit_condition(&mut self, _condition: &'ast Condition) {}
+
+    /// Called for each comparison in a guard, after `visit_condition`
+    fn visit_predicate(&mut self, _predicate: &'ast Predicate) {}
+}
+
+/// Walk every context of `program` in source order
+pub fn walk_program<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, program: &'ast Program) {
+    for context in &program.contexts {
+        walk_context(visitor, context);
+    }
+}
+
+/// Walk a context, its statements and its sub-contexts
+pub fn walk_context<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, context: &'ast Context) {
+    visitor.visit_context(context);
+    for stmt in &context.statements {
+        walk_statement(visitor, stmt);
+    }
+    for child in &context.children {
+        walk_context(visitor, child);
+    }
+    visitor.leave_context(context);
+}
+
+/// Visit a guard and each of its comparisons
+pub fn walk_condition<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, condition: &'ast Condition) {
+    visitor.visit_condition(condition);
+    for predicate in condition.predicates() {
+        visitor.visit_predicate(predicate);
+    }
+}
+
+/// Walk a statement and, for compound statements, everything nested in it
+pub fn walk_statement<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, stmt: &'ast Statement) {
+    visitor.visit_statement(stmt);
+    match &stmt.kind {
+        StatementKind::Conditional { condition, then_branch, else_branch } => {
+            walk_condition(visitor, condition);
+            for stmt in then_branch.iter().chain(else_branch.iter().flatten()) {
+                walk_statement(visitor, stmt);
+            }
+        }
+        StatementKind::Loop { condition, body } => {
+            if let Some(condition) = condition {
+                walk_condition(visitor, condition);
+            }
+            for stmt in body {
+                walk_statement(visitor, stmt);
+            }
+        }
+        StatementKind::Parallel { branches, .. } => {
+            for stmt in branches.iter().flatten() {
+                walk_statement(visitor, stmt);
+            }
+        }
+        StatementKind::Batch { body, .. } => {
+            for stmt in body {
+                walk_statement(visitor, stmt);
+            }
+        }
+        _ => {}
+    }
+    visitor.leave_statement(stmt);
+}
+
+/// A difference between two versions of a program, as found by [`diff`]
+///
+/// Contexts are named by their path, e.g. `Shop.Checkout` for a `Checkout`
+/// nested in `Shop`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Change {
+    AddedContext(String),
+    RemovedContext(String),
+    /// A context in both versions whose statements, besides its variables
+    /// and flows, differ
+    ModifiedContext(String),
+    AddedVariable { context: String, name: String },
+    RemovedVariable { context: String, name: String },
+    /// A variable declared with a different type
+    ModifiedVariable { context: String, name: String },
+    AddedFlow { context: String, from: String, to: String },
+    RemovedFlow { context: String, from: String, to: String },
+    /// A flow between the same ends with a different label or attributes
+    ModifiedFlow { context: String, from: String, to: String },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::AddedContext(context) => write!(f, "+ context {}", context),
+            Change::RemovedContext(context) => write!(f, "- context {}", context),
+            Change::ModifiedContext(context) => write!(f, "~ context {}", context),
+            Change::AddedVariable { context, name } => write!(f, "+ variable {}: {}", context, name),
+            Change::RemovedVariable { context, name } => write!(f, "- variable {}: {}", context, name),
+            Change::ModifiedVariable { context, name } => write!(f, "~ variable {}: {}", context, name),
+            Change::AddedFlow { context, from, to } => write!(f, "+ flow {}: {} -> {}", context, from, to),
+            Change::RemovedFlow { context, from, to } => write!(f, "- flow {}: {} -> {}", context, from, to),
+            Change::ModifiedFlow { context, from, to } => write!(f, "~ flow {}: {} -> {}", context, from, to),
+        }
+    }
+}
+
+/// Compare two versions of a program, matching contexts, variables and flows
+/// by name
+///
+/// Flows are identified by both ends, so retargeting one is a removal and an
+/// addition, while relabeling one is a modification. Variables and flows
+/// count wherever they sit in their context, including inside blocks, and
+/// only there; the contents of an added or removed context are not listed
+/// separately. Spans and comments are ignored. The changes are sorted:
+/// contexts first, then variables, then flows.
+pub fn diff(old: &Program, new: &Program) -> Vec<Change> {
+    let old = ContextSummary::collect(&old.contexts);
+    let new = ContextSummary::collect(&new.contexts);
+
+    let mut changes = Vec::new();
+    for (path, before) in &old {
+        let Some(after) = new.get(path) else {
+            changes.push(Change::RemovedContext(path.clone()));
+            continue;
+        };
+        if !same_block(before.statements, after.statements) {
+            changes.push(Change::ModifiedContext(path.clone()));
+        }
+
+        let context = || path.clone();
+        for (name, type_annotation) in &before.variables {
+            match after.variables.get(name) {
+                None => changes.push(Change::RemovedVariable { context: context(), name: name.to_string() }),
+                Some(other) if other != type_annotation => {
+                    changes.push(Change::ModifiedVariable { context: context(), name: name.to_string() });
+                }
+                Some(_) => {}
+            }
+        }
+        for name in after.variables.keys().filter(|name| !before.variables.contains_key(*name)) {
+            changes.push(Change::AddedVariable { context: context(), name: name.to_string() });
+        }
+
+        for (&(from, to), flow) in &before.flows {
+            let (from, to) = (from.to_string(), to.to_string());
+            match after.flows.get(&(from.as_str(), to.as_str())) {
+                None => changes.push(Change::RemovedFlow { context: context(), from, to }),
+                Some(other) if !same_flow(flow, other) => changes.push(Change::ModifiedFlow { context: context(), from, to }),
+                Some(_) => {}
+            }
+        }
+        for (from, to) in after.flows.keys().filter(|ends| !before.flows.contains_key(*ends)) {
+            changes.push(Change::AddedFlow { context: context(), from: from.to_string(), to: to.to_string() });
+        }
+    }
+    for path in new.keys().filter(|path| !old.contains_key(*path)) {
+        changes.push(Change::AddedContext(path.clone()));
+    }
+
+    changes.sort();
+    changes
+}
+
+/// The parts of one context that [`diff`] compares
+#[derive(Default)]
+struct ContextSummary<'ast> {
+    variables: BTreeMap<&'ast str, &'ast Option<TypeAnnotation>>,
+    /// The first flow between each pair of ends
+    flows: BTreeMap<(&'ast str, &'ast str), &'ast Statement>,
+    /// The context's own statements, compared with `same_block`
+    statements: &'ast [Statement],
+}
+
+impl<'ast> Visitor<'ast> for ContextSummary<'ast> {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        match &stmt.kind {
+            StatementKind::VarDecl { name, type_annotation } => {
+                self.variables.entry(name).or_insert(type_annotation);
+            }
+            StatementKind::Flow { from, to, .. } => {
+                self.flows.entry((from, to)).or_insert(stmt);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'ast> ContextSummary<'ast> {
+    /// Summaries of `contexts` and all their descendants, by path
+    fn collect(contexts: &'ast [Context]) -> BTreeMap<String, ContextSummary<'ast>> {
+        fn add<'ast>(contexts: &'ast [Context], prefix: &str, out: &mut BTreeMap<String, ContextSummary<'ast>>) {
+            for context in contexts {
+                let path = if prefix.is_empty() { context.name.clone() } else { format!("{}.{}", prefix, context.name) };
+                let mut summary = ContextSummary { statements: &context.statements, ..ContextSummary::default() };
+                for stmt in &context.statements {
+                    walk_statement(&mut summary, stmt);
+                }
+                add(&context.children, &path, out);
+                out.entry(path).or_insert(summary);
+            }
+        }
+
+        let mut out = BTreeMap::new();
+        add(contexts, "", &mut out);
+        out
+    }
+}
+
+/// Whether two blocks match statement for statement, ignoring spans, names
+/// and comments
+///
+/// Variables and flows are left out at every level, as `diff` reports them
+/// on their own.
+fn same_block(a: &[Statement], b: &[Statement]) -> bool {
+    fn compared(block: &[Statement]) -> Vec<&Statement> {
+        block.iter()
+            .filter(|stmt| !matches!(stmt.kind, StatementKind::VarDecl { .. } | StatementKind::Flow { .. }))
+            .collect()
+    }
+    let (a, b) = (compared(a), compared(b));
+    a.len() == b.len() && a.iter().zip(&b).all(|(a, b)| same_statement(a, b))
+}
+
+fn same_statement(a: &Statement, b: &Statement) -> bool {
+    let same_kind = match (&a.kind, &b.kind) {
+        (
+            StatementKind::Conditional { condition, then_branch, else_branch },
+            StatementKind::Conditional { condition: other_condition, then_branch: other_then, else_branch: other_else },
+        ) => {
+            same_condition(condition, other_condition)
+                && same_block(then_branch, other_then)
+                && match (else_branch, other_else) {
+                    (Some(else_branch), Some(other_else)) => same_block(else_branch, other_else),
+                    (else_branch, other_else) => else_branch.is_none() && other_else.is_none(),
+                }
+        }
+        (
+            StatementKind::Parallel { branches, join },
+            StatementKind::Parallel { branches: other_branches, join: other_join },
+        ) => {
+            join == other_join
+                && branches.len() == other_branches.len()
+                && branches.iter().zip(other_branches).all(|(branch, other)| same_block(branch, other))
+        }
+        (
+            StatementKind::Loop { condition, body },
+            StatementKind::Loop { condition: other_condition, body: other_body },
+        ) => {
+            let same_guard = match (condition, other_condition) {
+                (Some(condition), Some(other)) => same_condition(condition, other),
+                (condition, other) => condition.is_none() && other.is_none(),
+            };
+            same_guard && same_block(body, other_body)
+        }
+        (StatementKind::Batch { size, body }, StatementKind::Batch { size: other_size, body: other_body }) => {
+            size == other_size && same_block(body, other_body)
+        }
+        // The remaining kinds hold no statements or spans
+        (kind, other) => kind == other,
+    };
+    same_kind && same_attributes(&a.attributes, &b.attributes)
+}
+
+fn same_condition(a: &Condition, b: &Condition) -> bool {
+    match (a, b) {
+        (Condition::Leaf(a), Condition::Leaf(b)) => {
+            a.left == b.left && a.comparison == b.comparison && a.right == b.right
+        }
+        (Condition::And(a, a_right), Condition::And(b, b_right))
+        | (Condition::Or(a, a_right), Condition::Or(b, b_right)) => same_condition(a, b) && same_condition(a_right, b_right),
+        _ => false,
+    }
+}
+
+/// Whether two flows between the same ends have the same label and attributes
+fn same_flow(a: &Statement, b: &Statement) -> bool {
+    let label = |stmt: &Statement| match &stmt.kind {
+        StatementKind::Flow { label, .. } => label.clone(),
+        _ => None,
+    };
+    label(a) == label(b) && same_attributes(&a.attributes, &b.attributes)
+}
+
+fn same_attributes(a: &[Attribute], b: &[Attribute]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.key == b.key && a.value == b.value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_program;
+
+    #[derive(Default)]
+    struct Counter {
+        contexts: usize,
+        statements: usize,
+        conditions: usize,
+        depth: usize,
+        max_depth: usize,
+        nesting: usize,
+        max_nesting: usize,
+    }
+
+    impl<'ast> Visitor<'ast> for Counter {
+        fn visit_context(&mut self, _context: &'ast Context) {
+            self.contexts += 1;
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+        }
+
+        fn leave_context(&mut self, _context: &'ast Context) {
+            self.depth -= 1;
+        }
+
+        fn visit_statement(&mut self, _stmt: &'ast Statement) {
+            self.statements += 1;
+            self.max_nesting = self.max_nesting.max(self.nesting);
+            self.nesting += 1;
+        }
+
+        fn leave_statement(&mut self, _stmt: &'ast Statement) {
+            self.nesting -= 1;
+        }
+
+        fn visit_condition(&mut self, _condition: &'ast Condition) {
+            self.conditions += 1;
+        }
+    }
+
+    #[test]
+    fn test_counting_visitor_walks_nested_program() {
+        let input = r#"CONTEXT Outer {
+    VAR User
+    IF User IS "valid" THEN User -> Home ELSE User -> Login
+    PARALLEL { User DOES Login  User CALLS Api }
+    CONTEXT Inner {
+        WHILE Attempts < 3 { User -> Retry1  LOOP { BREAK } }
+    }
+}
+CONTEXT Other { User -> Home }"#;
+        let program = parse_program(input);
+
+        let mut counter = Counter::default();
+        walk_program(&mut counter, &program);
+
+        // VAR, IF + 2 flows, PARALLEL + 2, WHILE + flow + LOOP + BREAK, flow
+        assert_eq!(counter.statements, 12);
+        assert_eq!(counter.contexts, 3);
+        assert_eq!(counter.conditions, 2);
+        assert_eq!(counter.max_depth, 2);
+        assert_eq!(counter.depth, 0);
+        // BREAK sits inside LOOP and WHILE
+        assert_eq!(counter.max_nesting, 2);
+        assert_eq!(counter.nesting, 0);
+    }
+
+    fn parse(input: &str, source_id: &str) -> Program {
+        parse_program(input).with_source_id(source_id)
+    }
+
+    #[test]
+    fn test_merge_keeps_contexts_and_their_origin() {
+        let auth = parse("CONTEXT Auth { VAR User CONTEXT Login {} }", "auth.navi");
+        let orders = parse("CONTEXT Orders { VAR Order }", "orders.navi");
+        let orders_span = orders.contexts[0].span.clone();
+
+        let merged = auth.merge(orders).unwrap();
+        let names: Vec<&str> = merged.contexts.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Auth", "Orders"]);
+        assert_eq!(merged.contexts[0].source_id.as_deref(), Some("auth.navi"));
+        assert_eq!(merged.contexts[0].children[0].source_id.as_deref(), Some("auth.navi"));
+        assert_eq!(merged.contexts[1].source_id.as_deref(), Some("orders.navi"));
+        assert_eq!(merged.contexts[1].span, orders_span);
+    }
+
+    #[test]
+    fn test_merge_rejects_duplicate_context_names() {
+        let first = parse("CONTEXT Auth { VAR User }", "a.navi");
+        let second_src = "CONTEXT Other {}\nCONTEXT Auth { VAR Admin }";
+        let second = parse(second_src, "b.navi");
+
+        match first.clone().merge(second.clone()) {
+            Err(NaviLangError::SemanticError { message, span, .. }) => {
+                assert_eq!(message, "Context 'Auth' is defined in both a.navi and b.navi");
+                assert_eq!(span.offset(), "CONTEXT Other {}\n".len());
+            }
+            other => panic!("Expected SemanticError, got {:?}", other),
+        }
+
+        match first.merge_with_source(second, second_src) {
+            Err(NaviLangError::SemanticError { src, .. }) => assert_eq!(src, second_src),
+            other => panic!("Expected SemanticError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_added_flow_and_removed_variable() {
+        let old = parse("CONTEXT Shop {
+    VAR User
+    VAR Cart
+    User -> Browse
+    IF User IS \"member\" THEN User DOES Login
+}", "old.navi");
+        let new = parse("// Reformatted, with spans shifted
+CONTEXT Shop {
+    VAR User
+
+    User GOES TO Browse
+    Browse -> Checkout
+    IF User IS \"member\" THEN User DOES Login
+}", "new.navi");
+
+        assert_eq!(diff(&old, &new), vec![
+            Change::RemovedVariable { context: "Shop".to_string(), name: "Cart".to_string() },
+            Change::AddedFlow { context: "Shop".to_string(), from: "Browse".to_string(), to: "Checkout".to_string() },
+        ]);
+        assert!(diff(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_diff_nested_contexts_and_modifications() {
+        let old = parse("CONTEXT App { VAR Id:Number  User DOES Login  CONTEXT Admin {} }\nCONTEXT Old {}", "old.navi");
+        let new = parse("CONTEXT App { VAR Id:String  User DOES Logout  CONTEXT Audit {} }", "new.navi");
+
+        let changes: Vec<String> = diff(&old, &new).iter().map(ToString::to_string).collect();
+        assert_eq!(changes, vec![
+            "+ context App.Audit",
+            "- context App.Admin",
+            "- context Old",
+            "~ context App",
+            "~ variable App: Id",
+        ]);
+    }
+
+    #[test]
+    fn test_diff_compares_flow_labels_and_attributes() {
+        let old = parse(r#"CONTEXT A { A -> B LABELED "x" [weight=1] }"#, "old.navi");
+        let new = parse(r#"CONTEXT A { A -> B LABELED "y" [weight=9] }"#, "new.navi");
+        assert_eq!(diff(&old, &new), vec![
+            Change::ModifiedFlow { context: "A".to_string(), from: "A".to_string(), to: "B".to_string() },
+        ]);
+
+        let new = parse(r#"CONTEXT A { A -> B LABELED "x" [weight=9] }"#, "new.navi");
+        assert_eq!(diff(&old, &new).len(), 1);
+        assert!(diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_flows_in_blocks_once() {
+        let old = parse("CONTEXT A { VAR User  IF User IS 1 THEN User -> Home  LOOP { VAR Tries  User -> Again  BREAK } }", "old.navi");
+        let new = parse("CONTEXT A { VAR User  IF User IS 1 THEN User -> Admin  LOOP { User -> Again  BREAK } }", "new.navi");
+
+        let changes: Vec<String> = diff(&old, &new).iter().map(ToString::to_string).collect();
+        assert_eq!(changes, vec![
+            "- variable A: Tries",
+            "+ flow A: User -> Admin",
+            "- flow A: User -> Home",
+        ]);
+
+        // Anything else in a block still marks the context as modified
+        let new = parse("CONTEXT A { VAR User  IF User IS 2 THEN User -> Home  LOOP { VAR Tries  User -> Again  BREAK } }", "new.navi");
+        assert_eq!(diff(&old, &new), vec![Change::ModifiedContext("A".to_string())]);
+    }
+
+    #[test]
+    fn test_display_renders_compact_tree() {
+        let program = parse(r#"CONTEXT Auth {
+    // Comments and spans are left out
+    VAR User:Entity
+    User -> Dashboard
+    IF User IS "admin" THEN User -> Admin ELSE User DOES "Log out"
+    Api CALLS Db RETRY 3
+    CONTEXT Session { START Login  LOOP { BREAK } }
+}
+CONTEXT Empty {}"#, "auth.navi");
+
+        assert_eq!(program.to_string(), r#"Context "Auth" { VarDecl User:Entity; Flow User->Dashboard; If User IS "admin" { Flow User->Admin } Else { Action User "Log out" }; Calls Api Db Retry 3; Context "Session" { Start Login; Loop { Break } } }
+Context "Empty" {}"#);
+    }
 }
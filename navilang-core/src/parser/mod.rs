@@ -1,37 +1,1967 @@
 // Parser module - Syntax Analysis Stage
-// This module will implement the recursive descent parser
+// This module implements the recursive descent parser
 
 pub mod ast;
 
-use crate::error::NaviLangError;
-use crate::lexer::TokenWithSpan;
+use crate::error::{ErrorCollector, NaviLangError, Position, Span};
+use crate::lexer::{TokenWithSpan, tokens::Token};
+use crate::utils::duration::{Duration, ParseDurationError};
+use ast::{
+    Attribute, Comparison, Condition, Context, HttpMethod, Include, InteractionVerb, Name, OrderRelation,
+    Predicate, Program, Statement, StatementKind, TypeAnnotation, Value,
+};
 
-// Parser struct for syntax analysis
+/// Nesting depth allowed by [`Parser::new`]
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Recursive descent parser over a filtered token stream
+///
+/// Syntax errors don't abort parsing: each one is recorded and the parser
+/// skips ahead to the next statement boundary (or, at the top level, to the
+/// next `CONTEXT`) so that later errors are reported too.
 pub struct Parser {
     tokens: Vec<TokenWithSpan>,
     current: usize,
+    source: String,
+    errors: ErrorCollector,
+    /// Identifiers consumed by the statements currently being parsed
+    names: Vec<Name>,
+    /// Contexts, statements, conditions, lists and objects currently open
+    depth: usize,
+    max_depth: usize,
+    /// Top-level contexts that were dropped or had errors inside them
+    failed_contexts: usize,
+    max_errors: Option<usize>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<TokenWithSpan>) -> Self {
-        Self { 
+        Self {
             tokens,
             current: 0,
+            source: String::new(),
+            errors: ErrorCollector::new(),
+            names: Vec::new(),
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            failed_contexts: 0,
+            max_errors: None,
+        }
+    }
+
+    /// Stop parsing at the first syntax error past `max_errors`, ending the
+    /// errors with a `TooManyErrors` note
+    ///
+    /// Whatever was parsed before stopping is still returned by
+    /// [`parse_partial`](Self::parse_partial).
+    pub fn with_max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = Some(max_errors);
+        self.errors = ErrorCollector::with_limit(max_errors);
+        self
+    }
+
+    /// Limit how deeply contexts, blocks, statements, parenthesized
+    /// conditions, lists and objects may nest
+    ///
+    /// Input nested deeper is a syntax error rather than a stack overflow.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Attach the source text the tokens came from, so errors can render it
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = source.to_string();
+        self
+    }
+
+    pub fn parse(&mut self) -> Result<Program, NaviLangError> {
+        let (program, errors) = self.parse_partial();
+        let mut collector = ErrorCollector::with_optional_limit(self.max_errors);
+        for error in errors {
+            collector.add_error(error);
+        }
+        collector.into_result(program)
+    }
+
+    /// Parse as much as possible, returning whatever was recovered alongside
+    /// the syntax errors
+    ///
+    /// A context whose header is broken is left out; one with broken
+    /// statements is kept with the statements that did parse. Meant for tools
+    /// such as an outline view that must work on files mid-edit.
+    pub fn parse_partial(&mut self) -> (Program, Vec<NaviLangError>) {
+        let mut contexts = Vec::new();
+        let mut includes = Vec::new();
+
+        while !self.is_at_end() && !self.errors.is_full() {
+            if self.check(&Token::Include) {
+                match self.parse_include(contexts.len()) {
+                    Ok(include) => includes.push(include),
+                    Err(error) => {
+                        self.errors.add_error(error);
+                        self.synchronize_context();
+                    }
+                }
+                continue;
+            }
+
+            let errors = self.errors.error_count();
+            match self.nested(Self::parse_context) {
+                Ok(context) => {
+                    if self.errors.error_count() > errors {
+                        self.failed_contexts += 1;
+                    }
+                    contexts.push(context);
+                }
+                Err(error) => {
+                    self.errors.add_error(error);
+                    self.failed_contexts += 1;
+                    self.synchronize_context();
+                }
+            }
+        }
+
+        let program = Program {
+            contexts,
+            includes,
+            span: self.program_span(),
+        };
+        let errors = std::mem::replace(&mut self.errors, ErrorCollector::with_optional_limit(self.max_errors));
+        (program, errors.into_errors())
+    }
+
+    /// Number of top-level contexts the last parse left out or kept with
+    /// syntax errors inside them
+    pub fn failed_contexts(&self) -> usize {
+        self.failed_contexts
+    }
+
+    /// Parse `INCLUDE "<path>"`, found after `position` top-level contexts
+    ///
+    /// Only reached when includes were not inlined before parsing.
+    fn parse_include(&mut self, position: usize) -> Result<Include, NaviLangError> {
+        let comments = self.leading_comments();
+        let start = self.consume(&Token::Include, "Expected 'INCLUDE'")?;
+
+        let Some(Token::QuotedString(path)) = self.peek() else {
+            return Err(self.error_at_current("Expected a quoted path after 'INCLUDE'"));
+        };
+        let path = path.clone();
+        let end = self.advance().span.clone();
+
+        Ok(Include { path, comments, position, span: start.combine(&end) })
+    }
+
+    /// Parse `CONTEXT <name> { ... }`
+    fn parse_context(&mut self) -> Result<Context, NaviLangError> {
+        let comments = self.leading_comments();
+        let start = self.consume(&Token::Context, "Expected 'CONTEXT'")?;
+
+        let name = match self.peek() {
+            Some(Token::QuotedString(name)) | Some(Token::Identifier(name)) => name.clone(),
+            _ => return Err(self.error_at_current("Expected a context name")),
+        };
+        self.advance();
+
+        self.consume(&Token::LeftBrace, "Expected '{' after context name")?;
+
+        let mut children = Vec::new();
+        let statements = self.parse_body(Some(&mut children));
+
+        let end = self.consume(&Token::RightBrace, "Expected '}' to close context")?;
+
+        Ok(Context {
+            name,
+            statements,
+            children,
+            comments,
+            source_id: None,
+            span: start.combine(&end),
+        })
+    }
+
+    /// Parse statements up to the closing brace of the enclosing block,
+    /// recovering from errors at statement boundaries
+    ///
+    /// Sub-contexts are only accepted when `children` is given (context bodies).
+    fn parse_body(&mut self, mut children: Option<&mut Vec<Context>>) -> Vec<Statement> {
+        let mut statements = Vec::new();
+
+        while !self.check(&Token::RightBrace) && !self.is_at_end() && !self.errors.is_full() {
+            let start = self.current;
+
+            let result = match children.as_deref_mut() {
+                Some(children) if self.check(&Token::Context) => {
+                    self.nested(Self::parse_context).map(|context| children.push(context))
+                }
+                _ => self.parse_statement().map(|stmt| statements.push(stmt)),
+            };
+
+            if let Err(error) = result {
+                self.errors.add_error(error);
+                self.synchronize_statement(start);
+            }
+        }
+
+        statements
+    }
+
+    fn parse_statement(&mut self) -> Result<Statement, NaviLangError> {
+        let start = self.current_span();
+        let comments = self.leading_comments();
+
+        // Nested statements take their own names first, leaving ours behind
+        let first_name = self.names.len();
+        let kind = match self.nested(Self::parse_statement_kind) {
+            Ok(kind) => kind,
+            Err(error) => {
+                self.names.truncate(first_name);
+                return Err(error);
+            }
+        };
+
+        let names = self.names.split_off(first_name);
+        let attributes = if self.check(&Token::LeftBracket) {
+            self.parse_attributes()?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Statement {
+            kind,
+            comments,
+            names,
+            attributes,
+            span: start.combine(&self.previous_span()),
+        })
+    }
+
+    /// Parse `[<key>=<value>, ...]` after a statement; a trailing comma is
+    /// allowed
+    ///
+    /// Keys that are keywords, such as `retry`, are stored in lowercase.
+    /// Identifiers among the values are not names of the statement, so they
+    /// are never resolved.
+    fn parse_attributes(&mut self) -> Result<Vec<Attribute>, NaviLangError> {
+        self.consume(&Token::LeftBracket, "Expected '['")?;
+        let first_name = self.names.len();
+
+        let mut attributes = Vec::new();
+        while !self.check(&Token::RightBracket) {
+            let start = self.current_span();
+            let key = match self.peek() {
+                Some(Token::Identifier(key)) => key.clone(),
+                Some(token) if token.is_keyword() => token.to_string().to_ascii_lowercase(),
+                _ => return Err(self.error_at_current("Expected an attribute name")),
+            };
+            self.advance();
+            self.consume(&Token::Equals, &format!("Expected '=' after attribute '{}'", key))?;
+            let value = self.nested(Self::parse_value)?;
+            attributes.push(Attribute { key, value, span: start.combine(&self.previous_span()) });
+
+            if !self.check(&Token::Comma) {
+                break;
+            }
+            self.advance();
+        }
+
+        self.names.truncate(first_name);
+        self.consume(&Token::RightBracket, "Expected ',' or ']' in attributes")?;
+        Ok(attributes)
+    }
+
+    fn parse_statement_kind(&mut self) -> Result<StatementKind, NaviLangError> {
+        let kind = match self.peek() {
+            Some(Token::Var) => self.parse_var_decl()?,
+            Some(Token::If) => self.parse_conditional()?,
+            Some(Token::Parallel) => self.parse_parallel()?,
+            Some(Token::Batch) => self.parse_batch()?,
+            Some(Token::Loop) => {
+                self.advance();
+                let body = self.parse_block("LOOP")?;
+                StatementKind::Loop { condition: None, body }
+            }
+            Some(Token::While) => {
+                self.advance();
+                let condition = self.parse_condition()?;
+                let body = self.parse_block("WHILE")?;
+                StatementKind::Loop { condition: Some(condition), body }
+            }
+            Some(Token::Break) => {
+                self.advance();
+                StatementKind::Break
+            }
+            Some(Token::Continue) => {
+                self.advance();
+                StatementKind::Continue
+            }
+            Some(Token::Start) => {
+                self.advance();
+                let node = self.consume_identifier("Expected a node name after 'START'")?;
+                StatementKind::Start { node }
+            }
+            Some(Token::Async) => self.parse_async_interaction()?,
+            Some(Token::Endpoint) => self.parse_endpoint()?,
+            Some(Token::Identifier(_)) => self.parse_subject_statement()?,
+            _ => return Err(self.error_at_current("Expected a statement")),
+        };
+        Ok(kind)
+    }
+
+    /// Parse `VAR <name>` with an optional `:<type>` annotation
+    fn parse_var_decl(&mut self) -> Result<StatementKind, NaviLangError> {
+        self.consume(&Token::Var, "Expected 'VAR'")?;
+        let name = self.consume_identifier("Expected a variable name")?;
+
+        let type_annotation = if self.check(&Token::Colon) {
+            self.advance();
+            Some(self.parse_type_annotation()?)
+        } else {
+            None
+        };
+
+        Ok(StatementKind::VarDecl { name, type_annotation })
+    }
+
+    fn parse_type_annotation(&mut self) -> Result<TypeAnnotation, NaviLangError> {
+        let annotation = match self.peek() {
+            Some(Token::Entity) => TypeAnnotation::Entity,
+            Some(Token::Service) => TypeAnnotation::Service,
+            Some(Token::Endpoint) => TypeAnnotation::Endpoint,
+            Some(Token::Object) => TypeAnnotation::Object,
+            Some(Token::StringType) => TypeAnnotation::String,
+            Some(Token::NumberType) => TypeAnnotation::Number,
+            Some(Token::BooleanType) => TypeAnnotation::Boolean,
+            Some(Token::LeftBracket) => {
+                self.advance();
+                let element = self.nested(Self::parse_type_annotation)?;
+                self.consume(&Token::RightBracket, "Expected ']' after list element type")?;
+                return Ok(TypeAnnotation::List(Box::new(element)));
+            }
+            _ => return Err(self.error_at_current("Expected a type annotation")),
+        };
+        self.advance();
+        Ok(annotation)
+    }
+
+    /// Parse `ENDPOINT <method> "<path>"`, optionally followed by `"<description>"`
+    ///
+    /// Methods are matched contextually, so `GET` and friends stay usable as
+    /// identifiers elsewhere.
+    fn parse_endpoint(&mut self) -> Result<StatementKind, NaviLangError> {
+        self.consume(&Token::Endpoint, "Expected 'ENDPOINT'")?;
+
+        let method = match self.peek() {
+            Some(Token::Identifier(name)) => HttpMethod::from_name(name),
+            _ => None,
+        };
+        let Some(method) = method else {
+            return Err(self.error_at_current("Expected GET, POST, PUT or DELETE after 'ENDPOINT'"));
+        };
+        self.advance();
+
+        let path = match self.peek() {
+            Some(Token::QuotedString(path)) => path.clone(),
+            _ => return Err(self.error_at_current("Expected a quoted path after the endpoint method")),
+        };
+        if !path.starts_with('/') {
+            return Err(self.error_at_current("Endpoint path must start with '/'"));
+        }
+        self.advance();
+
+        let description = match self.peek() {
+            Some(Token::QuotedString(description)) => {
+                let description = description.clone();
+                self.advance();
+                Some(description)
+            }
+            _ => None,
+        };
+
+        Ok(StatementKind::Endpoint { method, path, description })
+    }
+
+    /// Parse `IF <condition> THEN <statement>` with an optional `ELSE <statement>`
+    ///
+    /// A dangling `ELSE` belongs to the innermost `IF`.
+    fn parse_conditional(&mut self) -> Result<StatementKind, NaviLangError> {
+        self.consume(&Token::If, "Expected 'IF'")?;
+        let condition = self.parse_condition()?;
+        self.consume(&Token::Then, "Expected 'THEN' after condition")?;
+        let then_branch = vec![self.parse_statement()?];
+
+        let else_branch = if self.check(&Token::Else) {
+            self.advance();
+            Some(vec![self.parse_statement()?])
+        } else {
+            None
+        };
+
+        Ok(StatementKind::Conditional { condition, then_branch, else_branch })
+    }
+
+    /// Parse `PARALLEL { ... }` with an optional `JOIN AT <name>`; each
+    /// statement in the block is its own branch
+    ///
+    /// `JOIN` and `AT` are matched as identifiers rather than keywords, so
+    /// they stay usable as names elsewhere.
+    fn parse_parallel(&mut self) -> Result<StatementKind, NaviLangError> {
+        self.consume(&Token::Parallel, "Expected 'PARALLEL'")?;
+        let body = self.parse_block("PARALLEL")?;
+
+        if body.is_empty() {
+            return Err(NaviLangError::syntax_error(
+                "PARALLEL block must contain at least one statement".to_string(),
+                self.source.clone(),
+                self.previous_span(),
+            ));
         }
+
+        let is_word = |token: Option<&TokenWithSpan>, word: &str| {
+            matches!(token.map(|t| &t.token), Some(Token::Identifier(name)) if name.eq_ignore_ascii_case(word))
+        };
+        let join = if is_word(self.tokens.get(self.current), "JOIN") && is_word(self.tokens.get(self.current + 1), "AT") {
+            self.advance();
+            self.advance();
+            Some(self.consume_identifier("Expected a join target after 'JOIN AT'")?)
+        } else {
+            None
+        };
+
+        Ok(StatementKind::Parallel {
+            branches: body.into_iter().map(|stmt| vec![stmt]).collect(),
+            join,
+        })
     }
-    
-    pub fn parse(&mut self) -> Result<ast::Program, NaviLangError> {
-        // TODO: Implement parsing
-        // For now, we use the fields to avoid warnings
-        let _token_count = self.tokens.len();
-        let _current_pos = self.current;
-        
-        Ok(ast::Program {
-            contexts: Vec::new(),
-            span: crate::error::Span::new(
-                crate::error::Position::new(1, 1, 0),
-                crate::error::Position::new(1, 1, 0)
+
+    /// Parse `BATCH { ... }` or `BATCH <size> { ... }`
+    fn parse_batch(&mut self) -> Result<StatementKind, NaviLangError> {
+        self.consume(&Token::Batch, "Expected 'BATCH'")?;
+
+        let size = match self.peek() {
+            Some(Token::Number(Some(size))) if *size > 0 => {
+                let size = *size;
+                self.advance();
+                Some(size)
+            }
+            Some(Token::Number(_)) => return Err(self.error_at_current("Batch size must be at least 1")),
+            _ => None,
+        };
+        let body = self.parse_block("BATCH")?;
+
+        Ok(StatementKind::Batch { size, body })
+    }
+
+    /// Parse a brace-delimited statement block following `keyword`
+    fn parse_block(&mut self, keyword: &str) -> Result<Vec<Statement>, NaviLangError> {
+        self.consume(&Token::LeftBrace, &format!("Expected '{{' after '{}'", keyword))?;
+
+        let statements = self.parse_body(None);
+
+        self.consume(&Token::RightBrace, &format!("Expected '}}' to close '{}' block", keyword))?;
+        Ok(statements)
+    }
+
+    /// Parse comparisons joined by `AND`/`OR`, with `AND` binding tighter
+    fn parse_condition(&mut self) -> Result<Condition, NaviLangError> {
+        self.parse_chain(&Token::Or, Self::parse_conjunction, Condition::Or)
+    }
+
+    fn parse_conjunction(&mut self) -> Result<Condition, NaviLangError> {
+        self.parse_chain(&Token::And, Self::parse_condition_operand, Condition::And)
+    }
+
+    /// Parse operands separated by `operator` into a left-deep tree
+    ///
+    /// Each operator puts the condition so far one level deeper, so a chain
+    /// counts against the nesting limit just as brackets do.
+    fn parse_chain(
+        &mut self,
+        operator: &Token,
+        operand: fn(&mut Self) -> Result<Condition, NaviLangError>,
+        join: fn(Box<Condition>, Box<Condition>) -> Condition,
+    ) -> Result<Condition, NaviLangError> {
+        let depth = self.depth;
+        let mut condition = operand(self);
+        while condition.is_ok() && self.check(operator) {
+            if self.depth >= self.max_depth {
+                condition = Err(self.depth_error());
+                break;
+            }
+            self.depth += 1;
+            self.advance();
+            condition = condition.and_then(|left| Ok(join(Box::new(left), Box::new(operand(self)?))));
+        }
+        self.depth = depth;
+        condition
+    }
+
+    /// Parse a single comparison or a parenthesized condition
+    fn parse_condition_operand(&mut self) -> Result<Condition, NaviLangError> {
+        if self.check(&Token::LeftParen) {
+            self.advance();
+            let condition = self.nested(Self::parse_condition)?;
+            self.consume(&Token::RightParen, "Expected ')' to close condition")?;
+            return Ok(condition);
+        }
+        self.parse_predicate().map(Condition::Leaf)
+    }
+
+    /// Parse `<identifier> <comparison> <value>`
+    fn parse_predicate(&mut self) -> Result<Predicate, NaviLangError> {
+        let start = self.current_span();
+        let left = self.consume_identifier("Expected an identifier in condition")?;
+
+        let comparison = match self.peek() {
+            Some(Token::Is) => Comparison::Is,
+            Some(Token::Equals) => Comparison::Equals,
+            Some(Token::NotEquals) => Comparison::NotEquals,
+            Some(Token::LessThan) => Comparison::LessThan,
+            Some(Token::GreaterThan) => Comparison::GreaterThan,
+            Some(Token::LessEqual) => Comparison::LessEqual,
+            Some(Token::GreaterEqual) => Comparison::GreaterEqual,
+            _ => return Err(self.error_at_current("Expected a comparison operator")),
+        };
+        self.advance();
+
+        let right = self.parse_value()?;
+
+        Ok(Predicate {
+            left,
+            comparison,
+            right,
+            span: start.combine(&self.previous_span()),
+        })
+    }
+
+    /// Parse a literal or identifier in value position
+    fn parse_value(&mut self) -> Result<Value, NaviLangError> {
+        let value = match self.peek() {
+            Some(Token::Identifier(_)) => {
+                return self.consume_identifier("Expected a value").map(Value::Identifier);
+            }
+            Some(Token::QuotedString(s)) => Value::String(s.clone()),
+            Some(Token::Number(Some(n))) => Value::Number(*n),
+            Some(Token::Float(Some(f))) => Value::Float(*f),
+            Some(Token::True) => Value::Boolean(true),
+            Some(Token::False) => Value::Boolean(false),
+            Some(Token::Duration(text)) => Value::Duration(
+                text.parse().map_err(|e: ParseDurationError| self.error_at_current(&e.to_string()))?,
             ),
+            Some(Token::LeftBracket) => return self.parse_list(Self::parse_value),
+            Some(Token::Number(None)) | Some(Token::Float(None)) => {
+                return Err(self.error_at_current("Invalid numeric literal"));
+            }
+            _ => return Err(self.error_at_current("Expected a value")),
+        };
+        self.advance();
+        Ok(value)
+    }
+
+    /// Parse `[<item>, ...]`, reading each item with `parse_item`; a trailing
+    /// comma is allowed
+    fn parse_list(
+        &mut self,
+        parse_item: fn(&mut Self) -> Result<Value, NaviLangError>,
+    ) -> Result<Value, NaviLangError> {
+        self.consume(&Token::LeftBracket, "Expected '['")?;
+
+        let mut items = Vec::new();
+        while !self.check(&Token::RightBracket) {
+            items.push(self.nested(parse_item)?);
+            if !self.check(&Token::Comma) {
+                break;
+            }
+            self.advance();
+        }
+
+        self.consume(&Token::RightBracket, "Expected ',' or ']' in list")?;
+        Ok(Value::List(items))
+    }
+
+    /// Parse `{ <key>: <value>, ... }`; a trailing comma is allowed
+    ///
+    /// Field values, and the items of lists among them, may also be type
+    /// names (`token: String`) or nested objects.
+    fn parse_object(&mut self) -> Result<Value, NaviLangError> {
+        self.consume(&Token::LeftBrace, "Expected '{'")?;
+
+        let mut fields = Vec::new();
+        while !self.check(&Token::RightBrace) {
+            let key = match self.peek() {
+                Some(Token::Identifier(key)) => key.clone(),
+                _ => return Err(self.error_at_current("Expected a field name")),
+            };
+            self.advance();
+            self.consume(&Token::Colon, &format!("Expected ':' after field '{}'", key))?;
+            fields.push((key, self.nested(Self::parse_field_value)?));
+
+            if !self.check(&Token::Comma) {
+                break;
+            }
+            self.advance();
+        }
+
+        self.consume(&Token::RightBrace, "Expected ',' or '}' in object")?;
+        Ok(Value::Object(fields))
+    }
+
+    fn parse_field_value(&mut self) -> Result<Value, NaviLangError> {
+        let list_type = self.check(&Token::LeftBracket)
+            && self.tokens.get(self.current + 1).is_some_and(|next| next.token.is_type());
+
+        if list_type || self.peek().is_some_and(Token::is_type) {
+            self.parse_type_annotation().map(Value::Type)
+        } else if self.check(&Token::LeftBrace) {
+            self.parse_object()
+        } else if self.check(&Token::LeftBracket) {
+            self.parse_list(Self::parse_field_value)
+        } else {
+            self.parse_value()
+        }
+    }
+
+    /// Parse a statement that starts with its subject identifier
+    fn parse_subject_statement(&mut self) -> Result<StatementKind, NaviLangError> {
+        let subject = self.consume_identifier("Expected an identifier")?;
+
+        match self.peek() {
+            Some(Token::Goes) => {
+                self.advance();
+                self.consume(&Token::To, "Expected 'TO' after 'GOES'")?;
+                self.parse_flow_target(subject)
+            }
+            Some(Token::Arrow) => {
+                self.advance();
+                self.parse_flow_target(subject)
+            }
+            Some(Token::Calls) => self.parse_interaction(subject, InteractionVerb::Calls),
+            Some(Token::Receives) => self.parse_interaction(subject, InteractionVerb::Receives),
+            Some(Token::Returns) => self.parse_interaction(subject, InteractionVerb::Returns),
+            Some(Token::Does) => {
+                self.advance();
+                self.parse_action(subject)
+            }
+            Some(Token::Uses) => {
+                self.advance();
+                let dependency = self.consume_identifier("Expected a dependency after 'USES'")?;
+                Ok(StatementKind::Uses { subject, dependency })
+            }
+            Some(Token::Created) => {
+                self.advance();
+                self.consume(&Token::By, "Expected 'BY' after 'CREATED'")?;
+                let creator = self.consume_identifier("Expected a creator after 'CREATED BY'")?;
+                Ok(StatementKind::Creation { subject, creator })
+            }
+            Some(Token::Before) => self.parse_ordering(subject, OrderRelation::Before),
+            Some(Token::After) => self.parse_ordering(subject, OrderRelation::After),
+            _ => Err(self.error_at_current(&format!("Expected a verb after '{}'", subject))),
+        }
+    }
+
+    /// Parse `ASYNC <interaction>`
+    fn parse_async_interaction(&mut self) -> Result<StatementKind, NaviLangError> {
+        self.consume(&Token::Async, "Expected 'ASYNC'")?;
+        if !matches!(self.peek(), Some(Token::Identifier(_))) {
+            return Err(self.error_at_current("Expected an interaction after 'ASYNC'"));
+        }
+        let start = self.current_span();
+
+        match self.parse_subject_statement()? {
+            StatementKind::Interaction { subject, verb, object, retry, timeout, is_async: false } => {
+                Ok(StatementKind::Interaction { subject, verb, object, retry, timeout, is_async: true })
+            }
+            kind => {
+                let message = match kind {
+                    StatementKind::Interaction { .. } => "Duplicate 'ASYNC' modifier",
+                    _ => "'ASYNC' can only modify CALLS, RECEIVES or RETURNS",
+                };
+                Err(NaviLangError::syntax_error(
+                    message.to_string(),
+                    self.source.clone(),
+                    start.combine(&self.previous_span()),
+                ))
+            }
+        }
+    }
+
+    fn parse_flow_target(&mut self, from: String) -> Result<StatementKind, NaviLangError> {
+        let to = self.consume_identifier("Expected a flow target")?;
+
+        let mut label = None;
+        if self.check(&Token::Labeled) {
+            self.advance();
+            match self.peek() {
+                Some(Token::QuotedString(text)) => label = Some(text.clone()),
+                _ => return Err(self.error_at_current("Expected a quoted label after 'LABELED'")),
+            }
+            self.advance();
+        }
+        Ok(StatementKind::Flow { from, to, label })
+    }
+
+    /// Parse the step after `BEFORE`/`AFTER`; the keyword is the current token
+    fn parse_ordering(&mut self, subject: String, relation: OrderRelation) -> Result<StatementKind, NaviLangError> {
+        let keyword = self.advance().token.to_string();
+        let other = self.consume_identifier(&format!("Expected a step after '{}'", keyword))?;
+        Ok(StatementKind::Ordering { subject, relation, other })
+    }
+
+    /// Parse the action after `DOES`; quoted actions may span several words
+    fn parse_action(&mut self, actor: String) -> Result<StatementKind, NaviLangError> {
+        let action = match self.peek() {
+            Some(Token::Identifier(action)) | Some(Token::QuotedString(action)) => action.clone(),
+            _ => return Err(self.error_at_current("Expected an action after 'DOES'")),
+        };
+        self.advance();
+
+        Ok(StatementKind::Action { actor, action })
+    }
+
+    /// Parse the object of an interaction verb; the verb is the current token
+    fn parse_interaction(
+        &mut self,
+        subject: String,
+        verb: InteractionVerb,
+    ) -> Result<StatementKind, NaviLangError> {
+        let verb_token = self.advance().token.to_string();
+
+        // A brace after RECEIVES or RETURNS is a payload, not a block
+        let payload = matches!(verb, InteractionVerb::Receives | InteractionVerb::Returns)
+            && self.check(&Token::LeftBrace);
+        if !payload && !self.peek().is_some_and(|t| t.is_literal() || matches!(t, Token::Identifier(_) | Token::LeftBracket)) {
+            return Err(self.error_at_current(&format!("Expected an object after '{}'", verb_token)));
+        }
+        let object = if payload { self.parse_object()? } else { self.parse_value()? };
+
+        let mut retry = None;
+        let mut timeout = None;
+        let mut is_async = false;
+        loop {
+            match self.peek() {
+                // A trailing ASYNC that begins the next statement is left for it
+                Some(Token::Async) if !self.starts_subject_statement(self.current + 1) => {
+                    if is_async {
+                        return Err(self.error_at_current("Duplicate 'ASYNC' modifier"));
+                    }
+                    self.advance();
+                    is_async = true;
+                }
+                Some(Token::Retry) => {
+                    if retry.is_some() {
+                        return Err(self.error_at_current("Duplicate 'RETRY' clause"));
+                    }
+                    self.advance();
+                    retry = Some(self.parse_retry_count()?);
+                }
+                Some(Token::Timeout) => {
+                    if timeout.is_some() {
+                        return Err(self.error_at_current("Duplicate 'TIMEOUT' clause"));
+                    }
+                    self.advance();
+                    timeout = Some(self.parse_duration()?);
+                }
+                _ => break,
+            }
+        }
+
+        Ok(StatementKind::Interaction { subject, verb, object, retry, timeout, is_async })
+    }
+
+    fn parse_retry_count(&mut self) -> Result<i64, NaviLangError> {
+        match self.peek() {
+            Some(Token::Number(Some(count))) if *count >= 0 => {
+                let count = *count;
+                self.advance();
+                Ok(count)
+            }
+            _ => Err(self.error_at_current("Expected a retry count after 'RETRY'")),
+        }
+    }
+
+    fn parse_duration(&mut self) -> Result<Duration, NaviLangError> {
+        let duration = match self.peek() {
+            Some(Token::Duration(text)) => text.parse::<Duration>()
+                .map_err(|e| self.error_at_current(&e.to_string())),
+            _ => Err(self.error_at_current("Expected a duration after 'TIMEOUT'")),
+        }?;
+        self.advance();
+        Ok(duration)
+    }
+
+    fn depth_error(&self) -> NaviLangError {
+        let message = format!("Maximum nesting depth of {} exceeded", self.max_depth);
+        NaviLangError::syntax_error(message, self.source.clone(), self.current_span())
+    }
+
+    /// Run `parse` one nesting level deeper
+    ///
+    /// Past the maximum depth this fails instead, after skipping to the
+    /// bracket that closes the current level: everything inside is nested
+    /// too deeply as well, and the enclosing levels can then close normally.
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, NaviLangError>) -> Result<T, NaviLangError> {
+        if self.depth >= self.max_depth {
+            let error = self.depth_error();
+            let mut open = 0usize;
+            while let Some(token) = self.peek() {
+                match token {
+                    Token::LeftBrace | Token::LeftBracket | Token::LeftParen => open += 1,
+                    Token::RightBrace | Token::RightBracket | Token::RightParen if open == 0 => break,
+                    Token::RightBrace | Token::RightBracket | Token::RightParen => open -= 1,
+                    _ => {}
+                }
+                self.advance();
+            }
+            return Err(error);
+        }
+
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    // Error recovery
+
+    /// Skip to the next statement boundary after an error in the statement
+    /// that began at token index `start`
+    fn synchronize_statement(&mut self, start: usize) {
+        // Always make progress, even if the statement failed on its first token
+        if self.current == start {
+            self.advance();
+        }
+
+        while !self.is_at_end() && !self.at_statement_boundary() {
+            self.advance();
+        }
+    }
+
+    /// Skip to the next `CONTEXT` keyword outside of any braces
+    fn synchronize_context(&mut self) {
+        let mut depth = 0usize;
+
+        while !self.is_at_end() {
+            match self.peek() {
+                Some(Token::Context | Token::Include) if depth == 0 => return,
+                Some(Token::LeftBrace) => depth += 1,
+                Some(Token::RightBrace) => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            self.advance();
+        }
+    }
+
+    /// Whether the current token can close a block or begin a new statement
+    fn at_statement_boundary(&self) -> bool {
+        match self.peek() {
+            Some(Token::RightBrace | Token::Context) => true,
+            Some(Token::Identifier(_)) => self.starts_subject_statement(self.current),
+            Some(Token::Async) => self.starts_subject_statement(self.current + 1),
+            // `Endpoint` is also a type name, so only count it when a method follows
+            Some(Token::Endpoint) => self.tokens.get(self.current + 1)
+                .is_some_and(|next| matches!(next.token, Token::Identifier(_))),
+            Some(token) => matches!(token,
+                Token::Var | Token::If | Token::Parallel | Token::Batch | Token::Loop |
+                Token::While | Token::Break | Token::Continue | Token::Start
+            ),
+            None => true,
+        }
+    }
+
+    /// Whether the token at `index` is an identifier followed by a statement verb
+    ///
+    /// Identifier-led statements are recognized by the verb that follows.
+    fn starts_subject_statement(&self, index: usize) -> bool {
+        let is_identifier = self.tokens.get(index)
+            .is_some_and(|t| matches!(t.token, Token::Identifier(_)));
+        is_identifier && self.tokens.get(index + 1).is_some_and(|next| {
+            matches!(next.token,
+                Token::Goes | Token::Arrow | Token::Calls | Token::Receives | Token::Returns |
+                Token::Does | Token::Uses | Token::Created | Token::Before | Token::After
+            )
+        })
+    }
+
+    // Helper methods
+
+    /// Consume a token of the given kind, returning its span
+    fn consume(&mut self, expected: &Token, message: &str) -> Result<Span, NaviLangError> {
+        if self.check(expected) {
+            Ok(self.advance().span.clone())
+        } else {
+            Err(self.error_at_current(message))
+        }
+    }
+
+    /// Consume an identifier, recording it for the statement being parsed
+    fn consume_identifier(&mut self, message: &str) -> Result<String, NaviLangError> {
+        match self.peek() {
+            Some(Token::Identifier(name)) => {
+                let name = name.clone();
+                let span = self.advance().span.clone();
+                self.names.push(Name { name: name.clone(), span });
+                Ok(name)
+            }
+            _ => Err(self.error_at_current(message)),
+        }
+    }
+
+    /// Check whether the current token has the same kind as `expected`
+    /// (payloads of data-carrying tokens are ignored)
+    fn check(&self, expected: &Token) -> bool {
+        self.peek().is_some_and(|token| {
+            std::mem::discriminant(token) == std::mem::discriminant(expected)
         })
     }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.current).map(|t| &t.token)
+    }
+
+    /// Comments the lexer attached to the current token as trivia
+    fn leading_comments(&self) -> Vec<String> {
+        self.tokens.get(self.current)
+            .map(|t| t.leading_comments.clone())
+            .unwrap_or_default()
+    }
+
+    fn advance(&mut self) -> &TokenWithSpan {
+        if !self.is_at_end() {
+            self.current += 1;
+        }
+        &self.tokens[self.current - 1]
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.current >= self.tokens.len()
+    }
+
+    /// Span of the current token, or an empty span at end of input
+    fn current_span(&self) -> Span {
+        match self.tokens.get(self.current) {
+            Some(token) => token.span.clone(),
+            None => {
+                let end = self.tokens.last()
+                    .map(|t| t.span.end)
+                    .unwrap_or(Position::new(1, 1, 0));
+                Span::new(end, end)
+            }
+        }
+    }
+
+    /// Span of the most recently consumed token
+    fn previous_span(&self) -> Span {
+        match self.current.checked_sub(1) {
+            Some(index) => self.tokens[index].span.clone(),
+            None => self.current_span(),
+        }
+    }
+
+    fn program_span(&self) -> Span {
+        match (self.tokens.first(), self.tokens.last()) {
+            (Some(first), Some(last)) => first.span.combine(&last.span),
+            _ => {
+                let start = Position::new(1, 1, 0);
+                Span::new(start, start)
+            }
+        }
+    }
+
+    /// Build a syntax error pointing at the current token
+    fn error_at_current(&self, message: &str) -> NaviLangError {
+        let found = match self.peek() {
+            Some(token) => format!("'{}'", token),
+            None => "end of input".to_string(),
+        };
+        NaviLangError::syntax_error(
+            format!("{}, found {}", message, found),
+            self.source.clone(),
+            self.current_span(),
+        )
+    }
+}
+
+/// Lex and parse `input`, comments included, panicking on any error
+///
+/// Shared by the unit tests of every pass that starts from a `Program`.
+#[cfg(test)]
+pub(crate) fn parse_program(input: &str) -> Program {
+    let tokens = crate::lexer::Lexer::new(input).tokenize_with_trivia().unwrap();
+    Parser::new(tokens).with_source(input).parse().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+
+    fn parse(input: &str) -> Result<Program, NaviLangError> {
+        let tokens = Lexer::new(input).tokenize_with_trivia().unwrap();
+        Parser::new(tokens).with_source(input).parse()
+    }
+
+    #[test]
+    fn test_parse_empty_program() {
+        let program = parse("").unwrap();
+        assert!(program.contexts.is_empty());
+    }
+
+    #[test]
+    fn test_parse_two_contexts() {
+        let input = r#"
+CONTEXT "User Authentication" {
+}
+
+CONTEXT OrderProcessing {}
+"#;
+        let program = parse(input).unwrap();
+
+        assert_eq!(program.contexts.len(), 2);
+        assert_eq!(program.contexts[0].name, "User Authentication");
+        assert_eq!(program.contexts[1].name, "OrderProcessing");
+
+        // The span runs from the CONTEXT keyword through the closing brace
+        let span = &program.contexts[0].span;
+        assert_eq!((span.start.line, span.start.column), (2, 1));
+        assert_eq!((span.end.line, span.end.column), (3, 2));
+        assert_eq!(program.span.end, program.contexts[1].span.end);
+    }
+
+    fn first_statement(input: &str) -> Statement {
+        let program = parse(input).unwrap();
+        program.contexts[0].statements[0].clone()
+    }
+
+    #[test]
+    fn test_parse_untyped_var() {
+        let stmt = first_statement("CONTEXT Test { VAR User }");
+
+        assert_eq!(stmt.kind, StatementKind::VarDecl {
+            name: "User".to_string(),
+            type_annotation: None,
+        });
+        assert_eq!((stmt.span.start.offset, stmt.span.end.offset), (15, 23));
+    }
+
+    #[test]
+    fn test_parse_typed_var() {
+        let stmt = first_statement("CONTEXT Test { VAR User:Entity }");
+
+        assert_eq!(stmt.kind, StatementKind::VarDecl {
+            name: "User".to_string(),
+            type_annotation: Some(TypeAnnotation::Entity),
+        });
+        assert_eq!((stmt.span.start.offset, stmt.span.end.offset), (15, 30));
+    }
+
+    #[test]
+    fn test_parse_all_type_annotations() {
+        let input = "CONTEXT Test {
+            VAR A:Entity VAR B:Service VAR C:Endpoint VAR D:Object
+            VAR E:String VAR F:Number VAR G:Boolean
+        }";
+        let program = parse(input).unwrap();
+        let types: Vec<_> = program.contexts[0].statements.iter()
+            .map(|s| match &s.kind {
+                StatementKind::VarDecl { type_annotation, .. } => type_annotation.clone().unwrap(),
+                other => panic!("Expected VarDecl, got {:?}", other),
+            })
+            .collect();
+
+        assert_eq!(types, vec![
+            TypeAnnotation::Entity, TypeAnnotation::Service, TypeAnnotation::Endpoint,
+            TypeAnnotation::Object, TypeAnnotation::String, TypeAnnotation::Number,
+            TypeAnnotation::Boolean,
+        ]);
+    }
+
+    #[test]
+    fn test_parse_list_type() {
+        let stmt = first_statement("CONTEXT Test { VAR Roles:[String] }");
+        assert_eq!(stmt.kind, StatementKind::VarDecl {
+            name: "Roles".to_string(),
+            type_annotation: Some(TypeAnnotation::List(Box::new(TypeAnnotation::String))),
+        });
+
+        let stmt = first_statement("CONTEXT Test { VAR Matrix:[[Number]] }");
+        let StatementKind::VarDecl { type_annotation: Some(annotation), .. } = &stmt.kind else {
+            panic!("Expected typed VarDecl, got {:?}", stmt.kind);
+        };
+        assert_eq!(annotation.to_string(), "[[Number]]");
+
+        let err = parse("CONTEXT Test { VAR Roles:[String }").unwrap_err();
+        assert!(format!("{:?}", err).contains("Expected ']' after list element type"));
+    }
+
+    #[test]
+    fn test_unknown_type_annotation() {
+        let result = parse("CONTEXT Test { VAR User:Person }");
+
+        if let Err(NaviLangError::SyntaxError { message, span, .. }) = result {
+            assert!(message.contains("Expected a type annotation"));
+            assert_eq!(span.offset(), 24);
+        } else {
+            panic!("Expected SyntaxError for unknown type");
+        }
+    }
+
+    #[test]
+    fn test_parse_flow() {
+        let stmt = first_statement("CONTEXT Test { User GOES TO Dashboard }");
+
+        assert_eq!(stmt.kind, StatementKind::Flow {
+            from: "User".to_string(),
+            to: "Dashboard".to_string(),
+            label: None,
+        });
+        assert_eq!((stmt.span.start.offset, stmt.span.end.offset), (15, 37));
+    }
+
+    #[test]
+    fn test_parse_created_by() {
+        let stmt = first_statement("CONTEXT Test { Order created by User }");
+
+        assert_eq!(stmt.kind, StatementKind::Creation {
+            subject: "Order".to_string(),
+            creator: "User".to_string(),
+        });
+        assert_eq!((stmt.span.start.offset, stmt.span.end.offset), (15, 36));
+
+        let result = parse("CONTEXT Test { Order CREATED User }");
+        let Err(NaviLangError::SyntaxError { message, .. }) = result else {
+            panic!("Expected SyntaxError for missing BY, got {:?}", result);
+        };
+        assert!(message.contains("Expected 'BY' after 'CREATED'"));
+    }
+
+    #[test]
+    fn test_parse_arrow_flow() {
+        let stmt = first_statement("CONTEXT Test { User -> Dashboard }");
+
+        assert_eq!(stmt.kind, StatementKind::Flow {
+            from: "User".to_string(),
+            to: "Dashboard".to_string(),
+            label: None,
+        });
+    }
+
+    #[test]
+    fn test_parse_statement_attributes() {
+        let input = r#"CONTEXT Test { Api CALLS Db [retry=3, priority=high, owner="ops",] }"#;
+        let stmt = first_statement(input);
+
+        let attributes: Vec<(&str, &Value)> = stmt.attributes.iter()
+            .map(|attribute| (attribute.key.as_str(), &attribute.value))
+            .collect();
+        assert_eq!(attributes, vec![
+            ("retry", &Value::Number(3)),
+            ("priority", &Value::Identifier("high".to_string())),
+            ("owner", &Value::String("ops".to_string())),
+        ]);
+        assert_eq!(stmt.attributes[0].span.start.offset, input.find("retry").unwrap());
+        assert_eq!(stmt.span.end.offset, input.find(" }").unwrap());
+        // Attribute values are not names the statement refers to
+        assert!(!stmt.names.iter().any(|name| name.name == "high"));
+
+        let result = parse("CONTEXT Test { User -> Home [priority high] }");
+        let Err(NaviLangError::SyntaxError { message, .. }) = result else {
+            panic!("Expected SyntaxError, got {:?}", result);
+        };
+        assert!(message.contains("Expected '=' after attribute 'priority'"));
+    }
+
+    #[test]
+    fn test_parse_labeled_flow() {
+        let stmt = first_statement(r#"CONTEXT Test { User GOES TO Dashboard LABELED "after login" }"#);
+
+        assert_eq!(stmt.kind, StatementKind::Flow {
+            from: "User".to_string(),
+            to: "Dashboard".to_string(),
+            label: Some("after login".to_string()),
+        });
+        assert_eq!(stmt.span.end.offset, 59);
+
+        let result = parse("CONTEXT Test { User -> Dashboard LABELED Home }");
+        let Err(NaviLangError::SyntaxError { message, .. }) = result else {
+            panic!("Expected SyntaxError for unquoted label, got {:?}", result);
+        };
+        assert!(message.contains("Expected a quoted label after 'LABELED'"));
+    }
+
+    #[test]
+    fn test_flow_missing_to() {
+        let result = parse("CONTEXT Test { User GOES Dashboard }");
+
+        if let Err(NaviLangError::SyntaxError { message, span, .. }) = result {
+            assert!(message.contains("Expected 'TO' after 'GOES'"));
+            assert!(message.contains("Dashboard"));
+            assert_eq!(span.offset(), 25);
+        } else {
+            panic!("Expected SyntaxError for missing TO");
+        }
+    }
+
+    #[test]
+    fn test_parse_conditional() {
+        let input = r#"CONTEXT Test { IF User IS "valid" THEN User GOES TO Dashboard }"#;
+        let stmt = first_statement(input);
+
+        let StatementKind::Conditional { condition, then_branch, else_branch } = &stmt.kind else {
+            panic!("Expected Conditional, got {:?}", stmt.kind);
+        };
+        let Condition::Leaf(condition) = condition else {
+            panic!("Expected a single comparison, got {:?}", condition);
+        };
+        assert_eq!(condition.left, "User");
+        assert_eq!(condition.comparison, Comparison::Is);
+        assert_eq!(condition.right, Value::String("valid".to_string()));
+        assert_eq!(then_branch.len(), 1);
+        assert_eq!(then_branch[0].kind, StatementKind::Flow {
+            from: "User".to_string(),
+            to: "Dashboard".to_string(),
+            label: None,
+        });
+        assert_eq!(stmt.span.end, then_branch[0].span.end);
+        assert!(else_branch.is_none());
+    }
+
+    #[test]
+    fn test_parse_else_branch() {
+        let input = r#"CONTEXT Test { IF User IS "valid" THEN User GOES TO Dashboard ELSE User GOES TO LoginPage }"#;
+        let stmt = first_statement(input);
+
+        let StatementKind::Conditional { then_branch, else_branch: Some(else_branch), .. } = &stmt.kind else {
+            panic!("Expected Conditional with ELSE, got {:?}", stmt.kind);
+        };
+        assert_eq!(then_branch.len(), 1);
+        assert_eq!(else_branch[0].kind, StatementKind::Flow {
+            from: "User".to_string(),
+            to: "LoginPage".to_string(),
+            label: None,
+        });
+        assert_eq!(stmt.span.end, else_branch[0].span.end);
+
+        // A dangling ELSE belongs to the innermost IF
+        let stmt = first_statement("CONTEXT Test { IF A IS 1 THEN IF B IS 2 THEN A -> B ELSE A -> C }");
+        let StatementKind::Conditional { then_branch, else_branch: None, .. } = &stmt.kind else {
+            panic!("Expected the outer IF without ELSE, got {:?}", stmt.kind);
+        };
+        assert!(matches!(&then_branch[0].kind, StatementKind::Conditional { else_branch: Some(_), .. }));
+
+        let message = parse("CONTEXT Test { IF A IS 1 THEN A -> B ELSE }").unwrap_err().to_string();
+        assert!(message.contains("Expected a statement"), "{}", message);
+    }
+
+    #[test]
+    fn test_comparison_operators() {
+        let cases = [
+            ("Count >= 5", Comparison::GreaterEqual, Value::Number(5)),
+            (r#"Name != "admin""#, Comparison::NotEquals, Value::String("admin".to_string())),
+            ("Score < 0.5", Comparison::LessThan, Value::Float(0.5)),
+            ("Count > Limit", Comparison::GreaterThan, Value::Identifier("Limit".to_string())),
+            ("Count <= -1", Comparison::LessEqual, Value::Number(-1)),
+            ("Done = true", Comparison::Equals, Value::Boolean(true)),
+        ];
+
+        for (source, comparison, right) in cases {
+            let input = format!("CONTEXT Test {{ IF {} THEN User -> Home }}", source);
+            let StatementKind::Conditional { condition, .. } = first_statement(&input).kind else {
+                panic!("Expected Conditional for: {}", source);
+            };
+            assert_eq!(condition.to_string(), source);
+            let Condition::Leaf(condition) = condition else {
+                panic!("Expected a single comparison for: {}", source);
+            };
+            assert_eq!(condition.comparison, comparison, "{}", source);
+            assert_eq!(condition.right, right, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_and_binds_tighter_than_or() {
+        let input = r#"CONTEXT Test { IF A IS 1 OR B IS 2 AND C IS 3 THEN User -> Home }"#;
+        let StatementKind::Conditional { condition, .. } = first_statement(input).kind else {
+            panic!("Expected Conditional");
+        };
+
+        let Condition::Or(left, right) = &condition else {
+            panic!("Expected OR at the root, got {:?}", condition);
+        };
+        assert!(matches!(left.as_ref(), Condition::Leaf(p) if p.left == "A"));
+        let Condition::And(b, c) = right.as_ref() else {
+            panic!("Expected AND on the right, got {:?}", right);
+        };
+        assert!(matches!(b.as_ref(), Condition::Leaf(p) if p.left == "B"));
+        assert!(matches!(c.as_ref(), Condition::Leaf(p) if p.left == "C"));
+
+        let names: Vec<_> = condition.predicates().iter().map(|p| p.left.as_str()).collect();
+        assert_eq!(names, vec!["A", "B", "C"]);
+        assert_eq!(&input[condition.span().start.offset..condition.span().end.offset], "A IS 1 OR B IS 2 AND C IS 3");
+    }
+
+    #[test]
+    fn test_condition_chains_are_left_associative() {
+        let cases = [
+            ("A IS 1 AND B IS 2 OR C IS 3", "Or(And(A, B), C)"),
+            ("A IS 1 AND B IS 2 AND C IS 3", "And(And(A, B), C)"),
+            ("A IS 1 OR B IS 2 OR C IS 3", "Or(Or(A, B), C)"),
+            ("A IS 1 AND (B IS 2 OR C IS 3)", "And(A, Or(B, C))"),
+        ];
+
+        fn shape(condition: &Condition) -> String {
+            match condition {
+                Condition::Leaf(predicate) => predicate.left.clone(),
+                Condition::And(l, r) => format!("And({}, {})", shape(l), shape(r)),
+                Condition::Or(l, r) => format!("Or({}, {})", shape(l), shape(r)),
+            }
+        }
+
+        for (source, expected) in cases {
+            let input = format!("CONTEXT Test {{ WHILE {} {{ BREAK }} }}", source);
+            let StatementKind::Loop { condition: Some(condition), .. } = first_statement(&input).kind else {
+                panic!("Expected WHILE for: {}", source);
+            };
+            assert_eq!(shape(&condition), expected, "{}", source);
+            assert_eq!(condition.to_string(), source);
+        }
+
+        let message = parse("CONTEXT Test { IF (A IS 1 THEN BREAK }").unwrap_err().to_string();
+        assert!(message.contains("Expected ')' to close condition"), "{}", message);
+    }
+
+    #[test]
+    fn test_conditional_missing_then() {
+        let result = parse(r#"CONTEXT Test { IF User IS "valid" User GOES TO Dashboard }"#);
+
+        if let Err(NaviLangError::SyntaxError { message, .. }) = result {
+            assert!(message.contains("Expected 'THEN'"));
+        } else {
+            panic!("Expected SyntaxError for missing THEN");
+        }
+    }
+
+    #[test]
+    fn test_parse_parallel() {
+        let input = "CONTEXT Test {
+    PARALLEL {
+        Service1 GOES TO ProcessA
+        Service2 GOES TO ProcessB
+    }
+}";
+        let stmt = first_statement(input);
+
+        let StatementKind::Parallel { branches, .. } = &stmt.kind else {
+            panic!("Expected Parallel, got {:?}", stmt.kind);
+        };
+        assert_eq!(branches.len(), 2);
+        assert_eq!(branches[0][0].kind, StatementKind::Flow {
+            from: "Service1".to_string(),
+            to: "ProcessA".to_string(),
+            label: None,
+        });
+        assert_eq!(branches[1][0].kind, StatementKind::Flow {
+            from: "Service2".to_string(),
+            to: "ProcessB".to_string(),
+            label: None,
+        });
+        assert_eq!((stmt.span.start.line, stmt.span.end.line), (2, 5));
+    }
+
+    #[test]
+    fn test_parse_parallel_join() {
+        let input = "CONTEXT Test {\n    PARALLEL { A -> Done  B -> Done } join at Done\n    Done -> Exit\n}";
+        let program = parse(input).unwrap();
+        let statements = &program.contexts[0].statements;
+
+        let StatementKind::Parallel { branches, join } = &statements[0].kind else {
+            panic!("Expected Parallel, got {:?}", statements[0].kind);
+        };
+        assert_eq!(branches.len(), 2);
+        assert_eq!(join.as_deref(), Some("Done"));
+        assert_eq!(statements[0].span.end.offset, input.find("Done\n").unwrap() + "Done".len());
+        assert_eq!(statements.len(), 2);
+
+        // Without AT, `Join` starts the next statement
+        let program = parse("CONTEXT Test { PARALLEL { A -> B } Join -> C }").unwrap();
+        let statements = &program.contexts[0].statements;
+        assert!(matches!(&statements[0].kind, StatementKind::Parallel { join: None, .. }));
+        assert_eq!(statements.len(), 2);
+
+        let err = parse("CONTEXT Test { PARALLEL { A -> B } JOIN AT }").unwrap_err();
+        assert!(format!("{:?}", err).contains("Expected a join target after 'JOIN AT'"));
+    }
+
+    #[test]
+    fn test_parse_nested_parallel() {
+        let input = "CONTEXT Test { PARALLEL { A -> B PARALLEL { C -> D E -> F } } }";
+        let stmt = first_statement(input);
+
+        let StatementKind::Parallel { branches, .. } = &stmt.kind else {
+            panic!("Expected Parallel, got {:?}", stmt.kind);
+        };
+        assert_eq!(branches.len(), 2);
+        let StatementKind::Parallel { branches: inner, .. } = &branches[1][0].kind else {
+            panic!("Expected nested Parallel");
+        };
+        assert_eq!(inner.len(), 2);
+        assert_eq!(stmt.span.end.offset, input.len() - 2);
+    }
+
+    #[test]
+    fn test_empty_parallel_is_rejected() {
+        let result = parse("CONTEXT Test { PARALLEL { } }");
+        assert!(matches!(result, Err(NaviLangError::SyntaxError { .. })));
+    }
+
+    #[test]
+    fn test_parse_while_loop() {
+        let input = r#"CONTEXT Test {
+    WHILE User IS "active" {
+        User GOES TO Dashboard
+        CONTINUE
+    }
+}"#;
+        let stmt = first_statement(input);
+
+        let StatementKind::Loop { condition, body } = &stmt.kind else {
+            panic!("Expected Loop, got {:?}", stmt.kind);
+        };
+        let Some(Condition::Leaf(condition)) = condition else {
+            panic!("Expected a single comparison, got {:?}", condition);
+        };
+        assert_eq!(condition.left, "User");
+        assert_eq!(condition.right, Value::String("active".to_string()));
+        assert_eq!(body.len(), 2);
+        assert_eq!(body[1].kind, StatementKind::Continue);
+        assert_eq!((stmt.span.start.line, stmt.span.end.line), (2, 5));
+    }
+
+    #[test]
+    fn test_parse_bare_loop() {
+        let stmt = first_statement("CONTEXT Test { LOOP { Poller -> Queue BREAK } }");
+
+        let StatementKind::Loop { condition, body } = &stmt.kind else {
+            panic!("Expected Loop, got {:?}", stmt.kind);
+        };
+        assert!(condition.is_none());
+        assert_eq!(body.len(), 2);
+        assert_eq!(body[1].kind, StatementKind::Break);
+    }
+
+    #[test]
+    fn test_parse_list_value() {
+        let stmt = first_statement(r#"CONTEXT Test { User RECEIVES ["a", "b",] }"#);
+        let StatementKind::Interaction { object, .. } = &stmt.kind else {
+            panic!("Expected Interaction, got {:?}", stmt.kind);
+        };
+        assert_eq!(object, &Value::List(vec![
+            Value::String("a".to_string()),
+            Value::String("b".to_string()),
+        ]));
+
+        let stmt = first_statement("CONTEXT Test { IF Role IS [Admin, [1, true]] THEN User -> Home }");
+        let StatementKind::Conditional { condition, .. } = &stmt.kind else {
+            panic!("Expected Conditional, got {:?}", stmt.kind);
+        };
+        assert_eq!(condition.predicates()[0].right, Value::List(vec![
+            Value::Identifier("Admin".to_string()),
+            Value::List(vec![Value::Number(1), Value::Boolean(true)]),
+        ]));
+
+        assert_eq!(first_statement("CONTEXT Test { User RECEIVES [] }").kind, StatementKind::Interaction {
+            subject: "User".to_string(),
+            verb: InteractionVerb::Receives,
+            object: Value::List(Vec::new()),
+            retry: None,
+            timeout: None,
+            is_async: false,
+        });
+
+        let err = parse(r#"CONTEXT Test { User RECEIVES ["a" "b"] }"#).unwrap_err();
+        assert!(format!("{:?}", err).contains("Expected ',' or ']' in list"));
+    }
+
+    #[test]
+    fn test_parse_object_payload() {
+        let input = "CONTEXT Test {
+    VAR Session
+    Session RETURNS { token: String, ttl: 30s, }
+    User RECEIVES { roles: [String], profile: { name: \"Ada\" } }
+}";
+        let program = parse(input).unwrap();
+        let statements = &program.contexts[0].statements;
+
+        let StatementKind::Interaction { object, .. } = &statements[1].kind else {
+            panic!("Expected Interaction, got {:?}", statements[1].kind);
+        };
+        assert_eq!(object, &Value::Object(vec![
+            ("token".to_string(), Value::Type(TypeAnnotation::String)),
+            ("ttl".to_string(), Value::Duration("30s".parse().unwrap())),
+        ]));
+
+        let StatementKind::Interaction { object, .. } = &statements[2].kind else {
+            panic!("Expected Interaction, got {:?}", statements[2].kind);
+        };
+        assert_eq!(object.to_string(), r#"{ roles: [String], profile: { name: "Ada" } }"#);
+
+        // CALLS has no payload, so the brace is not an object
+        let err = parse("CONTEXT Test { User CALLS { a: 1 } }").unwrap_err();
+        assert!(format!("{:?}", err).contains("Expected an object after 'CALLS'"));
+        let err = parse("CONTEXT Test { User RETURNS { a 1 } }").unwrap_err();
+        assert!(format!("{:?}", err).contains("Expected ':' after field 'a'"));
+    }
+
+    #[test]
+    fn test_parse_interactions() {
+        let input = r#"CONTEXT Test {
+    User CALLS AuthService
+    PaymentService RECEIVES "payment_data"
+    AuthService RETURNS Session
+}"#;
+        let program = parse(input).unwrap();
+        let statements = &program.contexts[0].statements;
+
+        assert_eq!(statements[0].kind, StatementKind::Interaction {
+            subject: "User".to_string(),
+            verb: InteractionVerb::Calls,
+            object: Value::Identifier("AuthService".to_string()),
+            retry: None,
+            timeout: None,
+            is_async: false,
+        });
+        assert_eq!(statements[1].kind, StatementKind::Interaction {
+            subject: "PaymentService".to_string(),
+            verb: InteractionVerb::Receives,
+            object: Value::String("payment_data".to_string()),
+            retry: None,
+            timeout: None,
+            is_async: false,
+        });
+        assert_eq!(statements[2].kind, StatementKind::Interaction {
+            subject: "AuthService".to_string(),
+            verb: InteractionVerb::Returns,
+            object: Value::Identifier("Session".to_string()),
+            retry: None,
+            timeout: None,
+            is_async: false,
+        });
+
+        // Spans cover subject through object
+        let span = &statements[0].span;
+        assert_eq!(&input[span.start.offset..span.end.offset], "User CALLS AuthService");
+        let span = &statements[1].span;
+        assert_eq!(&input[span.start.offset..span.end.offset], r#"PaymentService RECEIVES "payment_data""#);
+    }
+
+    #[test]
+    fn test_dangling_interaction_verb() {
+        let result = parse("CONTEXT Test { User CALLS }");
+
+        if let Err(NaviLangError::SyntaxError { message, span, .. }) = result {
+            assert!(message.contains("Expected an object after 'CALLS'"));
+            assert_eq!(span.offset(), 26);
+        } else {
+            panic!("Expected SyntaxError for dangling verb");
+        }
+    }
+
+    #[test]
+    fn test_parse_nested_context() {
+        let input = r#"CONTEXT Shop {
+    VAR Cart:Entity
+    CONTEXT "Checkout" {
+        Cart GOES TO Payment
+    }
+}"#;
+        let program = parse(input).unwrap();
+
+        assert_eq!(program.contexts.len(), 1);
+        let outer = &program.contexts[0];
+        assert_eq!(outer.statements.len(), 1);
+        assert_eq!(outer.children.len(), 1);
+
+        let inner = &outer.children[0];
+        assert_eq!(inner.name, "Checkout");
+        assert_eq!(inner.statements.len(), 1);
+        assert!(inner.children.is_empty());
+
+        // The parent span encloses the child span
+        assert!(outer.span.start.offset < inner.span.start.offset);
+        assert!(outer.span.end.offset > inner.span.end.offset);
+        assert_eq!((inner.span.start.line, inner.span.end.line), (3, 5));
+    }
+
+    #[test]
+    fn test_recovers_from_broken_statements() {
+        let input = r#"CONTEXT Test {
+    VAR User:Entity
+    User GOES Dashboard
+    User CALLS AuthService
+    VAR :Entity
+    AuthService RETURNS Session
+}
+CONTEXT Other { VAR Order }"#;
+        let tokens = Lexer::new(input).tokenize_filtered().unwrap();
+        let mut parser = Parser::new(tokens).with_source(input);
+        let result = parser.parse();
+
+        let Err(NaviLangError::MultipleErrors { errors }) = result else {
+            panic!("Expected MultipleErrors, got {:?}", result);
+        };
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].to_string().contains("Expected 'TO' after 'GOES'"));
+        assert!(errors[1].to_string().contains("Expected a variable name"));
+    }
+
+    #[test]
+    fn test_recovery_keeps_valid_statements() {
+        let input = "CONTEXT Test { VAR User User GOES Dashboard User CALLS AuthService VAR :Entity } CONTEXT Other { }";
+        let tokens = Lexer::new(input).tokenize_filtered().unwrap();
+        let mut parser = Parser::new(tokens);
+        assert!(parser.parse().is_err());
+
+        // Re-run the statement loop directly to inspect what was kept
+        let tokens = Lexer::new(input).tokenize_filtered().unwrap();
+        let mut parser = Parser::new(tokens);
+        parser.current = 3;
+        let statements = parser.parse_body(None);
+
+        assert_eq!(statements.len(), 2);
+        assert!(matches!(statements[0].kind, StatementKind::VarDecl { .. }));
+        assert!(matches!(statements[1].kind, StatementKind::Interaction { .. }));
+        assert_eq!(parser.errors.error_count(), 2);
+        assert!(parser.check(&Token::RightBrace));
+    }
+
+    #[test]
+    fn test_recovers_at_next_context() {
+        let input = "CONTEXT { VAR A } CONTEXT Good { VAR B } CONTEXT Bad VAR C }";
+        let tokens = Lexer::new(input).tokenize_filtered().unwrap();
+        let mut parser = Parser::new(tokens);
+
+        let Err(NaviLangError::MultipleErrors { errors }) = parser.parse() else {
+            panic!("Expected MultipleErrors");
+        };
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].to_string().contains("Expected a context name"));
+        assert!(errors[1].to_string().contains("Expected '{'"));
+    }
+
+    #[test]
+    fn test_parse_partial_keeps_good_contexts() {
+        let input = "CONTEXT { VAR A }\nCONTEXT Good { VAR B  B -> Done }";
+        let tokens = Lexer::new(input).tokenize_filtered().unwrap();
+        let mut parser = Parser::new(tokens).with_source(input);
+        let (program, errors) = parser.parse_partial();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(parser.failed_contexts(), 1);
+        assert!(errors[0].to_string().contains("Expected a context name"));
+        assert_eq!(program.contexts.len(), 1);
+        assert_eq!(program.contexts[0].name, "Good");
+        assert_eq!(program.contexts[0].statements.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_stops_after_max_errors() {
+        let input: String = (0..5).map(|i| format!("CONTEXT Broken{} VAR User }}\n", i)).collect();
+        let tokens = Lexer::new(&input).tokenize_filtered().unwrap();
+        let mut parser = Parser::new(tokens).with_source(&input).with_max_errors(2);
+        let (_, errors) = parser.parse_partial();
+
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors[2], NaviLangError::TooManyErrors { limit: 2 }));
+        // The fourth and fifth contexts were never reached
+        assert_eq!(parser.failed_contexts(), 3);
+
+        // A single broken context stops as well, and parse keeps the limit
+        let input = format!("CONTEXT Big {{\n{}}}", "    User GOES Home\n".repeat(50));
+        let tokens = Lexer::new(&input).tokenize_filtered().unwrap();
+        match Parser::new(tokens).with_source(&input).with_max_errors(2).parse() {
+            Err(NaviLangError::MultipleErrors { errors }) => {
+                assert_eq!(errors.len(), 3);
+                assert!(matches!(errors[2], NaviLangError::TooManyErrors { limit: 2 }));
+            }
+            other => panic!("Expected MultipleErrors, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_missing_brace_is_reported() {
+        let input = "CONTEXT Test VAR User }";
+        let result = parse(input);
+
+        if let Err(NaviLangError::SyntaxError { message, span, .. }) = result {
+            assert!(message.contains("Expected '{'"));
+            assert!(message.contains("VAR"));
+            assert_eq!(span.offset(), 13);
+        } else {
+            panic!("Expected SyntaxError for missing brace");
+        }
+    }
+
+    #[test]
+    fn test_missing_closing_brace_at_end_of_input() {
+        let input = "CONTEXT Test {";
+        let result = parse(input);
+
+        if let Err(NaviLangError::SyntaxError { message, span, .. }) = result {
+            assert!(message.contains("end of input"));
+            assert_eq!(span.offset(), input.len());
+        } else {
+            panic!("Expected SyntaxError for missing closing brace");
+        }
+    }
+
+    #[test]
+    fn test_leading_comments_are_attached() {
+        let program = parse("// Login flow\nCONTEXT Auth {\n    // Send users home\n    User -> Home\n    Home -> Done\n}").unwrap();
+        let context = &program.contexts[0];
+
+        assert_eq!(context.comments, vec!["Login flow"]);
+        assert_eq!(context.statements[0].comments, vec!["Send users home"]);
+        assert!(context.statements[1].comments.is_empty());
+    }
+
+    #[test]
+    fn test_backtick_escaped_variable_name() {
+        let stmt = first_statement("CONTEXT Test { VAR `Service` }");
+        assert_eq!(stmt.kind, StatementKind::VarDecl {
+            name: "Service".to_string(),
+            type_annotation: None,
+        });
+    }
+
+    #[test]
+    fn test_parse_action() {
+        let stmt = first_statement("CONTEXT Test { User DOES Login }");
+        assert_eq!(stmt.kind, StatementKind::Action {
+            actor: "User".to_string(),
+            action: "Login".to_string(),
+        });
+
+        let stmt = first_statement(r#"CONTEXT Test { User does "Reset Password" }"#);
+        assert_eq!(stmt.kind, StatementKind::Action {
+            actor: "User".to_string(),
+            action: "Reset Password".to_string(),
+        });
+    }
+
+    #[test]
+    fn test_parallel_actions() {
+        let stmt = first_statement("CONTEXT Test { PARALLEL { User DOES Login  Admin DOES Audit } }");
+        let StatementKind::Parallel { branches, .. } = stmt.kind else {
+            panic!("Expected a parallel block");
+        };
+        assert_eq!(branches.len(), 2);
+        assert!(matches!(&branches[1][0].kind, StatementKind::Action { actor, .. } if actor == "Admin"));
+    }
+
+    #[test]
+    fn test_action_requires_name() {
+        let result = parse("CONTEXT Test { User DOES }");
+        assert!(result.unwrap_err().to_string().contains("Expected an action after 'DOES'"));
+    }
+
+    #[test]
+    fn test_retry_and_timeout_clauses() {
+        use crate::utils::duration::TimeUnit;
+
+        for input in [
+            "CONTEXT Test { User CALLS AuthService RETRY 3 TIMEOUT 30s }",
+            "CONTEXT Test { User CALLS AuthService timeout 30s retry 3 }",
+        ] {
+            let StatementKind::Interaction { retry, timeout, .. } = first_statement(input).kind else {
+                panic!("Expected an interaction for: {}", input);
+            };
+            assert_eq!(retry, Some(3));
+            assert_eq!(timeout, Some(Duration { value: 30, unit: TimeUnit::Seconds }));
+        }
+    }
+
+    #[test]
+    fn test_invalid_retry_and_timeout_clauses() {
+        let errors = [
+            ("User CALLS Api RETRY 1 RETRY 2", "Duplicate 'RETRY' clause"),
+            ("User CALLS Api RETRY -1", "Expected a retry count after 'RETRY'"),
+            ("User CALLS Api TIMEOUT 3", "Expected a duration after 'TIMEOUT'"),
+            ("User CALLS Api TIMEOUT -5s", "Invalid duration '-5s'"),
+        ];
+
+        for (body, expected) in errors {
+            let input = format!("CONTEXT Test {{ {} }}", body);
+            let message = parse(&input).unwrap_err().to_string();
+            assert!(message.contains(expected), "{}: {}", body, message);
+        }
+    }
+
+    #[test]
+    fn test_async_interactions() {
+        let stmt = first_statement("CONTEXT Test { ASYNC User CALLS Notifier }");
+        assert_eq!(stmt.kind, StatementKind::Interaction {
+            subject: "User".to_string(),
+            verb: InteractionVerb::Calls,
+            object: Value::Identifier("Notifier".to_string()),
+            retry: None,
+            timeout: None,
+            is_async: true,
+        });
+
+        let program = parse("CONTEXT Test {
+    User CALLS Notifier TIMEOUT 5s ASYNC
+    ASYNC User CALLS Audit
+    User CALLS Api
+}").unwrap();
+        let flags: Vec<_> = program.contexts[0].statements.iter()
+            .map(|stmt| matches!(stmt.kind, StatementKind::Interaction { is_async: true, .. }))
+            .collect();
+        assert_eq!(flags, vec![true, true, false]);
+    }
+
+    #[test]
+    fn test_invalid_async_modifiers() {
+        let errors = [
+            ("ASYNC User -> Home", "'ASYNC' can only modify CALLS, RECEIVES or RETURNS"),
+            ("ASYNC User CALLS Api ASYNC", "Duplicate 'ASYNC' modifier"),
+            ("User CALLS Api ASYNC ASYNC", "Duplicate 'ASYNC' modifier"),
+            ("ASYNC VAR User", "Expected an interaction after 'ASYNC'"),
+        ];
+
+        for (body, expected) in errors {
+            let input = format!("CONTEXT Test {{ {} }}", body);
+            let message = parse(&input).unwrap_err().to_string();
+            assert!(message.contains(expected), "{}: {}", body, message);
+        }
+    }
+
+    #[test]
+    fn test_parse_endpoint() {
+        let stmt = first_statement(r#"CONTEXT Api { ENDPOINT GET "/users" }"#);
+        assert_eq!(stmt.kind, StatementKind::Endpoint {
+            method: HttpMethod::Get,
+            path: "/users".to_string(),
+            description: None,
+        });
+
+        let program = parse(r#"CONTEXT Api {
+    VAR Users:Endpoint
+    endpoint delete "/users/{id}" "Remove a user"
+    VAR Get
+}"#).unwrap();
+        let statements = &program.contexts[0].statements;
+        assert_eq!(statements.len(), 3);
+        assert!(matches!(&statements[1].kind, StatementKind::Endpoint { method: HttpMethod::Delete, path, description: Some(description) }
+            if path == "/users/{id}" && description == "Remove a user"));
+    }
+
+    #[test]
+    fn test_invalid_endpoints() {
+        let errors = [
+            (r#"ENDPOINT PATCH "/users""#, "Expected GET, POST, PUT or DELETE after 'ENDPOINT'"),
+            (r#"ENDPOINT GET users"#, "Expected a quoted path after the endpoint method"),
+            (r#"ENDPOINT GET "users""#, "Endpoint path must start with '/'"),
+        ];
+
+        for (body, expected) in errors {
+            let input = format!("CONTEXT Test {{ {} }}", body);
+            let message = parse(&input).unwrap_err().to_string();
+            assert!(message.contains(expected), "{}: {}", body, message);
+        }
+    }
+
+    #[test]
+    fn test_parse_ordering() {
+        let program = parse("CONTEXT Checkout { Pay AFTER Review  Review BEFORE Ship }").unwrap();
+        let statements = &program.contexts[0].statements;
+
+        assert_eq!(statements[0].kind, StatementKind::Ordering {
+            subject: "Pay".to_string(),
+            relation: OrderRelation::After,
+            other: "Review".to_string(),
+        });
+        assert_eq!(statements[1].kind, StatementKind::Ordering {
+            subject: "Review".to_string(),
+            relation: OrderRelation::Before,
+            other: "Ship".to_string(),
+        });
+
+        let message = parse("CONTEXT Checkout { Pay AFTER }").unwrap_err().to_string();
+        assert!(message.contains("Expected a step after 'AFTER'"), "{}", message);
+    }
+
+    #[test]
+    fn test_parse_uses() {
+        let program = parse("CONTEXT Backend { AuthService USES Database }").unwrap();
+        assert_eq!(program.contexts[0].statements[0].kind, StatementKind::Uses {
+            subject: "AuthService".to_string(),
+            dependency: "Database".to_string(),
+        });
+
+        let message = parse("CONTEXT Backend { AuthService USES }").unwrap_err().to_string();
+        assert!(message.contains("Expected a dependency after 'USES'"), "{}", message);
+    }
+
+    #[test]
+    fn test_parse_batch() {
+        // `Service` is a reserved type name, so it needs backticks as an actor
+        let program = parse("CONTEXT Jobs { BATCH 50 { `Service` DOES Process }  BATCH { } }").unwrap();
+        let statements = &program.contexts[0].statements;
+
+        let StatementKind::Batch { size, body } = &statements[0].kind else {
+            panic!("Expected a batch, got {:?}", statements[0].kind);
+        };
+        assert_eq!(*size, Some(50));
+        assert_eq!(body.len(), 1);
+        assert_eq!(body[0].kind, StatementKind::Action {
+            actor: "Service".to_string(),
+            action: "Process".to_string(),
+        });
+        assert_eq!(statements[1].kind, StatementKind::Batch { size: None, body: vec![] });
+
+        // Recovery may report follow-on errors, so look at the whole list
+        let message = format!("{:?}", parse("CONTEXT Jobs { BATCH 0 { Worker DOES Process } }").unwrap_err());
+        assert!(message.contains("Batch size must be at least 1"), "{}", message);
+        let message = format!("{:?}", parse("CONTEXT Jobs { BATCH Worker DOES Process }").unwrap_err());
+        assert!(message.contains("Expected '{' after 'BATCH'"), "{}", message);
+    }
+
+    #[test]
+    fn test_nesting_depth_is_limited() {
+        let input = format!("CONTEXT Deep {{ {}{} }}", "LOOP { ".repeat(200), "} ".repeat(200));
+        let error = parse(&input).unwrap_err();
+        let NaviLangError::SyntaxError { message, span, .. } = &error else {
+            panic!("Expected one SyntaxError, got {:?}", error);
+        };
+        assert_eq!(message, "Maximum nesting depth of 128 exceeded");
+        // The context and 127 loops fit
+        assert_eq!(span.offset(), "CONTEXT Deep { ".len() + "LOOP { ".len() * 127);
+
+        let braces = format!("{}{}", "CONTEXT A { ".repeat(200), "} ".repeat(200));
+        assert!(format!("{:?}", parse(&braces).unwrap_err()).contains("Maximum nesting depth"));
+
+        let lists = format!("CONTEXT A {{ User RECEIVES {}1{} }}", "[".repeat(500), "]".repeat(500));
+        assert!(format!("{:?}", parse(&lists).unwrap_err()).contains("Maximum nesting depth"));
+
+        // A flat AND/OR chain builds a tree as deep as it is long
+        for operator in [" AND ", " OR "] {
+            let chain = vec!["x IS 1"; 20_000].join(operator);
+            let conditions = format!("CONTEXT A {{ IF {} THEN BREAK }}", chain);
+            assert!(format!("{:?}", parse(&conditions).unwrap_err()).contains("Maximum nesting depth"));
+        }
+        let chain = vec!["x IS 1"; 100].join(" AND ");
+        assert!(parse(&format!("CONTEXT A {{ IF {} OR x IS 2 THEN BREAK }}", chain)).is_ok());
+
+        let shallow = "CONTEXT A { LOOP { LOOP { BREAK } } }";
+        let tokens = Lexer::new(shallow).tokenize_with_trivia().unwrap();
+        assert!(Parser::new(tokens.clone()).with_max_depth(4).parse().is_ok());
+        assert!(Parser::new(tokens).with_max_depth(3).parse().is_err());
+    }
+    #[test]
+    fn test_parse_start() {
+        assert_eq!(first_statement("CONTEXT Auth { start Login }").kind, StatementKind::Start { node: "Login".to_string() });
+
+        let error = parse("CONTEXT Auth { START }").unwrap_err();
+        assert!(format!("{:?}", error).contains("Expected a node name after 'START'"));
+    }
 }
@@ -1,13 +1,47 @@
 use std::fs;
-use std::path::Path;
-use anyhow::{Context, Result};
+use std::io::Read;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result, bail};
+use crate::error::{NaviLangError, Position, Span};
+use crate::lexer::{Lexer, tokens::Token};
+use serde::Serialize;
 
 /// Represents a source file with content and metadata
+///
+/// `content` is normalized on construction: a leading UTF-8 BOM is removed
+/// and `\r\n` line endings become `\n`. All spans and offsets produced by the
+/// compiler are relative to this normalized content.
 #[derive(Debug, Clone)]
 pub struct SourceFile {
     pub content: String,
     pub path: String,
     pub lines: Vec<String>,
+    /// Whether the original input started with a UTF-8 BOM
+    pub had_bom: bool,
+    /// Whether the original input used `\r\n` line endings
+    pub had_crlf: bool,
+    /// Regions of `content` inlined from other files by `resolve_includes`
+    pub includes: Vec<IncludedRegion>,
+    /// Byte offset at which each line of `content` starts
+    line_starts: Vec<usize>,
+}
+
+/// A place in an original source file, as reported by [`SourceFile::location`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Location {
+    pub path: String,
+    /// 1-based
+    pub line: usize,
+    /// 1-based, counted in characters
+    pub column: usize,
+}
+
+/// A byte range of combined content that came from an included file
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncludedRegion {
+    pub path: String,
+    pub range: Range<usize>,
 }
 
 impl SourceFile {
@@ -15,24 +49,41 @@ impl SourceFile {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path_str = path.as_ref().to_string_lossy().to_string();
         let content = fs::read_to_string(&path)
-            .with_context(|| format!("Failed to read file: {}", path_str))?;
+            .map_err(|source| NaviLangError::FileError { path: path_str.clone(), source })?;
         
-        let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
+        Ok(Self::from_string(content, path_str))
+    }
+    
+    /// Create a SourceFile from any reader, e.g. stdin or an in-memory buffer
+    pub fn from_reader<R: Read>(mut reader: R, path: String) -> Result<Self> {
+        let mut content = String::new();
+        reader.read_to_string(&mut content)
+            .with_context(|| format!("Failed to read input: {}", path))?;
         
-        Ok(SourceFile {
-            content,
-            path: path_str,
-            lines,
-        })
+        Ok(Self::from_string(content, path))
     }
     
     /// Create a SourceFile from a string (useful for testing)
     pub fn from_string(content: String, path: String) -> Self {
+        let (content, had_bom) = match content.strip_prefix('\u{feff}') {
+            Some(rest) => (rest.to_string(), true),
+            None => (content, false),
+        };
+        let had_crlf = content.contains("\r\n");
+        let content = if had_crlf { content.replace("\r\n", "\n") } else { content };
+        
         let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
+        let line_starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
         SourceFile {
             content,
             path,
             lines,
+            had_bom,
+            had_crlf,
+            includes: Vec::new(),
+            line_starts,
         }
     }
     
@@ -46,6 +97,101 @@ impl SourceFile {
         self.lines.len()
     }
     
+    /// Path of the file the byte at `offset` originally came from
+    ///
+    /// Offsets outside any included region belong to this file itself.
+    pub fn origin(&self, offset: usize) -> &str {
+        self.includes.iter()
+            .filter(|region| region.range.contains(&offset))
+            .min_by_key(|region| region.range.len())
+            .map(|region| region.path.as_str())
+            .unwrap_or(&self.path)
+    }
+    
+    /// Where the byte at `offset` was written: the file it came from, as
+    /// [`origin`](Self::origin) reports it, and its line and column there
+    ///
+    /// Text inlined by an `INCLUDE` earlier in the same file is skipped, as
+    /// if the directive were still in its place; on the line of such a
+    /// directive, columns after it do not count the directive itself.
+    pub fn location(&self, offset: usize) -> Location {
+        let region = self.includes.iter()
+            .filter(|region| region.range.contains(&offset))
+            .min_by_key(|region| region.range.len());
+        let (path, start) = region.map_or((&self.path, 0), |region| (&region.path, region.range.start));
+        let inlined: Vec<&Range<usize>> = self.includes.iter()
+            .map(|other| &other.range)
+            .filter(|range| range.start >= start && range.end <= offset)
+            .filter(|range| region.is_none_or(|region| region.range != **range))
+            .collect();
+        
+        let (mut line, mut column) = (1, 1);
+        for (i, c) in self.content.get(start..offset).unwrap_or_default().char_indices() {
+            if inlined.iter().any(|range| range.contains(&(start + i))) {
+                continue;
+            }
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        Location { path: path.clone(), line, column }
+    }
+    
+    /// Source text covered by `span`
+    ///
+    /// Returns `None` if the span's offsets fall outside the content or
+    /// inside a multi-byte character.
+    pub fn snippet(&self, span: &Span) -> Option<&str> {
+        self.content.get(span.start.offset..span.end.offset)
+    }
+    
+    /// Byte offset of a 1-indexed line and column, counting columns in
+    /// characters as the lexer does
+    ///
+    /// The column just past a line's last character is valid. Returns `None`
+    /// for positions beyond the content.
+    pub fn offset_at(&self, line: usize, column: usize) -> Option<usize> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self.line_starts.get(line).map_or(self.content.len(), |next| next - 1);
+        
+        self.content[start..end].char_indices()
+            .map(|(i, _)| start + i)
+            .chain(std::iter::once(end))
+            .nth(column.checked_sub(1)?)
+    }
+    
+    /// Line, column and offset of the byte at `offset`, matching the
+    /// positions in lexer spans
+    ///
+    /// The end of the content is a valid offset. Returns `None` past it or
+    /// inside a multi-byte character.
+    pub fn position_at(&self, offset: usize) -> Option<Position> {
+        if !self.content.is_char_boundary(offset) {
+            return None;
+        }
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let column = self.content[self.line_starts[line - 1]..offset].chars().count() + 1;
+        Some(Position::new(line, column, offset))
+    }
+    
+    /// Numbered lines of the span, plus up to `radius` lines before and after
+    ///
+    /// Line numbers are 1-indexed and derived from the span's offsets. Returns
+    /// an empty list if the span does not fit in the content.
+    pub fn context_lines(&self, span: &Span, radius: usize) -> Vec<(usize, &str)> {
+        if self.snippet(span).is_none() {
+            return Vec::new();
+        }
+        let line_of = |offset: usize| self.content[..offset].matches('\n').count() + 1;
+        
+        let first = line_of(span.start.offset).saturating_sub(radius).max(1);
+        let last = line_of(span.end.offset) + radius;
+        (first..).zip(self.get_lines(first, last)).collect()
+    }
+    
     /// Get a range of lines (1-indexed, inclusive)
     pub fn get_lines(&self, start: usize, end: usize) -> Vec<&str> {
         let start_idx = start.saturating_sub(1);
@@ -67,6 +213,88 @@ pub fn read_source<P: AsRef<Path>>(path: P) -> Result<SourceFile> {
     SourceFile::from_file(path)
 }
 
+/// Read source from standard input until EOF
+///
+/// The resulting `SourceFile` is named `"<stdin>"`.
+pub fn read_stdin() -> Result<SourceFile> {
+    SourceFile::from_reader(std::io::stdin().lock(), STDIN_PATH.to_string())
+}
+
+/// Inline every `INCLUDE "file.navi"` directive in `source`
+///
+/// Paths are relative to `base_dir` for the top-level source and to the
+/// including file's directory for nested includes. Spans computed on the
+/// result refer to the combined content; `SourceFile::origin` maps an offset
+/// back to the file it came from. Circular includes are rejected with the
+/// full chain of files in the error.
+pub fn resolve_includes(source: SourceFile, base_dir: &Path) -> Result<SourceFile> {
+    let mut stack = vec![canonical(Path::new(&source.path))];
+    let (content, includes) = inline_includes(&source.content, base_dir, &mut stack)?;
+    
+    let mut resolved = SourceFile::from_string(content, source.path);
+    resolved.includes = includes;
+    Ok(resolved)
+}
+
+fn inline_includes(
+    content: &str,
+    base_dir: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<(String, Vec<IncludedRegion>)> {
+    // Lexical errors are left for the compiler to report on the combined source
+    let (tokens, _) = Lexer::new(content).tokenize_recovering();
+    let significant: Vec<_> = tokens.iter().filter(|t| !t.token.is_whitespace()).collect();
+    
+    let mut out = String::new();
+    let mut regions = Vec::new();
+    let mut copied = 0;
+    
+    for pair in significant.windows(2) {
+        let (Token::Include, Token::QuotedString(target)) = (&pair[0].token, &pair[1].token) else {
+            continue;
+        };
+        
+        let path = base_dir.join(target);
+        let key = canonical(&path);
+        if let Some(first) = stack.iter().position(|p| *p == key) {
+            let chain: Vec<String> = stack[first..].iter()
+                .chain(std::iter::once(&key))
+                .map(|p| p.display().to_string())
+                .collect();
+            bail!("Circular include: {}", chain.join(" -> "));
+        }
+        
+        let included = SourceFile::from_file(&path)?;
+        stack.push(key);
+        let (text, nested) = inline_includes(
+            &included.content,
+            path.parent().unwrap_or(Path::new(".")),
+            stack,
+        )?;
+        stack.pop();
+        
+        out.push_str(&content[copied..pair[0].span.start.offset]);
+        let start = out.len();
+        out.push_str(&text);
+        regions.extend(nested.into_iter().map(|region| IncludedRegion {
+            path: region.path,
+            range: region.range.start + start..region.range.end + start,
+        }));
+        regions.push(IncludedRegion { path: included.path, range: start..out.len() });
+        copied = pair[1].span.end.offset;
+    }
+    
+    out.push_str(&content[copied..]);
+    Ok((out, regions))
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Path given to sources read from standard input
+pub const STDIN_PATH: &str = "<stdin>";
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,4 +341,163 @@ mod tests {
         let lines = source.get_lines(4, 10);
         assert_eq!(lines, vec!["line4", "line5"]);
     }
+    
+    #[test]
+    fn test_snippet_and_context_lines() {
+        let content = "line1\nline2\nline3\nline4\nline5".to_string();
+        let source = SourceFile::from_string(content, "test.navi".to_string());
+        let span = |start: usize, end: usize| Span::new(
+            crate::error::Position::new(0, 0, start),
+            crate::error::Position::new(0, 0, end),
+        );
+        
+        // "ne3\nli", spanning lines 3 and 4
+        assert_eq!(source.snippet(&span(14, 20)), Some("ne3\nli"));
+        assert_eq!(source.context_lines(&span(14, 20), 1), vec![
+            (2, "line2"), (3, "line3"), (4, "line4"), (5, "line5"),
+        ]);
+        assert_eq!(source.context_lines(&span(0, 5), 0), vec![(1, "line1")]);
+        assert_eq!(source.context_lines(&span(0, 2), 3), vec![
+            (1, "line1"), (2, "line2"), (3, "line3"), (4, "line4"),
+        ]);
+        
+        // Out-of-range and inverted spans are rejected
+        assert_eq!(source.snippet(&span(20, 40)), None);
+        assert_eq!(source.snippet(&span(4, 2)), None);
+        assert!(source.context_lines(&span(20, 40), 2).is_empty());
+    }
+    
+    #[test]
+    fn test_offset_position_round_trip() {
+        for content in ["CONTEXT \"Ä\" {\n  VAR User\n\n}", "VAR X\n  VAR Y\n"] {
+            let source = SourceFile::from_string(content.to_string(), "test.navi".to_string());
+            let tokens = Lexer::new(content).tokenize_with_trivia().unwrap();
+            
+            for token in &tokens {
+                for position in [token.span.start, token.span.end] {
+                    assert_eq!(source.position_at(position.offset), Some(position), "{:?}", token);
+                    assert_eq!(source.offset_at(position.line, position.column), Some(position.offset));
+                }
+            }
+            for offset in (0..=content.len()).filter(|&i| content.is_char_boundary(i)) {
+                let position = source.position_at(offset).unwrap();
+                assert_eq!(source.offset_at(position.line, position.column), Some(offset));
+            }
+        }
+        
+        let source = SourceFile::from_string("ab\ncd".to_string(), "test.navi".to_string());
+        // The last line has no newline; its end is the end of the content
+        assert_eq!(source.offset_at(2, 3), Some(5));
+        assert_eq!(source.position_at(5), Some(Position::new(2, 3, 5)));
+        assert_eq!(source.offset_at(1, 3), Some(2));
+        assert_eq!(source.offset_at(1, 4), None);
+        assert_eq!(source.offset_at(3, 1), None);
+        assert_eq!(source.offset_at(0, 1), None);
+        assert_eq!(source.offset_at(1, 0), None);
+        assert_eq!(source.position_at(6), None);
+        
+        let source = SourceFile::from_string("é".to_string(), "test.navi".to_string());
+        assert_eq!(source.position_at(1), None);
+        assert_eq!(source.offset_at(1, 2), Some(2));
+    }
+    
+    #[test]
+    fn test_source_file_from_reader() {
+        let input: &[u8] = b"CONTEXT Test {\n  VAR User\n}\n";
+        let source = SourceFile::from_reader(input, STDIN_PATH.to_string()).unwrap();
+        
+        assert_eq!(source.path, "<stdin>");
+        assert_eq!(source.line_count(), 3);
+        assert_eq!(source.get_line(2), Some("  VAR User"));
+        assert_eq!(source.content, "CONTEXT Test {\n  VAR User\n}\n");
+    }
+    
+    #[test]
+    fn test_from_reader_rejects_invalid_utf8() {
+        let input: &[u8] = &[0xff, 0xfe, 0x00];
+        let error = SourceFile::from_reader(input, "bad.navi".to_string()).unwrap_err();
+        assert!(error.to_string().contains("bad.navi"));
+    }
+    
+    #[test]
+    fn test_resolve_includes_inlines_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("b.navi"), "CONTEXT Shared { VAR Session }").unwrap();
+        let main = "INCLUDE \"b.navi\"\nCONTEXT Main { VAR User }";
+        fs::write(dir.path().join("a.navi"), main).unwrap();
+        
+        let source = read_source(dir.path().join("a.navi")).unwrap();
+        let resolved = resolve_includes(source, dir.path()).unwrap();
+        
+        assert_eq!(resolved.content, "CONTEXT Shared { VAR Session }\nCONTEXT Main { VAR User }");
+        let tokens = Lexer::new(&resolved.content).tokenize_filtered().unwrap();
+        assert!(tokens.iter().any(|t| t.token == Token::Identifier("Session".to_string())));
+        assert!(!tokens.iter().any(|t| t.token == Token::Include));
+        
+        assert!(resolved.origin(5).ends_with("b.navi"));
+        assert!(resolved.origin(resolved.content.len() - 3).ends_with("a.navi"));
+    }
+    
+    #[test]
+    fn test_location_maps_back_through_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("b.navi"), "CONTEXT Shared {\n    VAR Session\n}\n").unwrap();
+        fs::write(dir.path().join("a.navi"), "// Main\nINCLUDE \"b.navi\"\nCONTEXT Main { VAR User }").unwrap();
+        
+        let source = read_source(dir.path().join("a.navi")).unwrap();
+        let resolved = resolve_includes(source, dir.path()).unwrap();
+        let location = |needle: &str| resolved.location(resolved.content.find(needle).unwrap());
+        
+        let session = location("Session");
+        assert!(session.path.ends_with("b.navi"));
+        assert_eq!((session.line, session.column), (2, 9));
+        
+        // Line 6 of the combined content, but line 3 of a.navi
+        let user = location("User");
+        assert!(user.path.ends_with("a.navi"));
+        assert_eq!((user.line, user.column), (3, 20));
+    }
+    
+    #[test]
+    fn test_circular_include_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.navi"), "INCLUDE \"b.navi\"").unwrap();
+        fs::write(dir.path().join("b.navi"), "include \"a.navi\"").unwrap();
+        
+        let source = read_source(dir.path().join("a.navi")).unwrap();
+        let message = resolve_includes(source, dir.path()).unwrap_err().to_string();
+        
+        assert!(message.starts_with("Circular include: "));
+        assert_eq!(message.matches(" -> ").count(), 2);
+        assert!(message.contains("a.navi -> "));
+        assert!(message.contains("b.navi -> "));
+    }
+    
+    #[test]
+    fn test_crlf_is_normalized() {
+        let content = "CONTEXT Test {\r\n  VAR User\r\n}\r\n".to_string();
+        let source = SourceFile::from_string(content, "test.navi".to_string());
+        
+        assert_eq!(source.content, "CONTEXT Test {\n  VAR User\n}\n");
+        assert_eq!(source.line_count(), 3);
+        assert_eq!(source.get_line(1), Some("CONTEXT Test {"));
+        assert!(source.had_crlf);
+        assert!(!source.had_bom);
+    }
+    
+    #[test]
+    fn test_bom_is_stripped_from_file() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"\xEF\xBB\xBFCONTEXT Test {\r\n}").unwrap();
+        
+        let source = read_source(temp_file.path()).unwrap();
+        assert_eq!(source.content, "CONTEXT Test {\n}");
+        assert_eq!(source.line_count(), 2);
+        assert!(source.had_bom);
+        assert!(source.had_crlf);
+        
+        let tokens = Lexer::new(&source.content).tokenize_filtered().unwrap();
+        assert_eq!(tokens[0].span.start.offset, 0);
+        assert_eq!(tokens[3].span.start.line, 2);
+    }
 }
@@ -0,0 +1,87 @@
+// Terminal colors - a small ANSI palette for highlighting tokens
+use crate::lexer::tokens::Token;
+use std::ffi::OsStr;
+
+/// Role of a piece of text, which decides its color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Keyword,
+    Type,
+    Literal,
+    Identifier,
+    Comment,
+}
+
+impl Style {
+    /// Style of a token, or `None` for punctuation and whitespace
+    ///
+    /// Type names and `TRUE`/`FALSE` count as keywords too, so the more
+    /// specific roles are checked first.
+    pub fn of(token: &Token) -> Option<Style> {
+        match token {
+            token if token.is_type() => Some(Style::Type),
+            token if token.is_literal() => Some(Style::Literal),
+            token if token.is_keyword() => Some(Style::Keyword),
+            Token::Identifier(_) => Some(Style::Identifier),
+            Token::Comment | Token::BlockComment => Some(Style::Comment),
+            _ => None,
+        }
+    }
+
+    fn code(self) -> &'static str {
+        match self {
+            Style::Keyword => "1;35",
+            Style::Type => "36",
+            Style::Literal => "32",
+            Style::Identifier => "33",
+            Style::Comment => "2",
+        }
+    }
+}
+
+/// Whether to color output the user asked to color
+///
+/// A non-empty `NO_COLOR` environment variable always turns color off.
+pub fn enabled(requested: bool) -> bool {
+    requested && !no_color(std::env::var_os("NO_COLOR").as_deref())
+}
+
+fn no_color(value: Option<&OsStr>) -> bool {
+    value.is_some_and(|value| !value.is_empty())
+}
+
+/// Wrap `text` in the escape sequences for `style`, or return it unchanged
+/// when color is off
+pub fn paint(text: &str, style: Option<Style>, enabled: bool) -> String {
+    match style {
+        Some(style) if enabled => format!("\x1b[{}m{}\x1b[0m", style.code(), text),
+        _ => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_styles() {
+        assert_eq!(Style::of(&Token::Context), Some(Style::Keyword));
+        assert_eq!(Style::of(&Token::Service), Some(Style::Type));
+        assert_eq!(Style::of(&Token::True), Some(Style::Literal));
+        assert_eq!(Style::of(&Token::QuotedString("x".to_string())), Some(Style::Literal));
+        assert_eq!(Style::of(&Token::Identifier("User".to_string())), Some(Style::Identifier));
+        assert_eq!(Style::of(&Token::LeftBrace), None);
+    }
+
+    #[test]
+    fn test_disabled_color_has_no_escapes() {
+        assert_eq!(paint("VAR", Some(Style::Keyword), false), "VAR");
+        assert_eq!(paint("{", None, true), "{");
+        assert_eq!(paint("VAR", Some(Style::Keyword), true), "\x1b[1;35mVAR\x1b[0m");
+
+        assert!(no_color(Some(OsStr::new("1"))));
+        assert!(!no_color(Some(OsStr::new(""))));
+        assert!(!no_color(None));
+        assert!(!enabled(false));
+    }
+}
@@ -0,0 +1,61 @@
+// Edit distance utilities for "did you mean" suggestions
+
+/// Levenshtein distance between two strings, counted in chars
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// The candidate closest to `target` within `max_distance` edits, if any
+///
+/// Ties go to the candidate listed first.
+pub fn closest_match<'a, I>(target: &str, candidates: I, max_distance: usize) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut best: Option<(usize, &str)> = None;
+    for candidate in candidates {
+        let distance = levenshtein(target, candidate);
+        if distance <= max_distance && best.is_none_or(|(d, _)| distance < d) {
+            best = Some((distance, candidate));
+        }
+    }
+    best.map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("abc", ""), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("Dashboard", "Dashboard"), 0);
+        assert_eq!(levenshtein("Dashbord", "Dashboard"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("flaw", "lawn"), 2);
+        assert_eq!(levenshtein("Über", "Uber"), 1);
+    }
+
+    #[test]
+    fn test_closest_match() {
+        let names = ["User", "Dashboard", "Dash"];
+        assert_eq!(closest_match("Dashbord", names, 2), Some("Dashboard"));
+        assert_eq!(closest_match("Dasj", names, 2), Some("Dash"));
+        assert_eq!(closest_match("Checkout", names, 2), None);
+    }
+}
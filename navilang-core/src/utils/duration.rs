@@ -0,0 +1,172 @@
+// Duration literals such as `30s` or `500ms`
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::Add;
+use std::str::FromStr;
+
+/// Unit suffix of a duration literal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeUnit {
+    Milliseconds,
+    Seconds,
+    Minutes,
+    Hours,
+}
+
+/// A parsed duration literal, keeping the unit it was written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Duration {
+    pub value: u64,
+    pub unit: TimeUnit,
+}
+
+impl Duration {
+    /// The duration in milliseconds, for comparing values written in
+    /// different units (saturates instead of overflowing)
+    pub fn as_millis(&self) -> u64 {
+        self.value.saturating_mul(self.unit.millis())
+    }
+
+    /// A duration of `millis` milliseconds, in the largest unit that holds it
+    /// exactly
+    pub fn from_millis(millis: u64) -> Self {
+        let unit = [TimeUnit::Hours, TimeUnit::Minutes, TimeUnit::Seconds]
+            .into_iter()
+            .find(|unit| millis != 0 && millis.is_multiple_of(unit.millis()))
+            .unwrap_or(TimeUnit::Milliseconds);
+        Duration { value: millis / unit.millis(), unit }
+    }
+
+    /// The duration repeated `times` times (saturates instead of overflowing)
+    pub fn saturating_mul(self, times: u64) -> Self {
+        Duration::from_millis(self.as_millis().saturating_mul(times))
+    }
+}
+
+/// Sums are normalized like [`Duration::from_millis`] and saturate
+impl Add for Duration {
+    type Output = Duration;
+
+    fn add(self, other: Duration) -> Duration {
+        Duration::from_millis(self.as_millis().saturating_add(other.as_millis()))
+    }
+}
+
+impl Sum for Duration {
+    fn sum<I: Iterator<Item = Duration>>(iter: I) -> Duration {
+        iter.fold(Duration::from_millis(0), Add::add)
+    }
+}
+
+impl TimeUnit {
+    /// Milliseconds in one unit
+    pub fn millis(self) -> u64 {
+        match self {
+            TimeUnit::Milliseconds => 1,
+            TimeUnit::Seconds => 1_000,
+            TimeUnit::Minutes => 60_000,
+            TimeUnit::Hours => 3_600_000,
+        }
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            TimeUnit::Milliseconds => "ms",
+            TimeUnit::Seconds => "s",
+            TimeUnit::Minutes => "m",
+            TimeUnit::Hours => "h",
+        }
+    }
+}
+
+/// Error returned when a string is not a valid duration literal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDurationError(String);
+
+impl fmt::Display for ParseDurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid duration '{}': expected a whole number followed by ms, s, m or h", self.0)
+    }
+}
+
+impl std::error::Error for ParseDurationError {}
+
+impl FromStr for Duration {
+    type Err = ParseDurationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseDurationError(s.to_string());
+
+        let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (number, suffix) = s.split_at(digits);
+
+        let unit = match suffix {
+            "ms" => TimeUnit::Milliseconds,
+            "s" => TimeUnit::Seconds,
+            "m" => TimeUnit::Minutes,
+            "h" => TimeUnit::Hours,
+            _ => return Err(error()),
+        };
+        let value = number.parse().map_err(|_| error())?;
+
+        Ok(Duration { value, unit })
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.value, self.unit.suffix())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_units() {
+        let millis: Duration = "500ms".parse().unwrap();
+        assert_eq!(millis, Duration { value: 500, unit: TimeUnit::Milliseconds });
+        assert_eq!(millis.as_millis(), 500);
+
+        let minutes: Duration = "2m".parse().unwrap();
+        assert_eq!(minutes, Duration { value: 2, unit: TimeUnit::Minutes });
+        assert_eq!(minutes.as_millis(), 120_000);
+
+        assert_eq!("30s".parse::<Duration>().unwrap().as_millis(), 30_000);
+        assert_eq!("1h".parse::<Duration>().unwrap().as_millis(), 3_600_000);
+    }
+
+    #[test]
+    fn test_reject_invalid_durations() {
+        for input in ["30x", "s", "", "-5s", "1.5s", "10 s", "99999999999999999999s"] {
+            assert!(input.parse::<Duration>().is_err(), "accepted {:?}", input);
+        }
+        assert_eq!(
+            "30x".parse::<Duration>().unwrap_err().to_string(),
+            "Invalid duration '30x': expected a whole number followed by ms, s, m or h"
+        );
+    }
+
+    #[test]
+    fn test_display_round_trips_and_compares() {
+        let duration: Duration = "90s".parse().unwrap();
+        assert_eq!(duration.to_string(), "90s");
+        assert!(duration.as_millis() > "1m".parse::<Duration>().unwrap().as_millis());
+        assert_eq!(Duration { value: u64::MAX, unit: TimeUnit::Hours }.as_millis(), u64::MAX);
+    }
+
+    #[test]
+    fn test_arithmetic_normalizes_units() {
+        let duration = |s: &str| s.parse::<Duration>().unwrap();
+
+        assert_eq!(duration("30s").saturating_mul(4), duration("2m"));
+        assert_eq!(duration("30s") + duration("500ms"), duration("30500ms"));
+        assert_eq!(duration("45m") + duration("15m"), duration("1h"));
+        assert_eq!(duration("1h").saturating_mul(0), duration("0ms"));
+        assert_eq!(Duration::from_millis(90_000).to_string(), "90s");
+        assert_eq!([duration("1m"), duration("30s")].into_iter().sum::<Duration>(), duration("90s"));
+        assert_eq!(duration("1h").saturating_mul(u64::MAX).as_millis(), u64::MAX);
+    }
+}
@@ -1,5 +1,8 @@
 // Utility functions and helper modules
 
+pub mod color;
+pub mod distance;
+pub mod duration;
 pub mod formatting;
 
 // Common utilities for the compiler
@@ -0,0 +1,127 @@
+// Watch mode - rebuild whenever a source file changes
+use notify::{RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// How long the files must stay quiet before a rebuild
+///
+/// Editors often save in several steps (truncate, write, rename), each of
+/// which is a separate event.
+pub const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Collapses bursts of events into one, firing once none has arrived for
+/// the debounce delay
+#[derive(Debug, Clone)]
+pub struct Debouncer {
+    delay: Duration,
+    deadline: Option<Instant>,
+}
+
+impl Debouncer {
+    pub fn new(delay: Duration) -> Self {
+        Self { delay, deadline: None }
+    }
+
+    /// Record an event at `now`, restarting the quiet period
+    pub fn event(&mut self, now: Instant) {
+        self.deadline = Some(now + self.delay);
+    }
+
+    /// Whether the quiet period after the last event has passed at `now`;
+    /// returns `true` once per burst
+    pub fn ready(&mut self, now: Instant) -> bool {
+        match self.deadline {
+            Some(deadline) if deadline <= now => {
+                self.deadline = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Time left at `now` until a pending burst is ready, if there is one
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.deadline.map(|deadline| deadline.saturating_duration_since(now))
+    }
+}
+
+/// Run `build` now and again after every change to the files it returns,
+/// until the watcher fails
+///
+/// `build` reports the files the result depends on, such as the input and
+/// its includes, so the watched set follows the sources as they change.
+/// Their directories are watched rather than the files themselves, so a
+/// file replaced on save is still seen.
+pub fn watch(mut build: impl FnMut() -> Vec<PathBuf>) -> anyhow::Result<()> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    let mut debouncer = Debouncer::new(DEBOUNCE);
+
+    let mut files = absolute(build());
+    let mut dirs = BTreeSet::new();
+    loop {
+        let wanted: BTreeSet<PathBuf> = files.iter().filter_map(|file| file.parent().map(Path::to_path_buf)).collect();
+        for dir in dirs.difference(&wanted) {
+            watcher.unwatch(dir)?;
+        }
+        for dir in wanted.difference(&dirs) {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+        dirs = wanted;
+
+        loop {
+            let timeout = debouncer.remaining(Instant::now()).unwrap_or(Duration::from_secs(3600));
+            match receiver.recv_timeout(timeout) {
+                Ok(event) => {
+                    if event?.paths.iter().any(|path| files.contains(path)) {
+                        debouncer.event(Instant::now());
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+            if debouncer.ready(Instant::now()) {
+                break;
+            }
+        }
+        files = absolute(build());
+    }
+}
+
+/// Absolute forms of `paths`, which is how the watcher reports them
+fn absolute(paths: Vec<PathBuf>) -> BTreeSet<PathBuf> {
+    paths.into_iter()
+        .map(|path| std::path::absolute(&path).unwrap_or(path))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debouncer_fires_once_after_a_quiet_period() {
+        let start = Instant::now();
+        let ms = |n: u64| start + Duration::from_millis(n);
+        let mut debouncer = Debouncer::new(Duration::from_millis(100));
+
+        assert!(!debouncer.ready(ms(0)));
+        assert_eq!(debouncer.remaining(ms(0)), None);
+
+        // A burst of events keeps pushing the deadline back
+        debouncer.event(ms(0));
+        debouncer.event(ms(40));
+        debouncer.event(ms(80));
+        assert!(!debouncer.ready(ms(150)));
+        assert_eq!(debouncer.remaining(ms(150)), Some(Duration::from_millis(30)));
+
+        assert!(debouncer.ready(ms(180)));
+        assert!(!debouncer.ready(ms(500)));
+
+        debouncer.event(ms(600));
+        assert_eq!(debouncer.remaining(ms(800)), Some(Duration::ZERO));
+        assert!(debouncer.ready(ms(800)));
+    }
+}
@@ -0,0 +1,419 @@
+use navilang::lexer::tokens::Token;
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn navilang(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_navilang"))
+        .args(args)
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("failed to run navilang binary")
+}
+
+fn write(dir: &Path, name: &str, content: &str) -> String {
+    let path = dir.join(name);
+    std::fs::write(&path, content).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+#[test]
+fn test_check_valid_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "valid.navi", r#"CONTEXT Auth {
+    VAR User:Entity
+    VAR AuthService:Service
+    VAR Dashboard
+    User CALLS AuthService
+    IF User IS "valid" THEN User GOES TO Dashboard
+    CONTEXT Nested { User -> Dashboard }
+}"#);
+
+    let output = navilang(&["check", "-f", &file]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "OK: 2 contexts, 7 statements\n");
+
+    let quiet = navilang(&["check", "-f", &file, "--quiet"]);
+    assert!(quiet.status.success());
+    assert!(quiet.stdout.is_empty());
+}
+
+#[test]
+fn test_check_invalid_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "invalid.navi", "CONTEXT Auth {\n    VAR User\n    User GOES TO Dashbord\n}");
+
+    let output = navilang(&["check", "-f", &file]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "OK: 1 contexts, 2 statements\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Warning: Unknown identifier: Dashbord"));
+
+    let output = navilang(&["check", "-f", &file, "--strict"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Unknown identifier: Dashbord"));
+    assert!(!stderr.contains("Warning"), "{}", stderr);
+    assert!(stderr.contains("User GOES TO Dashbord"), "missing source context:\n{}", stderr);
+}
+
+#[test]
+fn test_check_reports_syntax_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "broken.navi", "CONTEXT Auth {");
+
+    let output = navilang(&["check", "-f", &file]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Syntax error"));
+}
+
+#[test]
+fn test_check_recursive_summarizes_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let dir_path = dir.path().to_string_lossy().to_string();
+    write(dir.path(), "good.navi", "CONTEXT Good { VAR User }");
+    std::fs::create_dir(dir.path().join("flows")).unwrap();
+    write(&dir.path().join("flows"), "bad.navi", "CONTEXT Bad {\n    VAR User\n    User -> Dashbord\n    Admin -> Home\n}");
+
+    let output = navilang(&["check", "-f", &dir_path, "--recursive", "--strict"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Unknown identifier: Dashbord"));
+    assert!(stderr.contains("3 errors in 1 of 2 files"), "{}", stderr);
+
+    std::fs::remove_file(dir.path().join("flows/bad.navi")).unwrap();
+    let output = navilang(&["check", "-f", &dir_path, "-r"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "OK: 1 file");
+}
+
+#[test]
+fn test_check_recursive_counts_a_single_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let dir_path = dir.path().to_string_lossy().to_string();
+    write(dir.path(), "bad.navi", "CONTEXT Bad {");
+
+    let output = navilang(&["check", "-f", &dir_path, "-r"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 error in 1 of 1 file"));
+}
+
+#[test]
+fn test_check_recursive_emits_one_sarif_log() {
+    let dir = tempfile::tempdir().unwrap();
+    let dir_path = dir.path().to_string_lossy().to_string();
+    let warned = write(dir.path(), "a.navi", "CONTEXT A {\n    VAR Begin\n    Begin -> Dashbord\n}");
+    let broken = write(dir.path(), "b.navi", "CONTEXT B {");
+
+    let output = navilang(&["check", "-f", &dir_path, "-r", "--error-format", "sarif"]);
+    assert!(!output.status.success());
+    // The whole of stderr is the log, with no summary after it
+    let sarif: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(sarif["runs"].as_array().unwrap().len(), 1);
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    let found: Vec<_> = results.iter()
+        .map(|result| (
+            result["level"].as_str().unwrap(),
+            result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"].as_str().unwrap(),
+        ))
+        .collect();
+    assert_eq!(found, [("warning", warned.as_str()), ("error", broken.as_str())]);
+
+    let output = navilang(&["check", "-f", &dir_path, "-r", "--error-format", "json"]);
+    assert!(!output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    let files: Vec<_> = json.as_array().unwrap().iter().map(|item| item["file"].as_str().unwrap()).collect();
+    assert_eq!(files, [warned.as_str(), broken.as_str()]);
+}
+
+#[test]
+fn test_check_strict_rejects_forward_references() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "forward.navi", "CONTEXT Auth {\n    User -> Home\n    VAR User\n    VAR Home\n}");
+
+    assert!(navilang(&["check", "-f", &file]).status.success());
+
+    let output = navilang(&["check", "-f", &file, "--strict"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("'Home' is used before its declaration"), "{}", stderr);
+}
+
+#[test]
+fn test_check_timeout_budget_warns() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "slow.navi", "CONTEXT Checkout {
+    VAR User
+    VAR Payment:Service
+    User CALLS Payment RETRY 3 TIMEOUT 30s
+    User CALLS Payment RETRY 3 TIMEOUT 30s
+}");
+
+    let output = navilang(&["check", "-f", &file, "--timeout-budget", "2m"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("may wait up to 3m on interactions"));
+
+    assert!(navilang(&["check", "-f", &file]).stderr.is_empty());
+    assert!(!navilang(&["check", "-f", &file, "--timeout-budget", "2x"]).status.success());
+}
+
+#[test]
+fn test_metrics_table_and_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "metrics.navi", "CONTEXT Auth { VAR User  IF User IS 1 THEN LOOP { User -> Home } }");
+
+    let output = navilang(&["metrics", "-f", &file]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Conditionals            1\n"), "{}", stdout);
+    assert!(stdout.contains("Max nesting depth       2\n"), "{}", stdout);
+
+    let output = navilang(&["metrics", "-f", &file, "--json"]);
+    let metrics: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(metrics["flows"], 1);
+    assert_eq!(metrics["complexity"], 3);
+}
+
+#[test]
+fn test_tokens_json_round_trips() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "tokens.navi", "CONTEXT Auth { VAR User:Entity } // done");
+
+    let output = navilang(&["tokens", "-f", &file, "--json"]);
+    assert!(output.status.success());
+
+    let tokens: Vec<Token> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(tokens, vec![
+        Token::Context,
+        Token::Identifier("Auth".to_string()),
+        Token::LeftBrace,
+        Token::Var,
+        Token::Identifier("User".to_string()),
+        Token::Colon,
+        Token::Entity,
+        Token::RightBrace,
+    ]);
+
+    let raw = navilang(&["tokens", "-f", &file, "--json", "--raw"]);
+    let raw_tokens: Vec<Token> = serde_json::from_slice(&raw.stdout).unwrap();
+    assert!(raw_tokens.contains(&Token::Whitespace));
+    assert_eq!(raw_tokens.last(), Some(&Token::Comment));
+}
+
+#[test]
+fn test_tokens_color_respects_no_color() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "tokens.navi", "CONTEXT Auth { VAR User:Entity }");
+
+    let plain = navilang(&["tokens", "-f", &file, "--color"]);
+    assert!(plain.status.success());
+    assert!(!plain.stdout.contains(&0x1b));
+    assert_eq!(plain.stdout, navilang(&["tokens", "-f", &file]).stdout);
+
+    let colored = Command::new(env!("CARGO_BIN_EXE_navilang"))
+        .args(["tokens", "-f", &file, "--color"])
+        .env_remove("NO_COLOR")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&colored.stdout);
+    assert!(stdout.contains("\x1b[1;35mContext"));
+    assert!(stdout.contains("\x1b[36mEntity"));
+}
+
+#[test]
+fn test_tokens_text_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "tokens.navi", "VAR \"User\"");
+
+    let output = navilang(&["tokens", "-f", &file]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("1:1 "));
+    assert!(lines[0].contains("Var"));
+    assert!(lines[1].starts_with("1:5 "));
+    assert!(lines[1].ends_with(r#""\"User\"""#));
+}
+
+#[test]
+fn test_json_error_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "broken.navi", "CONTEXT Test VAR User }");
+
+    let output = navilang(&["check", "-f", &file, "--error-format", "json"]);
+    assert!(!output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(json["code"], "syntax::invalid");
+    assert_eq!(json["spans"][0]["offset"], 13);
+}
+
+#[test]
+fn test_max_errors_caps_reported_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let broken: String = (0..5).map(|i| format!("CONTEXT Broken{} VAR User }}\n", i)).collect();
+    let file = write(dir.path(), "broken.navi", &broken);
+
+    let output = navilang(&["check", "-f", &file, "--error-format", "json", "--max-errors", "2"]);
+    assert!(!output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    let errors = json.as_array().unwrap();
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[2]["code"], "compilation::too_many_errors");
+    assert_eq!(errors[2]["message"], "Too many errors, stopped after 2");
+
+    let output = navilang(&["check", "-f", &file, "--error-format", "json", "--max-errors", "0"]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 5);
+
+    // Semantic errors are capped the same way
+    let undeclared: String = (0..5).map(|i| format!("    User -> Missing{}\n", i)).collect();
+    let file = write(dir.path(), "undeclared.navi", &format!("CONTEXT Auth {{\n    VAR User\n{}}}", undeclared));
+    let output = navilang(&["check", "-f", &file, "--strict", "--error-format", "json", "--max-errors", "2"]);
+    assert!(!output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    let codes: Vec<&str> = json.as_array().unwrap().iter().map(|error| error["code"].as_str().unwrap()).collect();
+    assert_eq!(codes, vec![
+        "semantic::unknown_identifier",
+        "semantic::unknown_identifier",
+        "compilation::too_many_errors",
+    ]);
+}
+
+#[test]
+fn test_sarif_error_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "broken.navi", "CONTEXT Test VAR User }");
+
+    let output = navilang(&["check", "-f", &file, "--error-format", "sarif"]);
+    assert!(!output.status.success());
+
+    let sarif: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(sarif["version"], "2.1.0");
+    let result = &sarif["runs"][0]["results"][0];
+    assert_eq!(result["ruleId"], "syntax::invalid");
+    assert_eq!(result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], file.as_str());
+    assert_eq!(result["locations"][0]["physicalLocation"]["region"]["startColumn"], 14);
+}
+
+#[test]
+fn test_unreachable_state_is_a_warning() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "warn.navi", "CONTEXT Flow {
+    VAR Begin
+    VAR Done
+    VAR Orphan
+    Begin -> Done
+    Orphan -> Done
+}");
+
+    let output = navilang(&["check", "-f", &file]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("OK: "));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Warning: State 'Orphan' is unreachable"));
+    assert!(!stderr.contains("Flow validation error"));
+
+    let denied = navilang(&["check", "-f", &file, "--deny-warnings"]);
+    assert!(!denied.status.success());
+    assert!(denied.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&denied.stderr).contains("State 'Orphan' is unreachable"));
+}
+
+#[test]
+fn test_fmt_prints_or_rewrites() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "messy.navi", "context Auth{var User  User->Home}");
+    let expected = "CONTEXT Auth {\n    VAR User\n    User GOES TO Home\n}\n";
+
+    let output = navilang(&["fmt", "-f", &file]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), expected);
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "context Auth{var User  User->Home}");
+
+    let output = navilang(&["fmt", "-f", &file, "--write"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), expected);
+}
+
+#[test]
+fn test_fmt_keeps_includes() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "entities.navi", "CONTEXT Entities {\n    VAR User\n}\n");
+    let source = "INCLUDE \"entities.navi\"\n\nCONTEXT Auth {\n    User GOES TO Home\n}\n";
+    let file = write(dir.path(), "main.navi", source);
+
+    let output = navilang(&["fmt", "-f", &file, "--write"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), source);
+}
+
+#[test]
+fn test_diff_summarizes_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let old = write(dir.path(), "old.navi", "CONTEXT Auth {\n    VAR User\n    VAR Token\n    User -> Login\n}");
+    let new = write(dir.path(), "new.navi", "CONTEXT Auth {\n    VAR User\n    User -> Login\n    Login -> Home\n}");
+
+    let output = navilang(&["diff", "--old", &old, "--new", &new]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "- variable Auth: Token\n+ flow Auth: Login -> Home\n");
+
+    let output = navilang(&["diff", "--old", &old, "--new", &old]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "No changes\n");
+}
+
+#[test]
+fn test_parse_with_locations() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "shared.navi", "CONTEXT Shared {\n    VAR Session\n}\n");
+    let file = write(dir.path(), "main.navi", "INCLUDE \"shared.navi\"\nCONTEXT Main {\n    VAR User\n}");
+
+    let output = navilang(&["parse", "-f", &file, "--with-locations"]);
+    assert!(output.status.success());
+    let ast: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let location = &ast["contexts"][1]["statements"][0]["location"];
+    assert!(location["path"].as_str().unwrap().ends_with("main.navi"));
+    assert_eq!(location["line"], 3);
+
+    let output = navilang(&["parse", "-f", &file]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("\"location\""));
+}
+
+#[test]
+fn test_generate_prints_to_stdout_without_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "flow.navi", "CONTEXT Auth {\n    VAR User\n    User -> Home\n}");
+
+    let output = navilang(&["generate", "-f", &file, "--format", "mermaid"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("flowchart TD\n"));
+    assert!(stdout.contains("User --> Home"));
+
+    let target = dir.path().join("flow.mmd");
+    let output = navilang(&["generate", "-f", &file, "--format", "mermaid", "-o", target.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(std::fs::read_to_string(&target).unwrap(), stdout);
+}
+
+#[test]
+fn test_generate_mermaid_from_partially_broken_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = write(dir.path(), "partial.navi", "CONTEXT Auth {\n    User -> Home\n}\nCONTEXT Orders VAR Order }\n");
+
+    let output = navilang(&["generate", "-f", &file, "--format", "mermaid"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("%% 1 context failed to parse\nflowchart TD\n"));
+    assert!(stdout.contains("subgraph ctx_1 [\"Auth\"]"));
+    assert!(stdout.contains("User --> Home"));
+    assert!(!stdout.contains("Orders"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Expected '{'"));
+
+    // JSON has no comments to carry the banner, so nothing is generated
+    let output = navilang(&["generate", "-f", &file, "--format", "json"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
@@ -0,0 +1,82 @@
+use navilang::analyzer::SemanticAnalyzer;
+use navilang::lexer::tokens::Token;
+
+#[test]
+fn test_compile_string_exposes_tokens_and_contexts() {
+    let result = navilang::compile_string(
+        "// Sign-in flow\nCONTEXT Auth { VAR User User -> Home }",
+        "auth.navi",
+    )
+    .unwrap();
+
+    let kinds: Vec<&Token> = result.tokens().iter().map(|t| &t.token).collect();
+    assert_eq!(kinds.first(), Some(&&Token::Context));
+    assert_eq!(kinds.last(), Some(&&Token::RightBrace));
+    assert!(!kinds.contains(&&Token::Whitespace));
+    assert_eq!(result.tokens()[0].leading_comments, vec!["Sign-in flow"]);
+
+    assert_eq!(result.contexts().len(), 1);
+    assert_eq!(result.contexts()[0].name, "Auth");
+    assert_eq!(result.contexts()[0].statements.len(), 2);
+}
+
+#[test]
+fn test_compile_string_reports_syntax_errors() {
+    let error = navilang::compile_string("CONTEXT Auth {", "broken.navi").unwrap_err();
+    assert!(error.to_string().contains("Syntax error"));
+}
+
+#[test]
+fn test_compile_dir_reports_each_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let nested = dir.path().join("flows");
+    std::fs::create_dir(&nested).unwrap();
+    std::fs::write(dir.path().join("good.navi"), "CONTEXT Good { VAR User }").unwrap();
+    std::fs::write(nested.join("bad.navi"), "CONTEXT Bad {").unwrap();
+    std::fs::write(dir.path().join("notes.txt"), "CONTEXT Ignored {").unwrap();
+
+    let results = navilang::compile_dir(dir.path());
+    let paths: Vec<_> = results.iter().map(|(path, _)| path.strip_prefix(dir.path()).unwrap()).collect();
+    assert_eq!(paths, vec![std::path::Path::new("flows/bad.navi"), std::path::Path::new("good.navi")]);
+
+    assert!(results[0].1.as_ref().unwrap_err().to_string().contains("Syntax error"));
+    assert_eq!(results[1].1.as_ref().unwrap().contexts()[0].name, "Good");
+}
+
+#[test]
+fn test_missing_file_error_names_the_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing.navi");
+
+    let error = navilang::compile_file(&missing).unwrap_err();
+    let navilang::NaviLangError::FileError { path, source } = &error else {
+        panic!("Expected FileError, got {:?}", error);
+    };
+    assert_eq!(path, &missing.to_string_lossy());
+    assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+    assert!(error.to_string().contains(&*missing.to_string_lossy()));
+
+    // A missing include is reported with its own path
+    let main = dir.path().join("main.navi");
+    std::fs::write(&main, "INCLUDE \"gone.navi\"\nCONTEXT Main {}").unwrap();
+    let error = navilang::compile_file(&main).unwrap_err();
+    assert!(matches!(&error, navilang::NaviLangError::FileError { path, .. } if path.ends_with("gone.navi")));
+}
+
+#[test]
+fn test_examples_compile() {
+    let examples = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let results = navilang::compile_dir(&examples);
+    assert!(!results.is_empty());
+
+    for (path, result) in results {
+        let result = match result {
+            Ok(result) => result,
+            Err(error) => panic!("{} failed to compile: {:?}", path.display(), miette::Report::new(error)),
+        };
+        // Undeclared names are warnings by default, so the examples may use them
+        if let Err(error) = SemanticAnalyzer::new().analyze(&result.ast, &result.source.content) {
+            panic!("{} failed to check: {:?}", path.display(), miette::Report::new(error));
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use navilang::generator::json::to_json;
+use navilang::parser::ast::Program;
+use std::io::Write;
+
+const SOURCE: &str = r#"CONTEXT "User Authentication" {
+    VAR User:Entity
+    VAR AuthService:Service
+    User CALLS AuthService
+    AuthService RETURNS "token"
+    IF User IS "invalid" THEN User GOES TO LoginPage
+    CONTEXT Session { User -> Dashboard }
+}"#;
+
+fn compile(source: &str) -> Program {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(source.as_bytes()).unwrap();
+    navilang::compile_file(file.path()).unwrap().ast
+}
+
+#[test]
+fn test_program_json_round_trip() {
+    let program = compile(SOURCE);
+
+    for pretty in [true, false] {
+        let json = to_json(&program, pretty).unwrap();
+        let decoded: Program = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, program);
+    }
+}
+
+#[test]
+fn test_program_json_shape() {
+    let program = compile(SOURCE);
+    let value: serde_json::Value = serde_json::from_str(&to_json(&program, false).unwrap()).unwrap();
+
+    assert_eq!(value["contexts"][0]["name"], "User Authentication");
+    assert_eq!(value["contexts"][0]["children"][0]["name"], "Session");
+}